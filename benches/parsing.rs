@@ -0,0 +1,57 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use nested_language_compiler::parsing::parse_string;
+
+/// Names can't contain digits, so numbers are spelled out with letters instead.
+fn name_for(mut index: usize) -> String {
+    let mut name = String::new();
+    loop {
+        name.push((b'a' + (index % 26) as u8) as char);
+        index /= 26;
+        if index == 0 {
+            break name;
+        }
+    }
+}
+
+/// Builds a large file, much like the generated ones we expect to be loading.
+fn generate_source(num_structs: usize) -> String {
+    let mut source = String::new();
+
+    for index in 0..num_structs {
+        let name = name_for(index);
+        source.push_str(&format!(
+            "struct Struct{name} {{ value: i32, other: bool }}
+impl Self {{
+    met method_{name}(&self, a: i32, b: i32) -> i32 {{
+        let c = a + b * 2
+        if c > 10 {{ c }} else {{ a }}
+    }}
+
+    get getter_{name}(&self) -> i32 {{}}
+    get default_{name}:default;
+    set setter_{name}(value: i32) {{}}
+}}
+
+fn function_{name}(x: i32) -> i32 {{
+    let y: i32 = x * 3
+    y
+}}
+
+",
+            name = name
+        ));
+    }
+
+    source
+}
+
+fn parse_large_file(c: &mut Criterion) {
+    let source = generate_source(500);
+
+    c.bench_function("parse 500 structs", |b| {
+        b.iter(|| parse_string(black_box(&source), "generated.nl").unwrap())
+    });
+}
+
+criterion_group!(benches, parse_large_file);
+criterion_main!(benches);
@@ -0,0 +1,742 @@
+use crate::parsing::*;
+
+use std::collections::HashMap;
+
+// All tests are kept in their own module.
+#[cfg(test)]
+mod tests;
+
+// Type checking is still narrow. Only constants, variables, operators, and the type annotations of `let`s are checked.
+// Anything we can't work out the type of is assumed to be fine.
+
+#[derive(Debug, PartialEq)]
+pub enum TypeError<'a> {
+    MismatchedAssignment(&'a str, NLType<'a>, NLType<'a>), // The variable, the type it was given, and the type of its value.
+    MismatchedOperands(NLType<'a>, NLType<'a>), // The types on either side of an operator.
+    VariableUndefined(&'a str),                 // String is the name of the variable.
+    MismatchedReturn(&'a str, NLType<'a>, NLType<'a>), // The function, the type it returns, and the type its body ends in.
+    MissingReturnValue(&'a str, NLType<'a>), // The function, and the type it should have returned.
+    UnexpectedReturnValue(&'a str, NLType<'a>), // The function, and the type of the value its body ends in.
+    FieldUndefined(&'a str, &'a str),           // The struct, and the field it doesn't have.
+    MissingTraitMethod(&'a str, &'a str, &'a str), // The struct, the trait, and the required method it didn't provide.
+    LabelUndefined(&'a str), // A break named a label that none of the loops around it have.
+    MismatchedBreakTypes(NLType<'a>, NLType<'a>), // The values given to two breaks out of the same loop.
+    ArgumentAfterDefault(&'a str, &'a str), // The function, and the argument without a default that came after one with a default.
+}
+
+/// Anything that can go wrong between reading a file and having an AST that's ready to be compiled.
+#[derive(Debug)]
+pub enum AnalysisError<'a> {
+    Parse(ParseError),         // The file couldn't be read at all.
+    DuplicateName(ParseError), // A name was declared more than once.
+    Type(TypeError<'a>),
+}
+
+/// Parses the file, then runs every check over it. The file is only given back if nothing was wrong with it.
+/// Parsing has to succeed before the rest can run, but the errors of every check after that are gathered together.
+pub fn analyze_string<'a>(
+    input: &'a str,
+    file_name: &str,
+) -> Result<NLFile<'a>, Vec<AnalysisError<'a>>> {
+    let file = parse_string(input, file_name).map_err(|error| vec![AnalysisError::Parse(error)])?;
+
+    let mut errors = Vec::new();
+
+    if let Err(duplicates) = validate_unique_names(&file) {
+        errors.extend(duplicates.into_iter().map(AnalysisError::DuplicateName));
+    }
+
+    if let Err(type_errors) = check_types(&file) {
+        errors.extend(type_errors.into_iter().map(AnalysisError::Type));
+    }
+
+    if errors.is_empty() {
+        Ok(file)
+    } else {
+        Err(errors)
+    }
+}
+
+// Literals that were written without a type could have been meant as other types of their kind, so we're lenient
+// with them.
+#[derive(Clone, Copy)]
+enum Literal {
+    Integer(i128), // Only the integer types that can hold the value will do.
+    Float,
+}
+
+// The type of an operation, if we could work it out.
+struct CheckedType<'a> {
+    nl_type: NLType<'a>,
+    literal: Option<Literal>,
+}
+
+impl<'a> CheckedType<'a> {
+    fn new(nl_type: NLType<'a>) -> CheckedType<'a> {
+        CheckedType {
+            nl_type,
+            literal: None,
+        }
+    }
+
+    fn fits(&self, nl_type: &NLType) -> bool {
+        if self.nl_type == *nl_type {
+            return true;
+        }
+
+        match self.literal {
+            Some(Literal::Integer(value)) => nl_type.holds_integer(value),
+            Some(Literal::Float) => nl_type.is_float(),
+            None => false,
+        }
+    }
+}
+
+/// The variables that can be seen from the operation being checked. A variable's type is None when we couldn't work
+/// it out, but it still counts as declared.
+#[derive(Debug, Clone)]
+pub struct TypeScope<'a> {
+    // The innermost scope is last.
+    scopes: Vec<HashMap<&'a str, Option<NLType<'a>>>>,
+}
+
+impl<'a> TypeScope<'a> {
+    pub fn new() -> TypeScope<'a> {
+        TypeScope {
+            scopes: vec![HashMap::new()],
+        }
+    }
+
+    pub fn push_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    pub fn pop_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    /// Declaring a variable that already exists shadows it.
+    pub fn declare_variable(&mut self, name: &'a str, var_type: Option<NLType<'a>>) {
+        let scope = self
+            .scopes
+            .last_mut()
+            .expect("There should always be at least one scope.");
+        scope.insert(name, var_type);
+    }
+
+    pub fn get_variable(&self, name: &str) -> Option<&Option<NLType<'a>>> {
+        // Names can reach into a variable, such as `point.x`, so we only look up the variable itself.
+        let name = name.split('.').next().unwrap_or(name);
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+}
+
+// The struct that `Self` refers to while checking the methods of its implementations.
+struct ImplContext<'a> {
+    name: &'a str,
+    fields: HashMap<&'a str, NLType<'a>>,
+}
+
+impl<'a> ImplContext<'a> {
+    fn new(nl_struct: &NLStruct<'a>) -> ImplContext<'a> {
+        let fields = nl_struct
+            .get_variables()
+            .iter()
+            .map(|variable| (variable.get_name(), variable.get_type().clone()))
+            .collect();
+
+        ImplContext {
+            name: nl_struct.get_name(),
+            fields,
+        }
+    }
+}
+
+impl<'a> Default for TypeScope<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Works out the type of an operation as best it can, without reporting any errors. Gives None for anything the type
+/// checker can't work out the type of, such as undeclared variables or operands that don't match.
+pub fn operation_type<'a>(
+    operation: &NLOperation<'a>,
+    scope: &TypeScope<'a>,
+) -> Option<NLType<'a>> {
+    // The operation could declare variables of its own, so it gets a copy of the scope to do that in.
+    let mut checker = TypeChecker {
+        scope: scope.clone(),
+        errors: Vec::new(),
+        impl_context: None,
+        loops: Vec::new(),
+    };
+
+    let result = checker.check_operation(operation)?;
+    if checker.errors.is_empty() {
+        Some(result.nl_type)
+    } else {
+        None
+    }
+}
+
+/// Checks the bodies of every function and method in the file, and returns every error found.
+pub fn check_types<'a>(file: &NLFile<'a>) -> Result<(), Vec<TypeError<'a>>> {
+    let mut checker = TypeChecker {
+        scope: TypeScope::new(),
+        errors: Vec::new(),
+        impl_context: None,
+        loops: Vec::new(),
+    };
+
+    for function in file.get_functions() {
+        checker.check_function(function);
+    }
+
+    for nl_struct in file.get_structs() {
+        checker.impl_context = Some(ImplContext::new(nl_struct));
+        for implementation in nl_struct.get_implementations() {
+            checker.check_implementors(implementation.get_implementors());
+            checker.check_trait_implementation(file, nl_struct, implementation);
+        }
+        checker.impl_context = None;
+    }
+
+    for nl_trait in file.get_traits() {
+        checker.check_implementors(nl_trait.get_implementors());
+    }
+
+    if checker.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(checker.errors)
+    }
+}
+
+struct TypeChecker<'a> {
+    scope: TypeScope<'a>,
+    errors: Vec<TypeError<'a>>,
+
+    // Only set while checking the implementation of a struct.
+    impl_context: Option<ImplContext<'a>>,
+
+    // The loops around the operation being checked, innermost last. Each has its label, and the type of the value it
+    // was first broken out of with.
+    loops: Vec<(Option<&'a str>, Option<CheckedType<'a>>)>,
+}
+
+impl<'a> TypeChecker<'a> {
+    fn check_implementors(&mut self, implementors: &[NLImplementor<'a>]) {
+        for implementor in implementors {
+            if let NLImplementor::Method(method) = implementor {
+                self.check_function(method);
+            }
+        }
+    }
+
+    // An implementation of a trait has to give a body to every method the trait left abstract.
+    fn check_trait_implementation(
+        &mut self,
+        file: &NLFile<'a>,
+        nl_struct: &NLStruct<'a>,
+        implementation: &NLImplementation<'a>,
+    ) {
+        // An implementation without a `for` is named after the trait it implements, unless it's `impl Self`.
+        // TODO report implementations of traits that don't exist.
+        let trait_name = implementation
+            .get_trait_name()
+            .unwrap_or_else(|| implementation.get_name());
+        let nl_trait = match file.find_trait(trait_name) {
+            Some(nl_trait) => nl_trait,
+            None => return,
+        };
+
+        for required in nl_trait
+            .iter_methods()
+            .filter(|method| method.is_abstract())
+        {
+            let is_provided = implementation
+                .iter_methods()
+                .any(|method| method.get_name() == required.get_name() && !method.is_abstract());
+
+            if !is_provided {
+                self.errors.push(TypeError::MissingTraitMethod(
+                    nl_struct.get_name(),
+                    nl_trait.get_name(),
+                    required.get_name(),
+                ));
+            }
+        }
+    }
+
+    fn check_function(&mut self, function: &NLFunction<'a>) {
+        self.check_arguments(function);
+
+        if let Some(block) = function.get_block() {
+            self.scope.push_scope();
+            for argument in function.get_arguments() {
+                self.scope
+                    .declare_variable(argument.get_name(), Some(argument.get_type().clone()));
+            }
+
+            let result = self.check_block(block);
+            self.check_function_result(function, block, result);
+            self.scope.pop_scope();
+        }
+    }
+
+    // Calls can only leave arguments off of the end, so once one argument has a default, every one after it needs one.
+    fn check_arguments(&mut self, function: &NLFunction<'a>) {
+        let mut has_default = false;
+
+        for argument in function.get_arguments() {
+            match argument.get_default() {
+                Some(default) => {
+                    has_default = true;
+
+                    if let Some(default_type) = self.check_operation(default) {
+                        if !default_type.fits(argument.get_type()) {
+                            self.errors.push(TypeError::MismatchedAssignment(
+                                argument.get_name(),
+                                argument.get_type().clone(),
+                                default_type.nl_type,
+                            ));
+                        }
+                    }
+                }
+                None if has_default => self.errors.push(TypeError::ArgumentAfterDefault(
+                    function.get_name(),
+                    argument.get_name(),
+                )),
+                None => {}
+            }
+        }
+    }
+
+    // The value a function's body ends in is what it returns, unless the body always returns before getting there.
+    fn check_function_result(
+        &mut self,
+        function: &NLFunction<'a>,
+        block: &NLBlock<'a>,
+        result: Option<CheckedType<'a>>,
+    ) {
+        if block_always_diverges(block) {
+            return;
+        }
+
+        let name = function.get_name();
+        let has_value = match block.get_result() {
+            // Assignments never produce a value, even without a semicolon after them.
+            Some(NLOperation::Assign(_)) | Some(NLOperation::Unit) | None => false,
+            Some(_) => true,
+        };
+
+        match (function.get_return_type(), has_value) {
+            (NLType::None, false) => {}
+            (NLType::None, true) => {
+                if let Some(result) = result {
+                    self.errors
+                        .push(TypeError::UnexpectedReturnValue(name, result.nl_type));
+                }
+            }
+            (return_type, false) => {
+                self.errors
+                    .push(TypeError::MissingReturnValue(name, return_type.clone()));
+            }
+            (return_type, true) => {
+                if let Some(result) = result {
+                    if !result.fits(return_type) {
+                        self.errors.push(TypeError::MismatchedReturn(
+                            name,
+                            return_type.clone(),
+                            result.nl_type,
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    // Every block gets its own scope, so variables declared in it are gone once it ends. The type of a block is the
+    // type of its result, if it has one.
+    fn check_block(&mut self, block: &NLBlock<'a>) -> Option<CheckedType<'a>> {
+        self.scope.push_scope();
+
+        let mut result = None;
+        for operation in block.get_operations() {
+            result = self.check_operation(operation);
+        }
+
+        self.scope.pop_scope();
+
+        block.get_result().and(result)
+    }
+
+    // Only a `loop` can have a value, which it gets from the breaks that exit it.
+    fn check_loop(
+        &mut self,
+        label: Option<&'a str>,
+        operation: &NLOperation<'a>,
+    ) -> Option<CheckedType<'a>> {
+        self.loops.push((label, None));
+
+        match operation {
+            NLOperation::Loop(block) => {
+                self.check_block(block);
+            }
+            NLOperation::WhileLoop(while_loop) => {
+                self.check_operation(while_loop.get_condition());
+                self.check_block(while_loop.get_block());
+            }
+            NLOperation::WhileLet(while_let) => {
+                self.check_operation(while_let.get_value());
+
+                self.scope.push_scope();
+                for variable in while_let.get_variables() {
+                    self.scope.declare_variable(variable, None);
+                }
+                self.check_block(while_let.get_block());
+                self.scope.pop_scope();
+            }
+            NLOperation::ForLoop(for_loop) => {
+                self.check_operation(for_loop.get_iterator());
+
+                // The loop variable only exists inside of the loop.
+                self.scope.push_scope();
+                self.scope
+                    .declare_variable(for_loop.get_variable().get_name(), None);
+                self.check_block(for_loop.get_block());
+                self.scope.pop_scope();
+            }
+            _ => {
+                self.check_operation(operation);
+            }
+        }
+
+        match (self.loops.pop(), operation) {
+            (Some((_, break_type)), NLOperation::Loop(_)) => break_type,
+            _ => None,
+        }
+    }
+
+    fn check_operation(&mut self, operation: &NLOperation<'a>) -> Option<CheckedType<'a>> {
+        match operation {
+            NLOperation::Block(block) => self.check_block(block),
+            NLOperation::Constant(constant) => Self::constant_type(constant),
+            NLOperation::Assign(assignment) => {
+                self.check_assignment(assignment);
+                None
+            }
+            NLOperation::VariableAccess(variable) => {
+                match self.variable_type(variable.get_name()) {
+                    Ok(var_type) => var_type.map(CheckedType::new),
+                    Err(error) => {
+                        self.errors.push(error);
+                        None
+                    }
+                }
+            }
+            NLOperation::Tuple(operations) => {
+                for operation in operations {
+                    self.check_operation(operation);
+                }
+                None
+            }
+            NLOperation::Unit => Some(CheckedType::new(NLType::None)),
+            NLOperation::Operator(operator) => self.check_operator(operator),
+            NLOperation::If(if_statement) => {
+                self.check_operation(if_statement.get_condition());
+                self.check_block(if_statement.get_true_block());
+                if let Some(false_block) = if_statement.get_false_block() {
+                    self.check_block(false_block);
+                }
+                None
+            }
+            NLOperation::IfLet(if_let) => {
+                self.check_operation(if_let.get_value());
+
+                // The pattern's variables only exist in the true block.
+                self.scope.push_scope();
+                for variable in if_let.get_variables() {
+                    self.scope.declare_variable(variable, None);
+                }
+                self.check_block(if_let.get_true_block());
+                self.scope.pop_scope();
+
+                self.check_block(if_let.get_false_block());
+                None
+            }
+            NLOperation::Loop(_)
+            | NLOperation::WhileLoop(_)
+            | NLOperation::WhileLet(_)
+            | NLOperation::ForLoop(_) => self.check_loop(None, operation),
+            NLOperation::Labeled { label, operation } => self.check_loop(Some(label), operation),
+            NLOperation::Break { label, value } => {
+                let value_type = value.as_ref().and_then(|value| self.check_operation(value));
+
+                let target = match label {
+                    Some(label) => self
+                        .loops
+                        .iter_mut()
+                        .rev()
+                        .find(|(loop_label, _)| loop_label == &Some(*label)),
+                    None => self.loops.last_mut(),
+                };
+
+                match (target, label) {
+                    (Some((_, break_type)), _) => match (break_type.as_ref(), value_type) {
+                        (None, value_type) => *break_type = value_type,
+                        (Some(first), Some(value_type)) if !value_type.fits(&first.nl_type) => {
+                            // A literal given to the first break takes the type of a later one.
+                            if first.fits(&value_type.nl_type) {
+                                *break_type = Some(value_type);
+                            } else {
+                                self.errors.push(TypeError::MismatchedBreakTypes(
+                                    first.nl_type.clone(),
+                                    value_type.nl_type,
+                                ));
+                            }
+                        }
+                        _ => {}
+                    },
+                    (None, Some(label)) => self.errors.push(TypeError::LabelUndefined(label)),
+                    // TODO report breaks that aren't inside of a loop.
+                    (None, None) => {}
+                }
+
+                None
+            }
+            NLOperation::FunctionCall(function_call) => {
+                // TODO check the arguments against the function being called.
+                for argument in function_call.get_arguments() {
+                    self.check_operation(argument);
+                }
+                None
+            }
+            NLOperation::ArrayLiteral(elements) => {
+                for element in elements {
+                    self.check_operation(element);
+                }
+                None
+            }
+            NLOperation::ArrayRepeat { value, count } => {
+                self.check_operation(value);
+                self.check_operation(count);
+                None
+            }
+            NLOperation::StructLiteral(literal) => {
+                // TODO check the fields against the struct being built.
+                for (_, value) in literal.get_fields() {
+                    self.check_operation(value);
+                }
+                if let Some(base) = literal.get_base() {
+                    self.check_operation(base);
+                }
+                Some(CheckedType::new(NLType::OwnedStruct(literal.get_struct())))
+            }
+            NLOperation::Cast { value, target } => {
+                self.check_operation(value);
+                Some(CheckedType::new(target.clone()))
+            }
+            NLOperation::Return(Some(value)) => {
+                self.check_operation(value);
+                None
+            }
+            _ => None,
+        }
+    }
+
+    // The type of a variable, or of a field inside of one, such as `self.value`. Fields can only be looked up on
+    // `self` inside of a struct's implementation, so the types of other fields are left unknown.
+    fn variable_type(&self, name: &'a str) -> Result<Option<NLType<'a>>, TypeError<'a>> {
+        let mut path = name.split('.');
+        let variable = path.next().unwrap_or(name);
+
+        let var_type = match self.scope.get_variable(variable) {
+            Some(var_type) => var_type,
+            None => return Err(TypeError::VariableUndefined(name)),
+        };
+
+        let field = match path.next() {
+            Some(field) => field,
+            None => return Ok(var_type.clone()),
+        };
+
+        let is_self = matches!(
+            var_type,
+            Some(NLType::SelfReference) | Some(NLType::MutableSelfReference)
+        );
+
+        match &self.impl_context {
+            Some(context) if is_self => match context.fields.get(field) {
+                // We can't see inside of the field's own type yet.
+                Some(_) if path.next().is_some() => Ok(None),
+                Some(field_type) => Ok(Some(field_type.clone())),
+                None => Err(TypeError::FieldUndefined(context.name, field)),
+            },
+            _ => Ok(None),
+        }
+    }
+
+    fn constant_type(constant: &OpConstant<'a>) -> Option<CheckedType<'a>> {
+        // A suffix, such as the `u8` of `5u8`, pins the integer to that type.
+        let (nl_type, literal) = match constant {
+            OpConstant::Boolean(_) => (NLType::Boolean, None),
+            OpConstant::Signed(value, nl_type, is_typed) => (
+                nl_type.clone(),
+                Some(Literal::Integer(*value as i128)).filter(|_| !is_typed),
+            ),
+            OpConstant::Unsigned(value, nl_type, is_typed) => (
+                nl_type.clone(),
+                Some(Literal::Integer(*value as i128)).filter(|_| !is_typed),
+            ),
+            OpConstant::Float32(_) => (NLType::F32, Some(Literal::Float)),
+            OpConstant::Float64(_) => (NLType::F64, Some(Literal::Float)),
+            OpConstant::String(_) | OpConstant::RawString(_) => return None,
+        };
+
+        Some(CheckedType { nl_type, literal })
+    }
+
+    fn check_assignment(&mut self, assignment: &OpAssignment<'a>) {
+        // The value is checked first, since it can't see the variables it's declaring.
+        let value = self.check_operation(assignment.get_value());
+
+        let variables = assignment.get_variable_to_assign();
+        if !assignment.is_new() {
+            // Without `let`, the variable must already exist. Unlike shadowing, it keeps the type it was declared with.
+            for variable in variables {
+                let name = variable.get_name();
+                match (self.variable_type(name), &value) {
+                    (Ok(Some(var_type)), Some(value)) if variables.len() == 1 => {
+                        if !value.fits(&var_type) {
+                            self.errors.push(TypeError::MismatchedAssignment(
+                                name,
+                                var_type,
+                                value.nl_type.clone(),
+                            ));
+                        }
+                    }
+                    (Err(error), _) => self.errors.push(error),
+                    _ => {}
+                }
+            }
+            return;
+        }
+
+        if variables.len() != 1 {
+            // TODO check the types of destructured variables.
+            for variable in variables {
+                self.scope.declare_variable(variable.get_name(), None);
+            }
+            return;
+        }
+
+        let name = variables[0].get_name();
+        let var_type = match (assignment.get_types().first(), value) {
+            (Some(var_type), Some(value)) => {
+                if !value.fits(var_type) {
+                    self.errors.push(TypeError::MismatchedAssignment(
+                        name,
+                        var_type.clone(),
+                        value.nl_type,
+                    ));
+                }
+                Some(var_type.clone())
+            }
+            (Some(var_type), None) => Some(var_type.clone()),
+            (None, Some(value)) => Some(value.nl_type),
+            (None, None) => None,
+        };
+
+        self.scope.declare_variable(name, var_type);
+    }
+
+    // Both operands must have the same type, which is also the type of the result.
+    fn check_operands(
+        &mut self,
+        operand_a: &NLOperation<'a>,
+        operand_b: &NLOperation<'a>,
+    ) -> Option<CheckedType<'a>> {
+        let operand_a = self.check_operation(operand_a);
+        let operand_b = self.check_operation(operand_b);
+
+        match (operand_a, operand_b) {
+            (Some(operand_a), Some(operand_b)) => {
+                if operand_a.fits(&operand_b.nl_type) || operand_b.fits(&operand_a.nl_type) {
+                    // A literal takes the type of whatever it's used with.
+                    if operand_a.literal.is_some() {
+                        Some(operand_b)
+                    } else {
+                        Some(operand_a)
+                    }
+                } else {
+                    self.errors.push(TypeError::MismatchedOperands(
+                        operand_a.nl_type,
+                        operand_b.nl_type,
+                    ));
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn check_operator(&mut self, operator: &OpOperator<'a>) -> Option<CheckedType<'a>> {
+        match operator {
+            OpOperator::ArithmeticAdd((a, b))
+            | OpOperator::ArithmeticSub((a, b))
+            | OpOperator::ArithmeticMul((a, b))
+            | OpOperator::ArithmeticDiv((a, b))
+            | OpOperator::ArithmeticMod((a, b))
+            | OpOperator::BitAnd((a, b))
+            | OpOperator::BitOr((a, b))
+            | OpOperator::BitXor((a, b)) => self.check_operands(a, b),
+
+            OpOperator::CompareEqual((a, b))
+            | OpOperator::CompareNotEqual((a, b))
+            | OpOperator::CompareGreater((a, b))
+            | OpOperator::CompareLess((a, b))
+            | OpOperator::CompareGreaterEqual((a, b))
+            | OpOperator::CompareLessEqual((a, b))
+            | OpOperator::LogicalAnd((a, b))
+            | OpOperator::LogicalOr((a, b))
+            | OpOperator::LogicalXor((a, b)) => {
+                self.check_operands(a, b);
+                Some(CheckedType::new(NLType::Boolean))
+            }
+
+            // A shift amount doesn't need to match the type being shifted.
+            OpOperator::BitLeftShift((a, b)) | OpOperator::BitRightShift((a, b)) => {
+                self.check_operation(b);
+                self.check_operation(a)
+            }
+
+            // Negating a literal leaves it a literal, but the value it has to fit changes.
+            OpOperator::ArithmeticNegate(operand) => {
+                self.check_operation(operand).map(|mut operand| {
+                    if let Some(Literal::Integer(value)) = operand.literal {
+                        operand.literal = Some(Literal::Integer(-value));
+                    }
+                    operand
+                })
+            }
+            OpOperator::BitNegate(operand) => self
+                .check_operation(operand)
+                .map(|operand| CheckedType::new(operand.nl_type)),
+            OpOperator::LogicalNegate(operand) => {
+                self.check_operation(operand);
+                Some(CheckedType::new(NLType::Boolean))
+            }
+
+            OpOperator::Range((a, b)) => {
+                self.check_operands(a, b);
+                None
+            }
+            OpOperator::PropError(operand) => {
+                self.check_operation(operand);
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,585 @@
+use super::*;
+
+/// Type checks the code and hands the result to the check.
+fn check_code<F>(code: &str, check: F)
+where
+    F: Fn(std::result::Result<(), Vec<TypeError>>),
+{
+    let file = parse_string(code, "virtual_file").unwrap();
+    check(check_types(&file));
+}
+
+mod assignments {
+    use super::*;
+
+    #[test]
+    fn matching_annotation() {
+        let code = "fn test() { let x: i32 = 5 let y: bool = true }";
+        check_code(code, |result| {
+            result.unwrap();
+        });
+    }
+
+    #[test]
+    fn literal_fits_annotation() {
+        // Literals without a suffix could have been meant as any integer type.
+        let code = "fn test() { let x: u8 = 5 let y: f64 = 1.5 }";
+        check_code(code, |result| {
+            result.unwrap();
+        });
+    }
+
+    #[test]
+    fn suffixed_literal_keeps_type() {
+        let code = "fn test() { let x: u8 = 5i64 }";
+        check_code(code, |result| {
+            assert_eq!(
+                result.unwrap_err(),
+                vec![TypeError::MismatchedAssignment(
+                    "x",
+                    NLType::U8,
+                    NLType::I64
+                )]
+            );
+        });
+    }
+
+    #[test]
+    fn literal_out_of_range() {
+        let code = "fn test() { let x: u8 = 300 let y: u8 = -1 let z: i8 = -128 }";
+        check_code(code, |result| {
+            assert_eq!(
+                result.unwrap_err(),
+                vec![
+                    TypeError::MismatchedAssignment("x", NLType::U8, NLType::I32),
+                    TypeError::MismatchedAssignment("y", NLType::U8, NLType::I32),
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn mismatched_annotation() {
+        let code = "fn test() { let x: i32 = true }";
+        check_code(code, |result| {
+            assert_eq!(
+                result.unwrap_err(),
+                vec![TypeError::MismatchedAssignment(
+                    "x",
+                    NLType::I32,
+                    NLType::Boolean
+                )]
+            );
+        });
+    }
+
+    #[test]
+    fn methods_are_checked() {
+        let code = "struct Thing {} impl Self { met test(&self) { let x: bool = 5 } }";
+        check_code(code, |result| {
+            assert_eq!(
+                result.unwrap_err(),
+                vec![TypeError::MismatchedAssignment(
+                    "x",
+                    NLType::Boolean,
+                    NLType::I32
+                )]
+            );
+        });
+    }
+}
+
+mod operators {
+    use super::*;
+
+    #[test]
+    fn matching_operands() {
+        let code = "fn test() { let x: i64 = 1 + 2i64 }";
+        check_code(code, |result| {
+            result.unwrap();
+        });
+    }
+
+    #[test]
+    fn integer_and_float() {
+        let code = "fn test() { let x = 1i32 + 2.5f32 }";
+        check_code(code, |result| {
+            assert_eq!(
+                result.unwrap_err(),
+                vec![TypeError::MismatchedOperands(NLType::I32, NLType::F32)]
+            );
+        });
+    }
+}
+
+mod variables {
+    use super::*;
+
+    #[test]
+    fn declared_variable() {
+        let code = "fn test(a: i32) -> i32 { let x: i32 = 5 let y: i32 = x a + y }";
+        check_code(code, |result| {
+            result.unwrap();
+        });
+    }
+
+    #[test]
+    fn undeclared_variable() {
+        let code = "fn test() { let x: i32 = z y = 5 }";
+        check_code(code, |result| {
+            assert_eq!(
+                result.unwrap_err(),
+                vec![
+                    TypeError::VariableUndefined("z"),
+                    TypeError::VariableUndefined("y")
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn mismatched_variable() {
+        let code = "fn test() { let x = true let y: i32 = x }";
+        check_code(code, |result| {
+            assert_eq!(
+                result.unwrap_err(),
+                vec![TypeError::MismatchedAssignment(
+                    "y",
+                    NLType::I32,
+                    NLType::Boolean
+                )]
+            );
+        });
+    }
+
+    #[test]
+    fn reassign_undeclared() {
+        let code = "fn test() { x = 5 }";
+        check_code(code, |result| {
+            assert_eq!(result.unwrap_err(), vec![TypeError::VariableUndefined("x")]);
+        });
+    }
+
+    #[test]
+    fn reassign_declared() {
+        let code = "fn test() { let x: i32 = 5 x = 6 x += 1 }";
+        check_code(code, |result| {
+            result.unwrap();
+        });
+
+        // Reassigning can't change the type of the variable.
+        let code = "fn test() { let x: i32 = 5 x = true }";
+        check_code(code, |result| {
+            assert_eq!(
+                result.unwrap_err(),
+                vec![TypeError::MismatchedAssignment(
+                    "x",
+                    NLType::I32,
+                    NLType::Boolean
+                )]
+            );
+        });
+    }
+
+    #[test]
+    fn shadowed_with_let() {
+        // Unlike a reassignment, `let` makes a new variable that can have a new type.
+        let code = "fn test() { let x: i32 = 5 let x = true let y: bool = x }";
+        check_code(code, |result| {
+            result.unwrap();
+        });
+    }
+
+    #[test]
+    fn pattern_variables() {
+        // The variables of an `if let` only exist in its true block.
+        let code = "fn test(y: i32) { if let Enum::A(x) = y { let z = x } else { let z = x } }";
+        check_code(code, |result| {
+            assert_eq!(result.unwrap_err(), vec![TypeError::VariableUndefined("x")]);
+        });
+
+        let code = "fn test(y: i32) { while let Enum::A(x) = y { let z = x } let w = x }";
+        check_code(code, |result| {
+            assert_eq!(result.unwrap_err(), vec![TypeError::VariableUndefined("x")]);
+        });
+    }
+
+    #[test]
+    fn shadowed_in_nested_block() {
+        // The inner x is a bool, but the outer one is back once the block ends.
+        let code = "fn test() { let x: i32 = 5 { let x = true let y: bool = x } let z: i32 = x }";
+        check_code(code, |result| {
+            result.unwrap();
+        });
+
+        // Variables declared in a block don't outlive it.
+        let code = "fn test() { { let x = 5 } let y = x }";
+        check_code(code, |result| {
+            assert_eq!(result.unwrap_err(), vec![TypeError::VariableUndefined("x")]);
+        });
+    }
+}
+
+mod inferred_types {
+    use super::*;
+
+    fn type_of<'a>(code: &'a str, scope: &TypeScope<'a>) -> Option<NLType<'a>> {
+        let operation = parse_expression(code).unwrap();
+        operation_type(&operation, scope)
+    }
+
+    #[test]
+    fn constant() {
+        let scope = TypeScope::new();
+        assert_eq!(type_of("5u8", &scope), Some(NLType::U8));
+        assert_eq!(type_of("true", &scope), Some(NLType::Boolean));
+        assert_eq!(type_of("\"text\"", &scope), None);
+    }
+
+    #[test]
+    fn variable_lookup() {
+        let mut scope = TypeScope::new();
+        scope.declare_variable("x", Some(NLType::F64));
+        scope.declare_variable("y", None);
+
+        assert_eq!(type_of("x", &scope), Some(NLType::F64));
+        assert_eq!(type_of("y", &scope), None);
+        assert_eq!(type_of("z", &scope), None);
+    }
+
+    #[test]
+    fn operators() {
+        let mut scope = TypeScope::new();
+        scope.declare_variable("a", Some(NLType::I32));
+        scope.declare_variable("b", Some(NLType::I32));
+        scope.declare_variable("c", Some(NLType::Boolean));
+
+        assert_eq!(type_of("a + b", &scope), Some(NLType::I32));
+        assert_eq!(type_of("a < b", &scope), Some(NLType::Boolean));
+        assert_eq!(type_of("a + c", &scope), None);
+    }
+}
+
+mod loops {
+    use super::*;
+
+    #[test]
+    fn loop_value() {
+        let code = "fn test() { let x: i32 = 'outer: loop { loop { break 'outer 5 } } }";
+        check_code(code, |result| {
+            result.unwrap();
+        });
+
+        let code = "fn test() { let x: bool = loop { break 5i32 } }";
+        check_code(code, |result| {
+            assert_eq!(
+                result.unwrap_err(),
+                vec![TypeError::MismatchedAssignment(
+                    "x",
+                    NLType::Boolean,
+                    NLType::I32
+                )]
+            );
+        });
+    }
+
+    #[test]
+    fn undefined_label() {
+        let code = "fn test() { 'outer: loop { break 'inner } }";
+        check_code(code, |result| {
+            assert_eq!(
+                result.unwrap_err(),
+                vec![TypeError::LabelUndefined("inner")]
+            );
+        });
+    }
+
+    #[test]
+    fn mismatched_breaks() {
+        let code = "fn test(c: bool) { let x = loop { if c { break 1 } break true } }";
+        check_code(code, |result| {
+            assert_eq!(
+                result.unwrap_err(),
+                vec![TypeError::MismatchedBreakTypes(
+                    NLType::I32,
+                    NLType::Boolean
+                )]
+            );
+        });
+
+        let code =
+            "fn test() { let x = 'outer: loop { loop { break 'outer 1.5 } break 'outer 2i32 } }";
+        check_code(code, |result| {
+            assert_eq!(
+                result.unwrap_err(),
+                vec![TypeError::MismatchedBreakTypes(NLType::F32, NLType::I32)]
+            );
+        });
+
+        // Literals take the type of the other breaks, whichever comes first.
+        let code = "fn test(c: bool) { let x: u8 = loop { if c { break 1 } break 2u8 } }";
+        check_code(code, |result| {
+            result.unwrap();
+        });
+    }
+}
+
+mod fields {
+    use super::*;
+
+    #[test]
+    fn declared_field() {
+        let code = "struct Thing { value: i32 } impl Self { met get(&self) -> i32 { self.value } }";
+        check_code(code, |result| {
+            result.unwrap();
+        });
+
+        let code =
+            "struct Thing { value: i32 } impl Self { met get(&self) { let x: bool = self.value } }";
+        check_code(code, |result| {
+            assert_eq!(
+                result.unwrap_err(),
+                vec![TypeError::MismatchedAssignment(
+                    "x",
+                    NLType::Boolean,
+                    NLType::I32
+                )]
+            );
+        });
+    }
+
+    #[test]
+    fn undeclared_field() {
+        let code =
+            "struct Thing { value: i32 } impl Self { met get(&mut self) { self.missing = 5 } }";
+        check_code(code, |result| {
+            assert_eq!(
+                result.unwrap_err(),
+                vec![TypeError::FieldUndefined("Thing", "missing")]
+            );
+        });
+    }
+}
+
+mod traits {
+    use super::*;
+
+    const SHAPE: &str = "trait Shape { met area(&self) -> i32; met sides(&self) -> i32 { 0 } }";
+
+    #[test]
+    fn required_and_default_methods() {
+        let file = parse_string(SHAPE, "virtual_file").unwrap();
+        let shape = file.find_trait("Shape").unwrap();
+
+        let area = shape
+            .iter_methods()
+            .find(|method| method.get_name() == "area");
+        assert!(area.unwrap().is_abstract(), "area should be required.");
+
+        let sides = shape
+            .iter_methods()
+            .find(|method| method.get_name() == "sides");
+        assert!(
+            !sides.unwrap().is_abstract(),
+            "sides should have a default."
+        );
+    }
+
+    #[test]
+    fn implementation_provides_required_methods() {
+        // Default methods can be left out.
+        let code = format!(
+            "{} struct Square {{ side: i32 }} impl Shape {{ met area(&self) -> i32 {{ self.side }} }}",
+            SHAPE
+        );
+        check_code(&code, |result| {
+            result.unwrap();
+        });
+    }
+
+    #[test]
+    fn implementation_with_for() {
+        let code = format!(
+            "{} struct Square {{}} impl Shape for Square {{ met sides(&self) -> i32 {{ 4 }} }}",
+            SHAPE
+        );
+        check_code(&code, |result| {
+            assert_eq!(
+                result.unwrap_err(),
+                vec![TypeError::MissingTraitMethod("Square", "Shape", "area")]
+            );
+        });
+    }
+
+    #[test]
+    fn implementation_missing_required_method() {
+        let code = format!(
+            "{} struct Square {{}} impl Shape {{ met sides(&self) -> i32 {{ 4 }} }}",
+            SHAPE
+        );
+        check_code(&code, |result| {
+            assert_eq!(
+                result.unwrap_err(),
+                vec![TypeError::MissingTraitMethod("Square", "Shape", "area")]
+            );
+        });
+    }
+}
+
+mod arguments {
+    use super::*;
+
+    #[test]
+    fn defaults_come_last() {
+        let code = "fn test(a: i32, b: i32 = 2, c: bool = true) {}";
+        check_code(code, |result| {
+            result.unwrap();
+        });
+
+        let code = "fn test(a: i32 = 1, b: i32) {}";
+        check_code(code, |result| {
+            assert_eq!(
+                result.unwrap_err(),
+                vec![TypeError::ArgumentAfterDefault("test", "b")]
+            );
+        });
+    }
+
+    #[test]
+    fn mismatched_default() {
+        let code = "fn test(a: bool = 5) {}";
+        check_code(code, |result| {
+            assert_eq!(
+                result.unwrap_err(),
+                vec![TypeError::MismatchedAssignment(
+                    "a",
+                    NLType::Boolean,
+                    NLType::I32
+                )]
+            );
+        });
+    }
+}
+
+mod returns {
+    use super::*;
+
+    #[test]
+    fn returns_value() {
+        let code = "fn test() -> i32 { let x: i32 = 5 x }";
+        check_code(code, |result| {
+            result.unwrap();
+        });
+
+        // Returning early counts too.
+        let code = "fn test() -> i32 { return 5 }";
+        check_code(code, |result| {
+            result.unwrap();
+        });
+    }
+
+    #[test]
+    fn missing_return_value() {
+        let code = "fn test() -> i32 { let x: i32 = 5; }";
+        check_code(code, |result| {
+            assert_eq!(
+                result.unwrap_err(),
+                vec![TypeError::MissingReturnValue("test", NLType::I32)]
+            );
+        });
+    }
+
+    #[test]
+    fn mismatched_return_value() {
+        let code = "fn test() -> i32 { true }";
+        check_code(code, |result| {
+            assert_eq!(
+                result.unwrap_err(),
+                vec![TypeError::MismatchedReturn(
+                    "test",
+                    NLType::I32,
+                    NLType::Boolean
+                )]
+            );
+        });
+    }
+
+    #[test]
+    fn void_function() {
+        let code = "fn test() { let x = 5 }";
+        check_code(code, |result| {
+            result.unwrap();
+        });
+
+        let code = "fn test() { 5; }";
+        check_code(code, |result| {
+            result.unwrap();
+        });
+
+        let code = "fn test() { () }";
+        check_code(code, |result| {
+            result.unwrap();
+        });
+
+        let code = "fn test() { 5 }";
+        check_code(code, |result| {
+            assert_eq!(
+                result.unwrap_err(),
+                vec![TypeError::UnexpectedReturnValue("test", NLType::I32)]
+            );
+        });
+    }
+}
+
+mod analysis {
+    use super::*;
+
+    fn analysis_errors(code: &str) -> Vec<AnalysisError> {
+        match analyze_string(code, "virtual_file") {
+            Ok(_) => panic!("Expected the analysis to fail."),
+            Err(errors) => errors,
+        }
+    }
+
+    #[test]
+    fn valid_file() {
+        let code = "struct Thing {} fn test() { let x: i32 = 5 }";
+        let file = analyze_string(code, "virtual_file").unwrap();
+        assert_eq!(file.get_functions().len(), 1);
+    }
+
+    #[test]
+    fn parse_failure() {
+        let code = "fn test( {";
+        let errors = analysis_errors(code);
+
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0], AnalysisError::Parse(_)), "{:?}", errors);
+    }
+
+    #[test]
+    fn duplicate_name() {
+        let code = "struct Thing {} struct Thing {}";
+        let errors = analysis_errors(code);
+
+        assert_eq!(errors.len(), 1);
+        assert!(
+            matches!(errors[0], AnalysisError::DuplicateName(_)),
+            "{:?}",
+            errors
+        );
+    }
+
+    #[test]
+    fn errors_are_gathered() {
+        let code = "fn test() { let x: i32 = true } fn test() {}";
+        let errors = analysis_errors(code);
+
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert!(matches!(errors[0], AnalysisError::DuplicateName(_)));
+        assert!(matches!(
+            errors[1],
+            AnalysisError::Type(TypeError::MismatchedAssignment("x", _, _))
+        ));
+    }
+}
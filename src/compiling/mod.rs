@@ -1,8 +1,9 @@
 use crate::parsing::*;
 
+use cranelift::codegen::ir::FuncRef;
 use cranelift::prelude::*;
 use cranelift_jit::{JITBuilder, JITModule};
-use cranelift_module::{DataContext, Linkage, Module};
+use cranelift_module::{default_libcall_names, DataContext, FuncId, Linkage, Module, ModuleError};
 use std::collections::HashMap;
 
 // All tests are kept in their own module.
@@ -13,9 +14,23 @@ mod tests;
 // Arguments are proveded by leaving them on the stack.
 // Values are returned by leaving them on the stack.
 
-enum CompileError<'a> {
+#[derive(Debug)]
+pub enum CompileError<'a> {
     VariableUndefined(&'a str), // String is the name of the variable.
     TypeUnspecified,            // We do not yet support type derive. The type must be specified.
+    MismatchedBranchTypes(NLType<'a>, NLType<'a>), // The types produced by the true and false branches of an if.
+    UnsupportedOperand(NLType<'a>), // The operator can't be applied to a value of this type.
+    MismatchedOperands(NLType<'a>, NLType<'a>), // The types on either side of an operator.
+    FunctionUndefined(&'a str),     // String is the name of the function.
+    UnsupportedType(NLType<'a>), // Structs and traits can't be passed in or out of functions yet.
+    LabelUndefined(&'a str),     // A break named a loop that it isn't inside of.
+    BreakOutsideLoop,            // A break with no loop around it to exit.
+    MismatchedBreakTypes(NLType<'a>, NLType<'a>), // The values given to two breaks out of the same loop.
+    MismatchedAssignment(&'a str, NLType<'a>, NLType<'a>), // The variable, its type, and the type of its value.
+    WrongArgumentCount(&'a str, usize, usize), // The function, the number of arguments it takes, and the number given.
+    MismatchedArgument(&'a str, NLType<'a>, NLType<'a>), // The function, the type of the argument, and the type given.
+    Unsupported(&'static str), // A feature of the language that can't be compiled yet.
+    Module(Box<ModuleError>), // The function couldn't be declared or defined, such as when two functions share a name.
 }
 
 type Result<'a, T> = std::result::Result<T, CompileError<'a>>;
@@ -29,79 +44,366 @@ pub struct Compiler {
     data_ctx: DataContext,
 }
 
+// A value produced by an operation, along with the type the language sees it as.
+struct TypedValue<'a> {
+    value: Value,
+    nl_type: NLType<'a>,
+}
+
 struct VariableTracker<'a> {
     variable: Variable,
     var_type: NLType<'a>,
 }
 
+// A function that can be called from the function being compiled.
+struct CallTarget<'a> {
+    func_ref: FuncRef,
+    function: &'a NLFunction<'a>,
+}
+
+// Where a `break` goes, and the type of the value it hands to the code after the loop. The first break of the loop
+// decides the type, so it's `None` until then. Breaks without a value give `NLType::None`.
+struct LoopExit<'a> {
+    label: Option<&'a str>,
+    exit_block: Block,
+    break_type: Option<NLType<'a>>,
+}
+
+// The functions of a file, by name, along with the ID they were declared to the module with.
+type FunctionTable<'a> = HashMap<&'a str, (FuncId, &'a NLFunction<'a>)>;
+
 struct StackScope<'a> {
-    parent: Option<&'a StackScope<'a>>,
     next_variable: usize,
-    variables: HashMap<&'a str, VariableTracker<'a>>,
+
+    // The innermost scope is last.
+    scopes: Vec<HashMap<&'a str, VariableTracker<'a>>>,
+
+    // Functions are visible from anywhere in the function being compiled.
+    functions: HashMap<&'a str, CallTarget<'a>>,
+
+    // The loops we're inside of. The innermost loop is last.
+    loops: Vec<LoopExit<'a>>,
 }
 
 impl<'a> StackScope<'a> {
-    fn new(parent: Option<&'a StackScope<'a>>) -> StackScope<'a> {
-        let next_variable = if let Some(parent) = parent {
-            parent.next_variable
-        } else {
-            0
-        };
-
+    fn new() -> StackScope<'a> {
         StackScope {
-            parent,
-            next_variable,
-            variables: HashMap::new(),
-        }
-    }
-    fn declare_variable(&mut self, name: &'a str, var_type: NLType<'a>) -> &VariableTracker<'a> {
-        // use std::collections::hash_map::Entry;
-
-        // match self.variables.entry(name) {
-        //     Entry::Occupied(mut variable) => {
-        //         // The variable exists, so we just have to update it.
-        //         let var = variable.into_mut();
-        //         var.var_type = var_type;
-        //         var
-        //     }
-        //     Entry::Vacant(vacancy) => {
-        //         // If the variable doesn't exist, we have to create it.
-        //         let variable = VariableTracker {
-        //             var_type,
-        //             variable: Variable::new(self.next_variable),
-        //         };
-        //         self.next_variable += 1;
-
-        //         vacancy.insert(variable)
-        //     }
-        // }
-        unimplemented!()
+            next_variable: 0,
+            scopes: vec![HashMap::new()],
+            functions: HashMap::new(),
+            loops: Vec::new(),
+        }
+    }
+
+    fn push_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    fn pop_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn declare_variable(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        name: &'a str,
+        var_type: NLType<'a>,
+    ) -> Result<'a, Variable> {
+        // Every declaration gets a new Cranelift variable, even when it shadows another, since the two can have
+        // different types.
+        let variable = Variable::new(self.next_variable);
+        self.next_variable += 1;
+
+        builder.declare_var(variable, Compiler::crane_type(&var_type)?);
+
+        let scope = self
+            .scopes
+            .last_mut()
+            .expect("There should always be at least one scope.");
+        scope.insert(name, VariableTracker { variable, var_type });
+
+        Ok(variable)
     }
 
     fn get_variable(&self, name: &'a str) -> Option<&VariableTracker<'a>> {
-        self.variables.get(name)
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    fn declare_function(&mut self, name: &'a str, func_ref: FuncRef, function: &'a NLFunction<'a>) {
+        self.functions
+            .insert(name, CallTarget { func_ref, function });
+    }
+
+    fn get_function(&self, name: &'a str) -> Option<&CallTarget<'a>> {
+        self.functions.get(name)
+    }
+
+    // A break without a label exits the innermost loop.
+    fn get_loop(&mut self, label: Option<&'a str>) -> Result<'a, &mut LoopExit<'a>> {
+        match label {
+            Some(label) => self
+                .loops
+                .iter_mut()
+                .rev()
+                .find(|exit| exit.label == Some(label))
+                .ok_or(CompileError::LabelUndefined(label)),
+            None => self.loops.last_mut().ok_or(CompileError::BreakOutsideLoop),
+        }
+    }
+}
+
+/// The structs that types can refer to, by name, so their layouts can be worked out.
+pub struct StructRegistry<'f, 'a> {
+    structs: HashMap<&'a str, &'f NLStruct<'a>>,
+}
+
+impl<'f, 'a> StructRegistry<'f, 'a> {
+    pub fn new() -> StructRegistry<'f, 'a> {
+        StructRegistry {
+            structs: HashMap::new(),
+        }
+    }
+
+    /// A registry of every struct in the file.
+    pub fn from_file(file: &'f NLFile<'a>) -> StructRegistry<'f, 'a> {
+        let mut registry = StructRegistry::new();
+        for nl_struct in file.get_structs() {
+            registry.add_struct(nl_struct);
+        }
+
+        registry
+    }
+
+    pub fn add_struct(&mut self, nl_struct: &'f NLStruct<'a>) {
+        self.structs.insert(nl_struct.get_name(), nl_struct);
+    }
+
+    pub fn get_struct(&self, name: &str) -> Option<&'f NLStruct<'a>> {
+        self.structs.get(name).copied()
     }
 }
 
+impl<'f, 'a> Default for StructRegistry<'f, 'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The size of a value of the type, in bytes. Tuples and structs are laid out like C lays out its structs: each field
+/// starts at a multiple of its own alignment, and the whole thing is padded to a multiple of its largest alignment.
+/// Gives None for types that don't have a size yet, such as strings and references, and for structs that are unknown
+/// or contain themselves.
+pub fn type_size(nl_type: &NLType, registry: &StructRegistry) -> Option<usize> {
+    type_layout(nl_type, registry, &mut Vec::new()).map(|(size, _alignment)| size)
+}
+
+// The size and alignment of a type. The structs we're in the middle of laying out are kept so that a struct that
+// contains itself is caught, rather than recursing forever.
+fn type_layout<'a>(
+    nl_type: &NLType<'a>,
+    registry: &StructRegistry<'_, 'a>,
+    parents: &mut Vec<&'a str>,
+) -> Option<(usize, usize)> {
+    match nl_type {
+        NLType::None => Some((0, 1)),
+        NLType::F32 => Some((4, 4)),
+        NLType::F64 => Some((8, 8)),
+        nl_type if nl_type.is_boolean() || nl_type.is_integer() => {
+            // Booleans are a single bit, but still take up a whole byte.
+            let size = nl_type.num_bits().div_ceil(8) as usize;
+            Some((size, size))
+        }
+        NLType::Tuple(elements) => fields_layout(elements.iter(), registry, parents),
+        NLType::Array(element, ArrayLen::Literal(length)) => {
+            let (size, alignment) = type_layout(element, registry, parents)?;
+            Some((size * length, alignment))
+        }
+        NLType::OwnedStruct(name) => {
+            if parents.contains(name) {
+                return None;
+            }
+
+            let nl_struct = registry.get_struct(name)?;
+            parents.push(name);
+            let layout = fields_layout(
+                nl_struct
+                    .get_variables()
+                    .iter()
+                    .map(|variable| variable.get_type()),
+                registry,
+                parents,
+            );
+            parents.pop();
+
+            layout
+        }
+        _ => None,
+    }
+}
+
+fn fields_layout<'f, 'a: 'f>(
+    fields: impl Iterator<Item = &'f NLType<'a>>,
+    registry: &StructRegistry<'_, 'a>,
+    parents: &mut Vec<&'a str>,
+) -> Option<(usize, usize)> {
+    let mut size = 0;
+    let mut alignment = 1;
+
+    for field in fields {
+        let (field_size, field_alignment) = type_layout(field, registry, parents)?;
+
+        size = align_to(size, field_alignment) + field_size;
+        alignment = alignment.max(field_alignment);
+    }
+
+    Some((align_to(size, alignment), alignment))
+}
+
+// Rounds the offset up to the next multiple of the alignment.
+fn align_to(offset: usize, alignment: usize) -> usize {
+    offset.div_ceil(alignment) * alignment
+}
+
 impl Compiler {
-    fn compile_function(&mut self, function: NLFunction) -> Result<()> {
-        let mut builder = FunctionBuilder::new(&mut self.ctx.func, &mut self.builder_context);
+    pub fn new() -> Compiler {
+        let module = JITModule::new(JITBuilder::new(default_libcall_names()));
+
+        Compiler {
+            builder_context: FunctionBuilderContext::new(),
+            ctx: module.make_context(),
+            data_ctx: DataContext::new(),
+            module,
+        }
+    }
+
+    // Only primitive types have a Cranelift type for now.
+    fn crane_type<'a>(nl_type: &NLType<'a>) -> Result<'a, Type> {
+        let crane_type = match nl_type {
+            NLType::Boolean => types::B1,
+            NLType::I8 => types::I8,
+            NLType::I16 => types::I16,
+            NLType::I32 => types::I32,
+            NLType::I64 => types::I64,
+            // So fun fact, the hardware treats signed and unsigned integers the same. We have to enforce the type safety.
+            NLType::U8 => types::I8,
+            NLType::U16 => types::I16,
+            NLType::U32 => types::I32,
+            NLType::U64 => types::I64,
+            NLType::F32 => types::F32,
+            NLType::F64 => types::F64,
+            _ => return Err(CompileError::UnsupportedType(nl_type.clone())),
+        };
+
+        Ok(crane_type)
+    }
+
+    /// Compiles every function in the file, then finalizes them so they can be run. Functions can call any other
+    /// function in the file, no matter which order they were written in. Functions without a body are expected to
+    /// be provided from outside of the file.
+    pub fn compile_file<'a>(&mut self, file: &'a NLFile<'a>) -> Result<'a, ()> {
+        // Everything gets declared first, so that calls can be made to functions we haven't compiled yet.
+        let mut functions = FunctionTable::new();
+        for function in file.get_functions() {
+            let mut signature = self.module.make_signature();
+            Self::build_signature(&mut signature, function)?;
+
+            let linkage = if function.get_block().is_some() {
+                Linkage::Export
+            } else {
+                Linkage::Import
+            };
+
+            let id = self
+                .module
+                .declare_function(function.get_name(), linkage, &signature)
+                .map_err(|error| CompileError::Module(Box::new(error)))?;
+            functions.insert(function.get_name(), (id, function));
+        }
+
+        for function in file.get_functions() {
+            if function.get_block().is_none() {
+                continue;
+            }
+
+            let (id, _) = functions[function.get_name()];
+            self.compile_function_with_calls(function, &functions)?;
+
+            self.module
+                .define_function(id, &mut self.ctx, &mut codegen::binemit::NullTrapSink {})
+                .map_err(|error| CompileError::Module(Box::new(error)))?;
+        }
 
+        self.module.clear_context(&mut self.ctx);
+        self.module.finalize_definitions();
+
+        Ok(())
+    }
+
+    fn build_signature<'a>(signature: &mut Signature, function: &NLFunction<'a>) -> Result<'a, ()> {
         // Adding the arguments.
-        // for _p in &params {
-        //     self.ctx.func.signature.params.push(AbiParam::new(int));
-        // }
+        for argument in function.get_arguments() {
+            let param = Self::abi_param(argument.get_type())?;
+            signature.params.push(param);
+        }
 
         // Adding the return values.
-        // function.return_type
-        // self.ctx.func.signature.returns.push(AbiParam::new());
+        Self::add_returns(signature, function.get_return_type())
+    }
+
+    // Only primitive types have a way to be passed in and out of functions for now.
+    fn abi_param<'a>(nl_type: &NLType<'a>) -> Result<'a, AbiParam> {
+        Ok(AbiParam::new(Self::crane_type(nl_type)?))
+    }
+
+    fn compile_function<'a>(&mut self, function: &'a NLFunction<'a>) -> Result<'a, ()> {
+        self.compile_function_with_calls(function, &FunctionTable::new())
+    }
+
+    // Compiles a function that can call any of the given functions.
+    fn compile_function_with_calls<'a>(
+        &mut self,
+        function: &'a NLFunction<'a>,
+        functions: &FunctionTable<'a>,
+    ) -> Result<'a, ()> {
+        // Each function starts from a fresh context, or it would be built on top of the last one.
+        self.module.clear_context(&mut self.ctx);
+        Self::build_signature(&mut self.ctx.func.signature, function)?;
+
+        let mut builder = FunctionBuilder::new(&mut self.ctx.func, &mut self.builder_context);
 
         if let Some(block) = function.get_block() {
             let entry_block = builder.create_block();
             builder.append_block_params_for_function_params(entry_block);
+            builder.switch_to_block(entry_block);
             builder.seal_block(entry_block);
 
-            Self::compile_block(None, &mut builder, &block);
+            let mut scope = StackScope::new();
+            for (name, (id, callee)) in functions {
+                let func_ref = self.module.declare_func_in_func(*id, builder.func);
+                scope.declare_function(name, func_ref, callee);
+            }
+
+            // Arguments are just variables that start out with a value.
+            let parameters = builder.block_params(entry_block).to_vec();
+            for (argument, value) in function.get_arguments().iter().zip(parameters) {
+                let variable = scope.declare_variable(
+                    &mut builder,
+                    argument.get_name(),
+                    argument.get_type().clone(),
+                )?;
+                builder.def_var(variable, value);
+            }
+
+            let results = Self::compile_function_block(&mut scope, &mut builder, block)?;
+
+            // When every path has already returned, the end of the function can never be reached.
+            if builder.is_unreachable() {
+                builder.ins().trap(TrapCode::UnreachableCodeReached);
+            } else {
+                builder.ins().return_(&results);
+            }
+
+            builder.finalize();
 
             Ok(())
         } else {
@@ -110,98 +412,979 @@ impl Compiler {
         }
     }
 
-    fn compile_block<'a>(
-        parent_scope: Option<&'a StackScope<'a>>,
+    // A tuple is returned as one value per element, so a function returning `(i32, (bool, u8))` has three returns.
+    fn add_returns<'a>(signature: &mut Signature, return_type: &NLType<'a>) -> Result<'a, ()> {
+        match return_type {
+            NLType::None => {}
+            NLType::Tuple(elements) => {
+                for element in elements {
+                    Self::add_returns(signature, element)?;
+                }
+            }
+            return_type => {
+                let param = Self::abi_param(return_type)?;
+                signature.returns.push(param);
+            }
+        }
+
+        Ok(())
+    }
+
+    // Compiles the body of a function and gives back the values it returns. A tuple at the end of the body has
+    // its elements returned as separate values, to match the signature built by `add_returns`.
+    fn compile_function_block<'a>(
+        scope: &mut StackScope<'a>,
         builder: &mut FunctionBuilder,
-        block: &NLBlock,
-    ) {
+        block: &'a NLBlock<'a>,
+    ) -> Result<'a, Vec<Value>> {
+        let result = match block.get_result() {
+            Some(result @ NLOperation::Tuple(_)) | Some(result @ NLOperation::FunctionCall(_)) => {
+                result
+            }
+            _ => {
+                let result = Self::compile_block(scope, builder, block)?;
+                return Ok(result.into_iter().map(|result| result.value).collect());
+            }
+        };
+
+        scope.push_scope();
+
         let operations = block.get_operations();
+        for operation in &operations[..operations.len() - 1] {
+            Self::compile_operation(scope, builder, operation)?;
+        }
 
-        // Start by getting all of the local variables.
-        let local_variables = StackScope::new(parent_scope);
+        let mut values = Vec::new();
+        Self::compile_tuple_elements(scope, builder, result, &mut values)?;
 
-        for operation in operations {
-            match operation {
-                NLOperation::Block(block) => {
-                    Self::compile_block(Some(&local_variables), builder, block);
-                }
-                NLOperation::Constant(constant) => {
-                    // let _value = match constant {
-                    //     OpConstant::Boolean(value) => builder.ins().bconst(types::B1, *value),
-                    //     OpConstant::Integer(value, nl_type) => {
-                    //         let crane_type = match nl_type {
-                    //             NLType::I8 => types::I8,
-                    //             NLType::I16 => types::I16,
-                    //             NLType::I32 => types::I32,
-                    //             NLType::I64 => types::I64,
-                    //             // So fun fact, the hardware treats signed and unsigned integers the same. We have to enforce the type safety.
-                    //             NLType::U8 => types::I8,
-                    //             NLType::U16 => types::I16,
-                    //             NLType::U32 => types::I32,
-                    //             NLType::U64 => types::I64,
-                    //             _ => unreachable!(),
-                    //         };
-                    //         builder.ins().iconst(crane_type, *value as i64)
-                    //     }
-                    //     OpConstant::Float32(value) => builder.ins().f32const(*value),
-                    //     OpConstant::Float64(value) => builder.ins().f64const(*value),
-                    //     OpConstant::String(value) => {
-                    //         // This one's not going to be so simple. We have to point to the string in memory.
-                    //         // Some kind of fat pointer would be ideal.
-                    //         // fn const_addr<T1>(self, iAddr: Type, constant: T1) -> Value
-                    //         unimplemented!()
-                    //     }
-                    // };
-                }
-                NLOperation::Assign(assignment) => {
-                    // if assignment.is_new() {
-                    //     // New variable. We need to allocate it a space on the stack (or reuse the space of a variable that's being redefined)
-
-                    //     for (name, var_type) in assignment
-                    //         .get_variable_to_assign()
-                    //         .iter()
-                    //         .zip(assignment.get_types())
-                    //     {
-                    //         local_variables.declare_variable(name.get_name(), *var_type);
-                    //     }
-                    // } else {
-                    // }
-
-                    // use_var
-                }
-                NLOperation::VariableAccess(variable) => {
-                    unimplemented!()
-                }
-                NLOperation::Tuple(_operations) => {
-                    unimplemented!()
-                }
-                NLOperation::Operator(_operator) => {
-                    unimplemented!()
-                }
-                NLOperation::If(_if_statement) => {
-                    unimplemented!()
+        scope.pop_scope();
+
+        Ok(values)
+    }
+
+    // Compiles each element of a tuple, and the elements of any tuples inside of it, in order. A call to a function
+    // that returns a tuple gives all of its values.
+    fn compile_tuple_elements<'a>(
+        scope: &mut StackScope<'a>,
+        builder: &mut FunctionBuilder,
+        operation: &'a NLOperation<'a>,
+        values: &mut Vec<Value>,
+    ) -> Result<'a, ()> {
+        match operation {
+            NLOperation::Tuple(elements) => {
+                for element in elements {
+                    Self::compile_tuple_elements(scope, builder, element, values)?;
                 }
-                NLOperation::Loop(_loop_block) => {
-                    unimplemented!()
+            }
+            NLOperation::FunctionCall(function_call) => {
+                let (results, _) = Self::compile_call(scope, builder, function_call)?;
+                values.extend(results);
+            }
+            operation => {
+                let value = Self::compile_value(scope, builder, operation)?;
+                values.push(value.value);
+            }
+        }
+
+        Ok(())
+    }
+
+    // Compiles the operations of a block in a new scope. The value of the block is the value of its last operation.
+    fn compile_block<'a>(
+        scope: &mut StackScope<'a>,
+        builder: &mut FunctionBuilder,
+        block: &'a NLBlock<'a>,
+    ) -> Result<'a, Option<TypedValue<'a>>> {
+        scope.push_scope();
+
+        let mut result = None;
+        for operation in block.get_operations() {
+            result = Self::compile_operation(scope, builder, operation)?;
+        }
+
+        // A block ended with a semicolon has no value.
+        if block.get_result().is_none() {
+            result = None;
+        }
+
+        scope.pop_scope();
+
+        Ok(result)
+    }
+
+    fn compile_operation<'a>(
+        scope: &mut StackScope<'a>,
+        builder: &mut FunctionBuilder,
+        operation: &'a NLOperation<'a>,
+    ) -> Result<'a, Option<TypedValue<'a>>> {
+        match operation {
+            NLOperation::Block(block) => Self::compile_block(scope, builder, block),
+            NLOperation::Constant(constant) => {
+                Ok(Some(Self::compile_constant(builder, constant, None)?))
+            }
+            NLOperation::Assign(assignment) => {
+                Self::compile_assignment(scope, builder, assignment)?;
+                Ok(None)
+            }
+            NLOperation::VariableAccess(variable) => {
+                let name = variable.get_name();
+                let tracker = scope
+                    .get_variable(name)
+                    .ok_or(CompileError::VariableUndefined(name))?;
+
+                Ok(Some(TypedValue {
+                    value: builder.use_var(tracker.variable),
+                    nl_type: tracker.var_type.clone(),
+                }))
+            }
+            NLOperation::Tuple(_operations) => {
+                unimplemented!()
+            }
+            NLOperation::Unit => Ok(None),
+            NLOperation::Operator(operator) => {
+                Ok(Some(Self::compile_operator(scope, builder, operator)?))
+            }
+            NLOperation::If(if_statement) => Self::compile_if(scope, builder, if_statement),
+            NLOperation::Loop(body) => Self::compile_loop(scope, builder, None, body),
+            NLOperation::WhileLoop(_while_loop) => {
+                unimplemented!()
+            }
+            // Patterns need enums, which can't be compiled yet.
+            NLOperation::IfLet(_) => Err(CompileError::Unsupported("`if let`")),
+            NLOperation::WhileLet(_) => Err(CompileError::Unsupported("`while let`")),
+            NLOperation::ForLoop(_for_loop) => {
+                unimplemented!()
+            }
+            NLOperation::Labeled { label, operation } => match &**operation {
+                NLOperation::Loop(body) => Self::compile_loop(scope, builder, Some(label), body),
+                // TODO labels for while and for loops, once those can be compiled.
+                _ => Err(CompileError::Unsupported(
+                    "labels on `while` and `for` loops",
+                )),
+            },
+            NLOperation::Break { label, value } => {
+                Self::compile_break(scope, builder, *label, value.as_deref())?;
+                Ok(None)
+            }
+            NLOperation::Return(value) => {
+                let mut values = Vec::new();
+                if let Some(value) = value {
+                    Self::compile_tuple_elements(scope, builder, value, &mut values)?;
                 }
-                NLOperation::WhileLoop(_while_loop) => {
-                    unimplemented!()
+                builder.ins().return_(&values);
+
+                // Anything after the return can never run, but it still needs a block to go in.
+                let after_return = builder.create_block();
+                builder.switch_to_block(after_return);
+                builder.seal_block(after_return);
+
+                Ok(None)
+            }
+            NLOperation::Match(_match_statement) => {
+                unimplemented!()
+            }
+            NLOperation::FunctionCall(function_call) => {
+                Self::compile_function_call(scope, builder, function_call)
+            }
+            NLOperation::StructLiteral(literal) => Err(CompileError::UnsupportedType(
+                NLType::OwnedStruct(literal.get_struct()),
+            )),
+            NLOperation::Cast { target, .. } => Err(CompileError::UnsupportedType(target.clone())),
+            NLOperation::ArrayLiteral(_) | NLOperation::ArrayRepeat { .. } => {
+                Err(CompileError::Unsupported("arrays"))
+            }
+        }
+    }
+
+    // A float literal has no set size, so it takes the expected type when that's a float. Otherwise it's an f32
+    // or f64, whichever it was read as. An integer literal without a suffix takes the expected type when that's an
+    // integer type that can hold it.
+    fn compile_constant<'a>(
+        builder: &mut FunctionBuilder,
+        constant: &'a OpConstant<'a>,
+        expected_type: Option<&NLType<'a>>,
+    ) -> Result<'a, TypedValue<'a>> {
+        let integer_type = |value: i128, nl_type: &NLType<'a>, is_typed: bool| match expected_type {
+            Some(expected_type) if !is_typed && expected_type.holds_integer(value) => {
+                expected_type.clone()
+            }
+            _ => nl_type.clone(),
+        };
+
+        let value = match constant {
+            OpConstant::Boolean(value) => TypedValue {
+                value: builder.ins().bconst(types::B1, *value),
+                nl_type: NLType::Boolean,
+            },
+            OpConstant::Signed(value, nl_type, is_typed) => {
+                let nl_type = integer_type(*value as i128, nl_type, *is_typed);
+                TypedValue {
+                    value: builder.ins().iconst(Self::crane_type(&nl_type)?, *value),
+                    nl_type,
                 }
-                NLOperation::ForLoop(_for_loop) => {
-                    unimplemented!()
+            }
+            OpConstant::Unsigned(value, nl_type, is_typed) => {
+                let nl_type = integer_type(*value as i128, nl_type, *is_typed);
+                TypedValue {
+                    value: builder
+                        .ins()
+                        .iconst(Self::crane_type(&nl_type)?, *value as i64),
+                    nl_type,
                 }
-                NLOperation::Break => {
-                    unimplemented!()
+            }
+            OpConstant::Float32(value) => match expected_type {
+                Some(NLType::F64) => Self::compile_float64(builder, *value as f64),
+                _ => Self::compile_float32(builder, *value),
+            },
+            OpConstant::Float64(value) => match expected_type {
+                Some(NLType::F32) => Self::compile_float32(builder, *value as f32),
+                _ => Self::compile_float64(builder, *value),
+            },
+            OpConstant::String(_) | OpConstant::RawString(_) => {
+                // This one's not going to be so simple. We have to point to the string in memory.
+                // Some kind of fat pointer would be ideal.
+                return Err(CompileError::UnsupportedType(NLType::BorrowedString));
+            }
+        };
+
+        Ok(value)
+    }
+
+    fn compile_float32<'a>(builder: &mut FunctionBuilder, value: f32) -> TypedValue<'a> {
+        TypedValue {
+            value: builder.ins().f32const(value),
+            nl_type: NLType::F32,
+        }
+    }
+
+    fn compile_float64<'a>(builder: &mut FunctionBuilder, value: f64) -> TypedValue<'a> {
+        TypedValue {
+            value: builder.ins().f64const(value),
+            nl_type: NLType::F64,
+        }
+    }
+
+    // A call used as a single value. Calls to functions that return tuples can only be returned or passed along as
+    // the elements of a tuple for now, which is handled by `compile_tuple_elements`.
+    fn compile_function_call<'a>(
+        scope: &mut StackScope<'a>,
+        builder: &mut FunctionBuilder,
+        function_call: &'a FunctionCall<'a>,
+    ) -> Result<'a, Option<TypedValue<'a>>> {
+        let (results, return_type) = Self::compile_call(scope, builder, function_call)?;
+
+        match results.as_slice() {
+            [] => Ok(None),
+            [value] => Ok(Some(TypedValue {
+                value: *value,
+                nl_type: return_type,
+            })),
+            _ => Err(CompileError::UnsupportedType(return_type)),
+        }
+    }
+
+    // Calls the function and gives back every value it returned, along with its return type. Arguments that are left
+    // out take their default values, which are compiled in the caller.
+    fn compile_call<'a>(
+        scope: &mut StackScope<'a>,
+        builder: &mut FunctionBuilder,
+        function_call: &'a FunctionCall<'a>,
+    ) -> Result<'a, (Vec<Value>, NLType<'a>)> {
+        let path = function_call.get_path();
+        let target = scope
+            .get_function(path)
+            .ok_or(CompileError::FunctionUndefined(path))?;
+        let func_ref = target.func_ref;
+        let function = target.function;
+
+        let parameters = function.get_arguments();
+        let given = function_call.get_arguments();
+        if given.len() > parameters.len() {
+            return Err(CompileError::WrongArgumentCount(
+                path,
+                parameters.len(),
+                given.len(),
+            ));
+        }
+
+        let mut arguments = Vec::new();
+        for (index, parameter) in parameters.iter().enumerate() {
+            let argument = match (given.get(index), parameter.get_default()) {
+                (Some(argument), _) => argument,
+                (None, Some(default)) => default,
+                (None, None) => {
+                    return Err(CompileError::WrongArgumentCount(
+                        path,
+                        parameters.len(),
+                        given.len(),
+                    ))
                 }
-                NLOperation::Match(_match_statement) => {
-                    unimplemented!()
+            };
+
+            let argument =
+                Self::compile_expected(scope, builder, argument, Some(parameter.get_type()))?;
+            if argument.nl_type != *parameter.get_type() {
+                return Err(CompileError::MismatchedArgument(
+                    path,
+                    parameter.get_type().clone(),
+                    argument.nl_type,
+                ));
+            }
+
+            arguments.push(argument.value);
+        }
+
+        let call = builder.ins().call(func_ref, &arguments);
+        let results = builder.inst_results(call).to_vec();
+
+        Ok((results, function.get_return_type().clone()))
+    }
+
+    // Compiles an operation that must produce a value, such as the operand of an operator.
+    fn compile_value<'a>(
+        scope: &mut StackScope<'a>,
+        builder: &mut FunctionBuilder,
+        operation: &'a NLOperation<'a>,
+    ) -> Result<'a, TypedValue<'a>> {
+        Self::compile_operation(scope, builder, operation)?.ok_or(CompileError::TypeUnspecified)
+    }
+
+    // Like `compile_value`, but a literal can take the expected type, such as when it's passed to a function.
+    fn compile_expected<'a>(
+        scope: &mut StackScope<'a>,
+        builder: &mut FunctionBuilder,
+        operation: &'a NLOperation<'a>,
+        expected_type: Option<&NLType<'a>>,
+    ) -> Result<'a, TypedValue<'a>> {
+        match operation {
+            NLOperation::Constant(constant) => {
+                Self::compile_constant(builder, constant, expected_type)
+            }
+            operation => Self::compile_value(scope, builder, operation),
+        }
+    }
+
+    // Compiles both operands of an operator, which must have the same type. A literal on either side takes the type
+    // of the other side. When only the left side is a literal, the right side is compiled first so its type is known,
+    // which is fine since a literal can't have side effects.
+    fn compile_operands<'a>(
+        scope: &mut StackScope<'a>,
+        builder: &mut FunctionBuilder,
+        a: &'a NLOperation<'a>,
+        b: &'a NLOperation<'a>,
+    ) -> Result<'a, (TypedValue<'a>, TypedValue<'a>)> {
+        let is_constant = |operation| matches!(operation, &NLOperation::Constant(_));
+
+        let (a, b) = if is_constant(a) && !is_constant(b) {
+            let b = Self::compile_value(scope, builder, b)?;
+            let a = Self::compile_expected(scope, builder, a, Some(&b.nl_type))?;
+            (a, b)
+        } else {
+            let a = Self::compile_value(scope, builder, a)?;
+            let b = Self::compile_expected(scope, builder, b, Some(&a.nl_type))?;
+            (a, b)
+        };
+
+        if a.nl_type == b.nl_type {
+            Ok((a, b))
+        } else {
+            Err(CompileError::MismatchedOperands(a.nl_type, b.nl_type))
+        }
+    }
+
+    // Compiles both operands of an arithmetic operator, which must be integers or floats of the same type.
+    fn compile_numeric_operands<'a>(
+        scope: &mut StackScope<'a>,
+        builder: &mut FunctionBuilder,
+        a: &'a NLOperation<'a>,
+        b: &'a NLOperation<'a>,
+    ) -> Result<'a, (TypedValue<'a>, TypedValue<'a>)> {
+        let (a, b) = Self::compile_operands(scope, builder, a, b)?;
+
+        if a.nl_type.is_numeric() {
+            Ok((a, b))
+        } else {
+            Err(CompileError::UnsupportedOperand(a.nl_type))
+        }
+    }
+
+    // Integers are compared as signed or unsigned depending on their type. Booleans can only be checked for equality.
+    fn compile_comparison<'a>(
+        scope: &mut StackScope<'a>,
+        builder: &mut FunctionBuilder,
+        (a, b): &'a (Box<NLOperation<'a>>, Box<NLOperation<'a>>),
+        signed: IntCC,
+        unsigned: IntCC,
+        float: FloatCC,
+    ) -> Result<'a, TypedValue<'a>> {
+        let (a, b) = Self::compile_operands(scope, builder, a, b)?;
+
+        let value = if a.nl_type.is_signed() {
+            builder.ins().icmp(signed, a.value, b.value)
+        } else if a.nl_type.is_unsigned() {
+            builder.ins().icmp(unsigned, a.value, b.value)
+        } else if a.nl_type.is_float() {
+            builder.ins().fcmp(float, a.value, b.value)
+        } else if a.nl_type.is_boolean() && signed == IntCC::NotEqual {
+            builder.ins().bxor(a.value, b.value)
+        } else if a.nl_type.is_boolean() && signed == IntCC::Equal {
+            let different = builder.ins().bxor(a.value, b.value);
+            builder.ins().bnot(different)
+        } else {
+            return Err(CompileError::UnsupportedOperand(a.nl_type));
+        };
+
+        Ok(TypedValue {
+            value,
+            nl_type: NLType::Boolean,
+        })
+    }
+
+    fn compile_operator<'a>(
+        scope: &mut StackScope<'a>,
+        builder: &mut FunctionBuilder,
+        operator: &'a OpOperator<'a>,
+    ) -> Result<'a, TypedValue<'a>> {
+        match operator {
+            OpOperator::LogicalXor((a, b)) => {
+                // Both sides always have to be evaluated to know the result, so there's no short circuit here.
+                let a = Self::compile_boolean(scope, builder, a)?;
+                let b = Self::compile_boolean(scope, builder, b)?;
+
+                Ok(TypedValue {
+                    value: builder.ins().bxor(a, b),
+                    nl_type: NLType::Boolean,
+                })
+            }
+            OpOperator::LogicalAnd((a, b)) => {
+                Self::compile_short_circuit(scope, builder, a, b, false)
+            }
+            OpOperator::LogicalOr((a, b)) => {
+                Self::compile_short_circuit(scope, builder, a, b, true)
+            }
+            OpOperator::LogicalNegate(operand) => {
+                let operand = Self::compile_boolean(scope, builder, operand)?;
+
+                Ok(TypedValue {
+                    value: builder.ins().bnot(operand),
+                    nl_type: NLType::Boolean,
+                })
+            }
+            OpOperator::ArithmeticNegate(operand) => {
+                let operand = Self::compile_value(scope, builder, operand)?;
+
+                // Unsigned values have no negative to take.
+                let value = if operand.nl_type.is_signed() {
+                    builder.ins().ineg(operand.value)
+                } else if operand.nl_type.is_float() {
+                    builder.ins().fneg(operand.value)
+                } else {
+                    return Err(CompileError::UnsupportedOperand(operand.nl_type));
+                };
+
+                Ok(TypedValue {
+                    value,
+                    nl_type: operand.nl_type,
+                })
+            }
+            OpOperator::BitAnd((a, b)) => {
+                let (a, b) = Self::compile_integer_operands(scope, builder, a, b)?;
+
+                Ok(TypedValue {
+                    value: builder.ins().band(a.value, b.value),
+                    nl_type: a.nl_type,
+                })
+            }
+            OpOperator::BitOr((a, b)) => {
+                let (a, b) = Self::compile_integer_operands(scope, builder, a, b)?;
+
+                Ok(TypedValue {
+                    value: builder.ins().bor(a.value, b.value),
+                    nl_type: a.nl_type,
+                })
+            }
+            OpOperator::BitXor((a, b)) => {
+                let (a, b) = Self::compile_integer_operands(scope, builder, a, b)?;
+
+                Ok(TypedValue {
+                    value: builder.ins().bxor(a.value, b.value),
+                    nl_type: a.nl_type,
+                })
+            }
+            OpOperator::BitNegate(operand) => {
+                let operand = Self::compile_integer(scope, builder, operand)?;
+
+                Ok(TypedValue {
+                    value: builder.ins().bnot(operand.value),
+                    nl_type: operand.nl_type,
+                })
+            }
+            OpOperator::BitLeftShift((a, b)) => {
+                // The shift amount doesn't need to be the same type as the value being shifted.
+                let a = Self::compile_integer(scope, builder, a)?;
+                let b = Self::compile_integer(scope, builder, b)?;
+
+                Ok(TypedValue {
+                    value: builder.ins().ishl(a.value, b.value),
+                    nl_type: a.nl_type,
+                })
+            }
+            OpOperator::BitRightShift((a, b)) => {
+                let a = Self::compile_integer(scope, builder, a)?;
+                let b = Self::compile_integer(scope, builder, b)?;
+
+                // Signed values keep their sign as they're shifted.
+                let value = if a.nl_type.is_signed() {
+                    builder.ins().sshr(a.value, b.value)
+                } else {
+                    builder.ins().ushr(a.value, b.value)
+                };
+
+                Ok(TypedValue {
+                    value,
+                    nl_type: a.nl_type,
+                })
+            }
+            OpOperator::ArithmeticAdd((a, b)) => {
+                let (a, b) = Self::compile_numeric_operands(scope, builder, a, b)?;
+
+                let value = if a.nl_type.is_float() {
+                    builder.ins().fadd(a.value, b.value)
+                } else {
+                    builder.ins().iadd(a.value, b.value)
+                };
+
+                Ok(TypedValue {
+                    value,
+                    nl_type: a.nl_type,
+                })
+            }
+            OpOperator::ArithmeticSub((a, b)) => {
+                let (a, b) = Self::compile_numeric_operands(scope, builder, a, b)?;
+
+                let value = if a.nl_type.is_float() {
+                    builder.ins().fsub(a.value, b.value)
+                } else {
+                    builder.ins().isub(a.value, b.value)
+                };
+
+                Ok(TypedValue {
+                    value,
+                    nl_type: a.nl_type,
+                })
+            }
+            OpOperator::ArithmeticMul((a, b)) => {
+                let (a, b) = Self::compile_numeric_operands(scope, builder, a, b)?;
+
+                let value = if a.nl_type.is_float() {
+                    builder.ins().fmul(a.value, b.value)
+                } else {
+                    builder.ins().imul(a.value, b.value)
+                };
+
+                Ok(TypedValue {
+                    value,
+                    nl_type: a.nl_type,
+                })
+            }
+            OpOperator::ArithmeticDiv((a, b)) => {
+                let (a, b) = Self::compile_numeric_operands(scope, builder, a, b)?;
+
+                // Dividing an integer by zero traps.
+                let value = if a.nl_type.is_float() {
+                    builder.ins().fdiv(a.value, b.value)
+                } else if a.nl_type.is_signed() {
+                    builder.ins().sdiv(a.value, b.value)
+                } else {
+                    builder.ins().udiv(a.value, b.value)
+                };
+
+                Ok(TypedValue {
+                    value,
+                    nl_type: a.nl_type,
+                })
+            }
+            OpOperator::ArithmeticMod((a, b)) => {
+                let (a, b) = Self::compile_numeric_operands(scope, builder, a, b)?;
+
+                // Cranelift has no instruction for the remainder of floats.
+                let value = if a.nl_type.is_float() {
+                    return Err(CompileError::UnsupportedOperand(a.nl_type));
+                } else if a.nl_type.is_signed() {
+                    builder.ins().srem(a.value, b.value)
+                } else {
+                    builder.ins().urem(a.value, b.value)
+                };
+
+                Ok(TypedValue {
+                    value,
+                    nl_type: a.nl_type,
+                })
+            }
+            OpOperator::CompareEqual(pair) => Self::compile_comparison(
+                scope,
+                builder,
+                pair,
+                IntCC::Equal,
+                IntCC::Equal,
+                FloatCC::Equal,
+            ),
+            OpOperator::CompareNotEqual(pair) => Self::compile_comparison(
+                scope,
+                builder,
+                pair,
+                IntCC::NotEqual,
+                IntCC::NotEqual,
+                FloatCC::NotEqual,
+            ),
+            OpOperator::CompareGreater(pair) => Self::compile_comparison(
+                scope,
+                builder,
+                pair,
+                IntCC::SignedGreaterThan,
+                IntCC::UnsignedGreaterThan,
+                FloatCC::GreaterThan,
+            ),
+            OpOperator::CompareLess(pair) => Self::compile_comparison(
+                scope,
+                builder,
+                pair,
+                IntCC::SignedLessThan,
+                IntCC::UnsignedLessThan,
+                FloatCC::LessThan,
+            ),
+            OpOperator::CompareGreaterEqual(pair) => Self::compile_comparison(
+                scope,
+                builder,
+                pair,
+                IntCC::SignedGreaterThanOrEqual,
+                IntCC::UnsignedGreaterThanOrEqual,
+                FloatCC::GreaterThanOrEqual,
+            ),
+            OpOperator::CompareLessEqual(pair) => Self::compile_comparison(
+                scope,
+                builder,
+                pair,
+                IntCC::SignedLessThanOrEqual,
+                IntCC::UnsignedLessThanOrEqual,
+                FloatCC::LessThanOrEqual,
+            ),
+            OpOperator::Range(_) => Err(CompileError::Unsupported("ranges")),
+            OpOperator::PropError(_) => Err(CompileError::Unsupported("the `?` operator")),
+        }
+    }
+
+    // Compiles the operand of a bitwise operator, which must be an integer.
+    fn compile_integer<'a>(
+        scope: &mut StackScope<'a>,
+        builder: &mut FunctionBuilder,
+        operation: &'a NLOperation<'a>,
+    ) -> Result<'a, TypedValue<'a>> {
+        let operand = Self::compile_value(scope, builder, operation)?;
+
+        if operand.nl_type.is_integer() {
+            Ok(operand)
+        } else {
+            Err(CompileError::UnsupportedOperand(operand.nl_type))
+        }
+    }
+
+    // Compiles both operands of a bitwise operator, which must be integers of the same type.
+    fn compile_integer_operands<'a>(
+        scope: &mut StackScope<'a>,
+        builder: &mut FunctionBuilder,
+        a: &'a NLOperation<'a>,
+        b: &'a NLOperation<'a>,
+    ) -> Result<'a, (TypedValue<'a>, TypedValue<'a>)> {
+        let (a, b) = Self::compile_operands(scope, builder, a, b)?;
+
+        if a.nl_type.is_integer() {
+            Ok((a, b))
+        } else {
+            Err(CompileError::UnsupportedOperand(a.nl_type))
+        }
+    }
+
+    // The right side of `&&` and `||` is only evaluated when the left side doesn't already decide the result.
+    // For `&&` that's when the left side is true, and for `||` when it's false. `short_circuit_on` is the value of
+    // the left side that skips the right.
+    fn compile_short_circuit<'a>(
+        scope: &mut StackScope<'a>,
+        builder: &mut FunctionBuilder,
+        a: &'a NLOperation<'a>,
+        b: &'a NLOperation<'a>,
+        short_circuit_on: bool,
+    ) -> Result<'a, TypedValue<'a>> {
+        let a = Self::compile_boolean(scope, builder, a)?;
+
+        let right_block = builder.create_block();
+        let merge_block = builder.create_block();
+        let result = builder.append_block_param(merge_block, types::B1);
+
+        // The left side is the result when it short circuits.
+        if short_circuit_on {
+            builder.ins().brnz(a, merge_block, &[a]);
+        } else {
+            builder.ins().brz(a, merge_block, &[a]);
+        }
+        builder.ins().jump(right_block, &[]);
+
+        builder.switch_to_block(right_block);
+        builder.seal_block(right_block);
+        let b = Self::compile_boolean(scope, builder, b)?;
+        builder.ins().jump(merge_block, &[b]);
+
+        builder.switch_to_block(merge_block);
+        builder.seal_block(merge_block);
+
+        Ok(TypedValue {
+            value: result,
+            nl_type: NLType::Boolean,
+        })
+    }
+
+    // Compiles the operand of a logical operator, which must be a boolean.
+    fn compile_boolean<'a>(
+        scope: &mut StackScope<'a>,
+        builder: &mut FunctionBuilder,
+        operation: &'a NLOperation<'a>,
+    ) -> Result<'a, Value> {
+        let operand = Self::compile_value(scope, builder, operation)?;
+
+        if operand.nl_type.is_boolean() {
+            Ok(operand.value)
+        } else {
+            Err(CompileError::UnsupportedOperand(operand.nl_type))
+        }
+    }
+
+    fn compile_assignment<'a>(
+        scope: &mut StackScope<'a>,
+        builder: &mut FunctionBuilder,
+        assignment: &'a OpAssignment<'a>,
+    ) -> Result<'a, ()> {
+        let variables = assignment.get_variable_to_assign();
+        if variables.len() != 1 || assignment.get_struct_pattern().is_some() {
+            // TODO destructuring tuples and structs.
+            return Err(CompileError::Unsupported("destructuring assignments"));
+        }
+
+        let name = variables[0].get_name();
+        // Constants can take the type of the variable they're being assigned to.
+        let expected_type = if assignment.is_new() {
+            assignment.get_types().first().cloned()
+        } else {
+            scope
+                .get_variable(name)
+                .map(|tracker| tracker.var_type.clone())
+        };
+        let value = Self::compile_expected(
+            scope,
+            builder,
+            assignment.get_value(),
+            expected_type.as_ref(),
+        )?;
+
+        // The value has to match the type the variable was given, or the type it was declared with.
+        if let Some(var_type) = expected_type {
+            if var_type != value.nl_type {
+                return Err(CompileError::MismatchedAssignment(
+                    name,
+                    var_type,
+                    value.nl_type,
+                ));
+            }
+        }
+
+        let variable = if assignment.is_new() {
+            scope.declare_variable(builder, name, value.nl_type)?
+        } else {
+            scope
+                .get_variable(name)
+                .ok_or(CompileError::VariableUndefined(name))?
+                .variable
+        };
+
+        builder.def_var(variable, value.value);
+
+        Ok(())
+    }
+
+    // Both branches of an if statement must produce the same type, which becomes the type of the whole statement.
+    // The value is passed to the block after the if statement as a block parameter. An if statement without an
+    // else block produces no value.
+    fn compile_if<'a>(
+        scope: &mut StackScope<'a>,
+        builder: &mut FunctionBuilder,
+        if_statement: &'a IfStatement<'a>,
+    ) -> Result<'a, Option<TypedValue<'a>>> {
+        let condition = Self::compile_value(scope, builder, if_statement.get_condition())?;
+
+        let true_block = builder.create_block();
+        let false_block = builder.create_block();
+        let merge_block = builder.create_block();
+
+        builder.ins().brz(condition.value, false_block, &[]);
+        builder.ins().jump(true_block, &[]);
+
+        builder.switch_to_block(true_block);
+        builder.seal_block(true_block);
+        let true_value = Self::compile_block(scope, builder, if_statement.get_true_block())?;
+
+        let true_value = if if_statement.has_else_block() {
+            true_value
+        } else {
+            None
+        };
+
+        // Now that we know what the true branch produces, the merge block can take it as a parameter.
+        let result = match &true_value {
+            Some(true_value) => {
+                let crane_type = Self::crane_type(&true_value.nl_type)?;
+                let parameter = builder.append_block_param(merge_block, crane_type);
+                builder.ins().jump(merge_block, &[true_value.value]);
+
+                Some(TypedValue {
+                    value: parameter,
+                    nl_type: true_value.nl_type.clone(),
+                })
+            }
+            None => {
+                builder.ins().jump(merge_block, &[]);
+                None
+            }
+        };
+
+        builder.switch_to_block(false_block);
+        builder.seal_block(false_block);
+        let false_value = match if_statement.get_false_block() {
+            Some(false_block) => Self::compile_block(scope, builder, false_block)?,
+            None => None,
+        };
+
+        match (true_value, false_value) {
+            (Some(true_value), Some(false_value)) => {
+                if true_value.nl_type != false_value.nl_type {
+                    return Err(CompileError::MismatchedBranchTypes(
+                        true_value.nl_type,
+                        false_value.nl_type,
+                    ));
                 }
-                NLOperation::FunctionCall(_function_call) => {
-                    unimplemented!()
+
+                builder.ins().jump(merge_block, &[false_value.value]);
+            }
+            (None, None) => {
+                builder.ins().jump(merge_block, &[]);
+            }
+            (true_value, false_value) => {
+                let type_of = |value: Option<TypedValue<'a>>| match value {
+                    Some(value) => value.nl_type,
+                    None => NLType::None,
+                };
+
+                return Err(CompileError::MismatchedBranchTypes(
+                    type_of(true_value),
+                    type_of(false_value),
+                ));
+            }
+        }
+
+        builder.switch_to_block(merge_block);
+        builder.seal_block(merge_block);
+
+        Ok(result)
+    }
+
+    // A loop is a single block that jumps back to its own start, so the only way out is a `break`. The block after
+    // the loop is created even when nothing breaks to it, since the rest of the function still needs somewhere to
+    // go. Breaks that carry a value pass it to that block as a parameter, which becomes the value of the loop.
+    fn compile_loop<'a>(
+        scope: &mut StackScope<'a>,
+        builder: &mut FunctionBuilder,
+        label: Option<&'a str>,
+        body: &'a NLBlock<'a>,
+    ) -> Result<'a, Option<TypedValue<'a>>> {
+        let body_block = builder.create_block();
+        let exit_block = builder.create_block();
+
+        builder.ins().jump(body_block, &[]);
+
+        // The body can't be sealed until the jump back to its start has been added.
+        builder.switch_to_block(body_block);
+
+        scope.loops.push(LoopExit {
+            label,
+            exit_block,
+            break_type: None,
+        });
+        let result = Self::compile_block(scope, builder, body);
+        let exit = scope
+            .loops
+            .pop()
+            .expect("The loop we pushed should still be there.");
+        result?;
+
+        // A body that ends in a break has nothing left to jump back with.
+        if !builder.is_unreachable() {
+            builder.ins().jump(body_block, &[]);
+        }
+        builder.seal_block(body_block);
+
+        builder.switch_to_block(exit_block);
+        builder.seal_block(exit_block);
+
+        match exit.break_type {
+            Some(NLType::None) | None => Ok(None),
+            Some(nl_type) => Ok(Some(TypedValue {
+                value: builder.block_params(exit_block)[0],
+                nl_type,
+            })),
+        }
+    }
+
+    // Jumps to the block after the loop being broken out of. Every break out of a loop must give a value of the
+    // same type, or no value at all.
+    fn compile_break<'a>(
+        scope: &mut StackScope<'a>,
+        builder: &mut FunctionBuilder,
+        label: Option<&'a str>,
+        value: Option<&'a NLOperation<'a>>,
+    ) -> Result<'a, ()> {
+        let value = match value {
+            Some(value) => Some(Self::compile_value(scope, builder, value)?),
+            None => None,
+        };
+        let value_type = match &value {
+            Some(value) => value.nl_type.clone(),
+            None => NLType::None,
+        };
+
+        let exit = scope.get_loop(label)?;
+        match &exit.break_type {
+            Some(break_type) if *break_type != value_type => {
+                return Err(CompileError::MismatchedBreakTypes(
+                    break_type.clone(),
+                    value_type,
+                ));
+            }
+            Some(_) => {}
+            None => {
+                // This is the first break, so nothing has jumped to the exit yet and it can still take a parameter.
+                if value.is_some() {
+                    builder.append_block_param(exit.exit_block, Self::crane_type(&value_type)?);
                 }
+                exit.break_type = Some(value_type);
             }
         }
 
-        unimplemented!()
+        let arguments: Vec<Value> = value.iter().map(|value| value.value).collect();
+        builder.ins().jump(exit.exit_block, &arguments);
+
+        // Anything after the break can never run, but it still needs a block to go in.
+        let after_break = builder.create_block();
+        builder.switch_to_block(after_break);
+        builder.seal_block(after_break);
+
+        Ok(())
     }
 }
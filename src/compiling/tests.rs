@@ -1,4 +1,820 @@
 use super::*;
 
+use cranelift::codegen::ir::Opcode;
 use unwrap_to::unwrap_to;
 
+/// Compiles the first function of the code and hands the IR to the check.
+fn compile_function<F>(code: &str, check: F)
+where
+    F: Fn(&Compiler, Result<()>),
+{
+    let file = parse_string(code, "virtual_file").unwrap();
+    let function = &file.get_functions()[0];
+
+    let mut compiler = Compiler::new();
+    let result = compiler.compile_function(function);
+
+    check(&compiler, result);
+}
+
+/// Compiles the first function of the code, then runs it without any arguments.
+fn run_function<R>(code: &str) -> R {
+    let file = parse_string(code, "virtual_file").unwrap();
+    let function = &file.get_functions()[0];
+
+    let mut compiler = Compiler::new();
+    compiler.compile_function(function).unwrap();
+
+    let id = compiler
+        .module
+        .declare_function(
+            function.get_name(),
+            Linkage::Export,
+            &compiler.ctx.func.signature,
+        )
+        .unwrap();
+    compiler
+        .module
+        .define_function(
+            id,
+            &mut compiler.ctx,
+            &mut codegen::binemit::NullTrapSink {},
+        )
+        .unwrap();
+    compiler.module.finalize_definitions();
+
+    let function = compiler.module.get_finalized_function(id);
+    let function = unsafe { std::mem::transmute::<_, fn() -> R>(function) };
+
+    function()
+}
+
+/// Lists the opcode of every instruction in the compiled function, in layout order.
+fn opcodes(compiler: &Compiler) -> Vec<Opcode> {
+    let func = &compiler.ctx.func;
+    func.layout
+        .blocks()
+        .flat_map(|block| func.layout.block_insts(block))
+        .map(|inst| func.dfg[inst].opcode())
+        .collect()
+}
+
+/// Makes sure Cranelift is happy with the IR we produced.
+fn verify(compiler: &Compiler) {
+    if let Err(errors) = codegen::verify_function(&compiler.ctx.func, compiler.module.isa()) {
+        panic!("{}", errors);
+    }
+}
+
+mod if_statements {
+    use super::*;
+
+    #[test]
+    fn branch_types_match() {
+        let code = "fn test(c: bool) -> i32 { let x = if c { 1 } else { 2 } x }";
+        compile_function(code, |compiler, result| {
+            result.unwrap();
+            verify(compiler);
+        });
+
+        let code = "fn test() -> i32 { let c = true let x = if c { 1 } else { 2 } x }";
+        assert_eq!(run_function::<i32>(code), 1);
+
+        let code = "fn test() -> i32 { let c = false let x = if c { 1 } else { 2 } x }";
+        assert_eq!(run_function::<i32>(code), 2);
+    }
+
+    #[test]
+    fn branch_types_mismatch() {
+        let code = "fn test(c: bool) -> i32 { let x = if c { 1 } else { true } x }";
+        compile_function(code, |_compiler, result| match result {
+            Err(CompileError::MismatchedBranchTypes(true_type, false_type)) => {
+                assert_eq!(true_type, NLType::I32);
+                assert_eq!(false_type, NLType::Boolean);
+            }
+            _ => panic!("Expected the branch types to mismatch."),
+        });
+
+        // An empty else block has no value, which doesn't match the true block.
+        let code = "fn test(c: bool) { if c { 1 } else {} }";
+        compile_function(code, |_compiler, result| match result {
+            Err(CompileError::MismatchedBranchTypes(true_type, false_type)) => {
+                assert_eq!(true_type, NLType::I32);
+                assert_eq!(false_type, NLType::None);
+            }
+            _ => panic!("Expected the branch types to mismatch."),
+        });
+    }
+
+    #[test]
+    fn without_else() {
+        // Without an else block, the value of the true block is dropped.
+        let code = "fn test(c: bool) { if c { 1 } }";
+        compile_function(code, |compiler, result| {
+            result.unwrap();
+            verify(compiler);
+        });
+    }
+}
+
+mod loops {
+    use super::*;
+
+    #[test]
+    fn loop_with_break() {
+        let code = "fn test() { loop { break } }";
+        compile_function(code, |compiler, result| {
+            result.unwrap();
+            verify(compiler);
+        });
+
+        // Makes sure the IR can be finalized and run.
+        run_function::<()>(code);
+    }
+
+    #[test]
+    fn loop_value() {
+        let code = "fn test() -> i32 { let done = false loop { if done { break 7 } done = true } }";
+        assert_eq!(run_function::<i32>(code), 7);
+
+        // Breaking out of the outer loop skips the rest of both.
+        let code = "fn test() -> bool { 'outer: loop { loop { break 'outer true } } }";
+        assert!(run_function::<bool>(code));
+    }
+
+    #[test]
+    fn infinite_loop() {
+        // Nothing can reach the end of the function, so it traps instead of returning.
+        let code = "fn test() { loop {} }";
+        compile_function(code, |compiler, result| {
+            result.unwrap();
+            verify(compiler);
+
+            let opcodes = opcodes(compiler);
+            assert!(opcodes.contains(&Opcode::Trap), "{:?}", opcodes);
+            assert!(!opcodes.contains(&Opcode::Return), "{:?}", opcodes);
+        });
+    }
+
+    #[test]
+    fn break_errors() {
+        let code = "fn test() { break }";
+        compile_function(code, |_compiler, result| match result {
+            Err(CompileError::BreakOutsideLoop) => {}
+            _ => panic!("Expected the break to be outside of a loop."),
+        });
+
+        let code = "fn test() { loop { break 'missing } }";
+        compile_function(code, |_compiler, result| match result {
+            Err(CompileError::LabelUndefined(label)) => assert_eq!(label, "missing"),
+            _ => panic!("Expected the label to be undefined."),
+        });
+
+        let code = "fn test(c: bool) { loop { if c { break 1 } break true } }";
+        compile_function(code, |_compiler, result| match result {
+            Err(CompileError::MismatchedBreakTypes(first, second)) => {
+                assert_eq!(first, NLType::I32);
+                assert_eq!(second, NLType::Boolean);
+            }
+            _ => panic!("Expected the breaks to have mismatched types."),
+        });
+
+        let code = "fn test() { loop { break \"text\" } }";
+        compile_function(code, |_compiler, result| match result {
+            Err(CompileError::UnsupportedType(_)) => {}
+            _ => panic!("Expected the break value to be unsupported."),
+        });
+    }
+
+    #[test]
+    fn labeled_while_loop() {
+        let code = "fn test(c: bool) { 'outer: while c {} }";
+        compile_function(code, |_compiler, result| match result {
+            Err(CompileError::Unsupported(_)) => {}
+            _ => panic!("Expected the label to be unsupported."),
+        });
+    }
+}
+
+mod operators {
+    use super::*;
+
+    #[test]
+    fn logical_xor() {
+        let code = "fn test() -> bool { true ^^ false }";
+        assert_eq!(run_function::<bool>(code), true);
+
+        let code = "fn test() -> bool { true ^^ true }";
+        assert_eq!(run_function::<bool>(code), false);
+    }
+
+    #[test]
+    fn logical_and_short_circuits() {
+        let code = "fn test(a: bool, b: bool) -> bool { a && b }";
+        compile_function(code, |compiler, result| {
+            result.unwrap();
+            verify(compiler);
+        });
+
+        // A false left side skips the right side, so the assignment never happens.
+        let code = "fn test() -> i32 { let x = 0 false && { x = 1 true } x }";
+        assert_eq!(run_function::<i32>(code), 0);
+
+        let code = "fn test() -> i32 { let x = 0 true && { x = 1 true } x }";
+        assert_eq!(run_function::<i32>(code), 1);
+
+        let code = "fn test() -> bool { false && true }";
+        assert_eq!(run_function::<bool>(code), false);
+
+        let code = "fn test() -> bool { true && true }";
+        assert_eq!(run_function::<bool>(code), true);
+    }
+
+    #[test]
+    fn logical_or() {
+        let code = "fn test() -> bool { false || true }";
+        assert_eq!(run_function::<bool>(code), true);
+
+        let code = "fn test() -> bool { false || false }";
+        assert_eq!(run_function::<bool>(code), false);
+    }
+
+    #[test]
+    fn logical_negate() {
+        let code = "fn test(x: bool) -> bool { !x }";
+        compile_function(code, |compiler, result| {
+            result.unwrap();
+            verify(compiler);
+
+            let ir = compiler.ctx.func.display(None).to_string();
+            assert!(ir.contains("bnot"), "{}", ir);
+        });
+
+        let code = "fn test() -> bool { !false }";
+        assert_eq!(run_function::<bool>(code), true);
+    }
+
+    #[test]
+    fn negate() {
+        // A minus sign right before a number is part of the literal, so a variable is needed to get an ineg.
+        let code = "fn test(x: i32) -> i32 { -x }";
+        compile_function(code, |compiler, result| {
+            result.unwrap();
+            verify(compiler);
+
+            let ir = compiler.ctx.func.display(None).to_string();
+            assert!(ir.contains("ineg"), "{}", ir);
+        });
+
+        let code = "fn test(x: f32) -> f32 { -x }";
+        compile_function(code, |compiler, result| {
+            result.unwrap();
+            verify(compiler);
+
+            let ir = compiler.ctx.func.display(None).to_string();
+            assert!(ir.contains("fneg"), "{}", ir);
+        });
+
+        let code = "fn test() -> i32 { let x = 5i32; -x }";
+        assert_eq!(run_function::<i32>(code), -5);
+    }
+
+    #[test]
+    fn negate_unsigned() {
+        let code = "fn test(x: u32) -> u32 { -x }";
+        compile_function(code, |_compiler, result| match result {
+            Err(CompileError::UnsupportedOperand(nl_type)) => assert_eq!(nl_type, NLType::U32),
+            _ => panic!("Expected the operand to be rejected."),
+        });
+    }
+
+    #[test]
+    fn bit_and() {
+        let code = "fn test() -> i32 { 1 & 2 }";
+        compile_function(code, |compiler, result| {
+            result.unwrap();
+            verify(compiler);
+
+            let ir = compiler.ctx.func.display(None).to_string();
+            assert!(ir.contains("band"), "{}", ir);
+        });
+
+        let code = "fn test() -> i32 { 6 & 3 }";
+        assert_eq!(run_function::<i32>(code), 2);
+    }
+
+    #[test]
+    fn bit_shifts() {
+        let code = "fn test() -> i32 { 1 << 2 }";
+        compile_function(code, |compiler, result| {
+            result.unwrap();
+            verify(compiler);
+
+            let ir = compiler.ctx.func.display(None).to_string();
+            assert!(ir.contains("ishl"), "{}", ir);
+        });
+
+        let code = "fn test() -> i32 { 1 << 2 }";
+        assert_eq!(run_function::<i32>(code), 4);
+
+        // Right shifts keep the sign of signed values, but not of unsigned ones.
+        let code = "fn test(a: i32) -> i32 { a >> 1 }";
+        compile_function(code, |compiler, result| {
+            result.unwrap();
+            let ir = compiler.ctx.func.display(None).to_string();
+            assert!(ir.contains("sshr"), "{}", ir);
+        });
+
+        let code = "fn test(a: u32) -> u32 { a >> 1 }";
+        compile_function(code, |compiler, result| {
+            result.unwrap();
+            let ir = compiler.ctx.func.display(None).to_string();
+            assert!(ir.contains("ushr"), "{}", ir);
+        });
+    }
+
+    #[test]
+    fn bit_operators_need_integers() {
+        let code = "fn test() -> f32 { 1.5 & 2.5 }";
+        compile_function(code, |_compiler, result| match result {
+            Err(CompileError::UnsupportedOperand(nl_type)) => assert_eq!(nl_type, NLType::F32),
+            _ => panic!("Expected the operands to be rejected."),
+        });
+    }
+
+    #[test]
+    fn arithmetic() {
+        let code = "fn test() -> i32 { 2 + 3 * 4 - 1 }";
+        assert_eq!(run_function::<i32>(code), 13);
+
+        // Signed and unsigned integers round and take remainders differently.
+        let code = "fn test() -> i32 { -7 / 2 }";
+        assert_eq!(run_function::<i32>(code), -3);
+        let code = "fn test() -> i32 { -7 % 3 }";
+        assert_eq!(run_function::<i32>(code), -1);
+        let code = "fn test() -> u8 { 250u8 / 100 }";
+        assert_eq!(run_function::<u8>(code), 2);
+
+        let code = "fn test() -> f64 { let x: f64 = 1.5 x * 2.0 + 0.25 }";
+        assert_eq!(run_function::<f64>(code), 3.25);
+    }
+
+    #[test]
+    fn arithmetic_errors() {
+        let code = "fn test() -> f32 { 1.5 % 2.5 }";
+        compile_function(code, |_compiler, result| match result {
+            Err(CompileError::UnsupportedOperand(nl_type)) => assert_eq!(nl_type, NLType::F32),
+            _ => panic!("Expected the operands to be rejected."),
+        });
+
+        let code = "fn test(a: i32, b: i64) -> i32 { a + b }";
+        compile_function(code, |_compiler, result| match result {
+            Err(CompileError::MismatchedOperands(a, b)) => {
+                assert_eq!((a, b), (NLType::I32, NLType::I64))
+            }
+            _ => panic!("Expected the operands to be rejected."),
+        });
+
+        let code = "fn test() -> bool { true + false }";
+        compile_function(code, |_compiler, result| match result {
+            Err(CompileError::UnsupportedOperand(nl_type)) => {
+                assert_eq!(nl_type, NLType::Boolean)
+            }
+            _ => panic!("Expected the operands to be rejected."),
+        });
+    }
+
+    #[test]
+    fn comparisons() {
+        let code = "fn test() -> bool { 1 + 1 == 2 }";
+        assert!(run_function::<bool>(code));
+        let code = "fn test() -> bool { -1 < 1 }";
+        assert!(run_function::<bool>(code));
+        let code = "fn test() -> bool { 1.5 >= 2.5 }";
+        assert!(!run_function::<bool>(code));
+        let code = "fn test() -> bool { true != false }";
+        assert!(run_function::<bool>(code));
+        let code = "fn test() -> bool { true == false }";
+        assert!(!run_function::<bool>(code));
+
+        // 255 is -1 when read as signed, so this only works if the comparison is unsigned.
+        let code = "fn test() -> bool { 255u8 > 1 }";
+        assert!(run_function::<bool>(code));
+
+        let code = "fn test() -> bool { true < false }";
+        compile_function(code, |_compiler, result| match result {
+            Err(CompileError::UnsupportedOperand(nl_type)) => {
+                assert_eq!(nl_type, NLType::Boolean)
+            }
+            _ => panic!("Expected the operands to be rejected."),
+        });
+    }
+
+    #[test]
+    fn logical_xor_needs_booleans() {
+        let code = "fn test() -> bool { 1 ^^ 2 }";
+        compile_function(code, |_compiler, result| match result {
+            Err(CompileError::UnsupportedOperand(nl_type)) => assert_eq!(nl_type, NLType::I32),
+            _ => panic!("Expected the operands to be rejected."),
+        });
+    }
+}
+
+mod constants {
+    use super::*;
+
+    #[test]
+    fn float_constants() {
+        let code = "fn test() { let x: f32 = 1.5; let y: f64 = 2.5; }";
+        compile_function(code, |compiler, result| {
+            result.unwrap();
+            verify(compiler);
+
+            let ir = compiler.ctx.func.display(None).to_string();
+            assert!(ir.contains("f32const"), "{}", ir);
+            assert!(ir.contains("f64const"), "{}", ir);
+        });
+    }
+
+    #[test]
+    fn float_constant_value() {
+        let code = "fn test() -> f64 { let y: f64 = 2.5 y }";
+        assert_eq!(run_function::<f64>(code), 2.5);
+    }
+}
+
+mod assignments {
+    use super::*;
+
+    #[test]
+    fn literal_takes_annotation() {
+        let code = "fn test() -> u8 { let x: u8 = 200 x }";
+        assert_eq!(run_function::<u8>(code), 200);
+    }
+
+    #[test]
+    fn mismatched_annotation() {
+        let code = "fn test() { let x: i32 = 1.5 }";
+        compile_function(code, |_compiler, result| match result {
+            Err(CompileError::MismatchedAssignment(name, var_type, value_type)) => {
+                assert_eq!(name, "x");
+                assert_eq!((var_type, value_type), (NLType::I32, NLType::F32));
+            }
+            _ => panic!("Expected the assignment to be rejected."),
+        });
+
+        // Reassigning can't change the type of the variable.
+        let code = "fn test() { let x = 1 x = true }";
+        compile_function(code, |_compiler, result| match result {
+            Err(CompileError::MismatchedAssignment(name, var_type, value_type)) => {
+                assert_eq!(name, "x");
+                assert_eq!((var_type, value_type), (NLType::I32, NLType::Boolean));
+            }
+            _ => panic!("Expected the assignment to be rejected."),
+        });
+    }
+
+    #[test]
+    fn destructuring() {
+        let code = "fn test() { let (a, b) = (1, 2) }";
+        compile_function(code, |_compiler, result| match result {
+            Err(CompileError::Unsupported(_)) => {}
+            _ => panic!("Expected destructuring to be unsupported."),
+        });
+    }
+}
+
+mod returns {
+    use super::*;
+
+    #[test]
+    fn tuple_return_signature() {
+        let code = "fn divmod(a: i32, b: i32) -> (i32, i32) { (a, b) }";
+        compile_function(code, |compiler, result| {
+            result.unwrap();
+            verify(compiler);
+
+            let signature = &compiler.ctx.func.signature;
+            assert_eq!(
+                signature.returns,
+                vec![AbiParam::new(types::I32), AbiParam::new(types::I32)]
+            );
+        });
+    }
+
+    #[test]
+    fn tuple_return_values() {
+        let code = "fn swap(a: i32, b: bool) -> (bool, i32) { let c = !b; (c, a) }";
+        compile_function(code, |compiler, result| {
+            result.unwrap();
+            verify(compiler);
+
+            // Each element of the tuple is returned as its own value.
+            let ir = compiler.ctx.func.display(None).to_string();
+            assert!(ir.contains("return v2, v0"), "{}", ir);
+        });
+    }
+
+    #[test]
+    fn unit_return() {
+        let code = "fn test() { () }";
+        compile_function(code, |compiler, result| {
+            result.unwrap();
+            verify(compiler);
+
+            assert!(compiler.ctx.func.signature.returns.is_empty());
+        });
+    }
+}
+
+mod contexts {
+    use super::*;
+
+    #[test]
+    fn compile_in_sequence() {
+        let code = "fn first(a: i32, b: i32) -> i32 { a | b } fn second(c: bool) -> bool { !c }";
+        let file = parse_string(code, "virtual_file").unwrap();
+        let functions = file.get_functions();
+
+        let mut compiler = Compiler::new();
+
+        compiler.compile_function(&functions[0]).unwrap();
+        verify(&compiler);
+        assert_eq!(compiler.ctx.func.signature.params.len(), 2);
+
+        // Nothing from the first function should be left over.
+        compiler.compile_function(&functions[1]).unwrap();
+        verify(&compiler);
+        assert_eq!(
+            compiler.ctx.func.signature.params,
+            vec![AbiParam::new(types::B1)]
+        );
+
+        let ir = compiler.ctx.func.display(None).to_string();
+        assert!(!ir.contains("bor"), "{}", ir);
+    }
+}
+
+mod signatures {
+    use super::*;
+
+    #[test]
+    fn struct_argument() {
+        let code = "fn f(x: SomeStruct) {}";
+        compile_function(code, |_compiler, result| match result {
+            Err(CompileError::UnsupportedType(_)) => {}
+            _ => panic!("Expected the struct to be unsupported."),
+        });
+    }
+
+    #[test]
+    fn reference_return() {
+        let code = "fn f() -> (i32, &SomeStruct) {}";
+        compile_function(code, |_compiler, result| match result {
+            Err(CompileError::UnsupportedType(_)) => {}
+            _ => panic!("Expected the reference to be unsupported."),
+        });
+    }
+}
+
+mod function_calls {
+    use super::*;
+
+    use cranelift_module::FuncOrDataId;
+
+    /// Compiles the whole file, then runs the named function without any arguments.
+    fn run_file<R>(code: &str, name: &str) -> R {
+        let file = parse_string(code, "virtual_file").unwrap();
+
+        let mut compiler = Compiler::new();
+        compiler.compile_file(&file).unwrap();
+
+        let id = match compiler.module.get_name(name) {
+            Some(FuncOrDataId::Func(id)) => id,
+            _ => panic!("Function {} was not declared.", name),
+        };
+        let function = compiler.module.get_finalized_function(id);
+        let function = unsafe { std::mem::transmute::<_, fn() -> R>(function) };
+
+        function()
+    }
+
+    /// Compiles the whole file and hands back the error.
+    fn file_error(code: &'static str) -> CompileError<'static> {
+        // The error can borrow from the file, so the file has to outlive the test.
+        let file = Box::leak(Box::new(parse_string(code, "virtual_file").unwrap()));
+
+        let mut compiler = Compiler::new();
+        match compiler.compile_file(file) {
+            Err(error) => error,
+            Ok(()) => panic!("Expected the file to fail to compile."),
+        }
+    }
+
+    #[test]
+    fn call_function() {
+        let code = "fn main() -> i32 { return combine(2, 4) }
+                    fn combine(a: i32, b: i32) -> i32 { a * 10 + b }";
+        assert_eq!(run_file::<i32>(code, "main"), 24);
+
+        let code = "fn main() -> i32 { add(2, 3) }
+                    fn add(a: i32, b: i32) -> i32 { a + b }";
+        assert_eq!(run_file::<i32>(code, "main"), 5);
+    }
+
+    #[test]
+    fn literal_arguments() {
+        // Literals take the type of the argument they're given to.
+        let code = "fn main() -> u8 { add(200, 50) }
+                    fn add(a: u8, b: u8) -> u8 { a + b }";
+        assert_eq!(run_file::<u8>(code, "main"), 250);
+    }
+
+    #[test]
+    fn default_arguments() {
+        let code = "fn main() -> i32 { add(2) }
+                    fn add(a: i32, b: i32 = 3) -> i32 { a + b }";
+        assert_eq!(run_file::<i32>(code, "main"), 5);
+    }
+
+    #[test]
+    fn argument_errors() {
+        let code = "fn main() -> i32 { add(1) }
+                    fn add(a: i32, b: i32) -> i32 { a + b }";
+        match file_error(code) {
+            CompileError::WrongArgumentCount(name, expected, given) => {
+                assert_eq!((name, expected, given), ("add", 2, 1))
+            }
+            error => panic!("Expected the wrong number of arguments, got {:?}.", error),
+        }
+
+        let code = "fn main() -> i32 { add(1, 2, 3) }
+                    fn add(a: i32, b: i32) -> i32 { a + b }";
+        match file_error(code) {
+            CompileError::WrongArgumentCount(name, expected, given) => {
+                assert_eq!((name, expected, given), ("add", 2, 3))
+            }
+            error => panic!("Expected the wrong number of arguments, got {:?}.", error),
+        }
+
+        let code = "fn main() -> i32 { add(true, 2) }
+                    fn add(a: i32, b: i32) -> i32 { a + b }";
+        match file_error(code) {
+            CompileError::MismatchedArgument(name, expected, given) => {
+                assert_eq!(name, "add");
+                assert_eq!((expected, given), (NLType::I32, NLType::Boolean));
+            }
+            error => panic!("Expected a mismatched argument, got {:?}.", error),
+        }
+    }
+
+    #[test]
+    fn duplicate_function() {
+        let code = "fn f() -> i32 { 1 } fn f() -> bool { true }";
+        match file_error(code) {
+            CompileError::Module(_) => {}
+            error => panic!("Expected the module to reject the name, got {:?}.", error),
+        }
+    }
+
+    #[test]
+    fn tuple_returning_call() {
+        // The values of the call can be handed straight back.
+        let code = "fn main() -> (i32, bool) { pair() }
+                    fn other() -> (i32, bool) { return pair() }
+                    fn pair() -> (i32, bool) { (1, true) }";
+        let file = parse_string(code, "virtual_file").unwrap();
+        Compiler::new().compile_file(&file).unwrap();
+
+        // But they can't be kept in a single value yet.
+        let code = "fn main() -> i32 { let x = pair() 1 }
+                    fn pair() -> (i32, bool) { (1, true) }";
+        match file_error(code) {
+            CompileError::UnsupportedType(NLType::Tuple(_)) => {}
+            error => panic!("Expected the tuple to be unsupported, got {:?}.", error),
+        }
+    }
+
+    #[test]
+    fn lone_function_cannot_call() {
+        let code = "fn main() -> bool { flip(true) }
+                    fn flip(a: bool) -> bool { !a }";
+        compile_function(code, |_compiler, result| match result {
+            // Functions compiled on their own can't see the rest of the file.
+            Err(CompileError::FunctionUndefined(name)) => assert_eq!(name, "flip"),
+            _ => panic!("Expected the function to be undefined."),
+        });
+    }
+
+    #[test]
+    fn undefined_function() {
+        let code = "fn main() -> i32 { missing(1) }";
+        let file = parse_string(code, "virtual_file").unwrap();
+
+        let mut compiler = Compiler::new();
+        match compiler.compile_file(&file) {
+            Err(CompileError::FunctionUndefined(name)) => assert_eq!(name, "missing"),
+            _ => panic!("Expected the function to be undefined."),
+        }
+    }
+}
+
+mod unsupported {
+    use super::*;
+
+    #[test]
+    fn cast() {
+        let code = "fn test() -> i64 { 5 as i64 }";
+        compile_function(code, |_compiler, result| match result {
+            Err(CompileError::UnsupportedType(nl_type)) => assert_eq!(nl_type, NLType::I64),
+            _ => panic!("Expected the cast to be unsupported."),
+        });
+    }
+
+    #[test]
+    fn struct_literal() {
+        let code = "fn test() { let p = Point { x: 1, y: 2 } }";
+        compile_function(code, |_compiler, result| match result {
+            Err(CompileError::UnsupportedType(nl_type)) => {
+                assert_eq!(nl_type, NLType::OwnedStruct("Point"))
+            }
+            _ => panic!("Expected the struct literal to be unsupported."),
+        });
+    }
+
+    #[test]
+    fn pattern_matching() {
+        for code in [
+            "fn test(x: i32) { if let Enum::A(y) = x {} }",
+            "fn test(x: i32) { while let Enum::A(y) = x {} }",
+        ]
+        .iter()
+        {
+            compile_function(code, |_compiler, result| match result {
+                Err(CompileError::Unsupported(_)) => {}
+                _ => panic!("Expected {} to be unsupported.", code),
+            });
+        }
+    }
+
+    #[test]
+    fn arrays() {
+        for code in [
+            "fn test() { let x = [1, 2, 3] }",
+            "fn test() { let x = [0; 4] }",
+        ]
+        .iter()
+        {
+            compile_function(code, |_compiler, result| match result {
+                Err(CompileError::Unsupported(_)) => {}
+                _ => panic!("Expected {} to be unsupported.", code),
+            });
+        }
+    }
+}
+
+mod layouts {
+    use super::*;
+
+    #[test]
+    fn primitive_sizes() {
+        let registry = StructRegistry::new();
+        assert_eq!(type_size(&NLType::I32, &registry), Some(4));
+        assert_eq!(type_size(&NLType::Boolean, &registry), Some(1));
+        assert_eq!(type_size(&NLType::F64, &registry), Some(8));
+        assert_eq!(type_size(&NLType::OwnedString, &registry), None);
+    }
+
+    #[test]
+    fn tuple_size() {
+        let registry = StructRegistry::new();
+
+        // The i32 has to start on a multiple of 4, so there's padding after the u8.
+        let tuple = NLType::Tuple(vec![NLType::U8, NLType::I32]);
+        assert_eq!(type_size(&tuple, &registry), Some(8));
+
+        // The end is padded too, so the next tuple in an array would still be aligned.
+        let tuple = NLType::Tuple(vec![NLType::I64, NLType::Boolean]);
+        assert_eq!(type_size(&tuple, &registry), Some(16));
+
+        assert_eq!(type_size(&NLType::Tuple(vec![]), &registry), Some(0));
+    }
+
+    #[test]
+    fn struct_size() {
+        let code = "struct Pair { a: i32, b: i32 }
+                    struct Nested { flag: bool, pair: Pair }
+                    struct Loop { next: Loop }
+                    struct Broken { missing: Missing }";
+        let file = parse_string(code, "virtual_file").unwrap();
+        let registry = StructRegistry::from_file(&file);
+
+        assert_eq!(type_size(&NLType::OwnedStruct("Pair"), &registry), Some(8));
+        assert_eq!(
+            type_size(&NLType::OwnedStruct("Nested"), &registry),
+            Some(12)
+        );
+        assert_eq!(type_size(&NLType::OwnedStruct("Loop"), &registry), None);
+        assert_eq!(type_size(&NLType::OwnedStruct("Broken"), &registry), None);
+    }
+}
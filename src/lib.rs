@@ -4,4 +4,5 @@
 extern crate unwrap_to;
 
 pub mod parsing;
+pub mod checking;
 pub mod compiling;
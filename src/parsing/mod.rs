@@ -2,18 +2,26 @@ use nom::Err as NomErr;
 use nom::{
     branch::alt,
     bytes::complete::{tag, take_until, take_while, take_while1, is_not, take_while_m_n},
-    character::{
-        complete::{alpha1, alphanumeric0, alphanumeric1, char, digit1, multispace0, one_of, multispace1},
-        is_alphanumeric,
-    },
-    combinator::{opt, recognize, value, map, verify, map_res, map_opt},
+    character::complete::{alpha1, alphanumeric0, char, digit1, multispace0, one_of, multispace1, satisfy},
+    combinator::{opt, recognize, value, map, verify, map_res, not, rest},
     error::{convert_error, FromExternalError, VerboseError, VerboseErrorKind},
     multi::{many0, many0_count, many1, fold_many0},
     sequence::tuple,
     sequence::{delimited, preceded, terminated},
-    IResult,
+    IResult, Offset,
+};
+use std::{
+    cell::{Cell, RefCell},
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+    fmt::{Debug, Formatter},
+    fs::File,
+    hash::{Hash, Hasher},
+    io::Read,
+    path::Path,
+    str::FromStr,
 };
-use std::{fmt::Formatter, fs::File, io::Read, path::Path, str::FromStr};
 
 // All tests are kept in their own module.
 #[cfg(test)]
@@ -23,7 +31,7 @@ pub type ParserResult<'a, O> = IResult<&'a str, O, VerboseError<&'a str>>;
 
 // TODO replace all the getters with reference handles and mut_handles.
 
-#[derive(PartialOrd, PartialEq, Debug, Clone)]
+#[derive(PartialOrd, PartialEq, Debug, Clone, Hash)]
 pub enum NLType<'a> {
     None,
     Boolean,
@@ -39,7 +47,9 @@ pub enum NLType<'a> {
     F64,
     OwnedString,
     BorrowedString,
-    Tuple(Vec<NLType<'a>>),
+    Tuple(Vec<NLType<'a>>), // `()` is read as an empty tuple, not as None.
+    Array(Box<NLType<'a>>, ArrayLen<'a>),
+    Function(Vec<NLType<'a>>, Box<NLType<'a>>), // The argument types and the return type, such as `fn(i32) -> bool`.
     OwnedStruct(&'a str),
     ReferencedStruct(&'a str),
     MutableReferencedStruct(&'a str),
@@ -51,6 +61,14 @@ pub enum NLType<'a> {
     MutableSelfReference,
 }
 
+// The length of an array type. Literal lengths are known right away, named ones refer to a constant
+// and must be resolved later.
+#[derive(PartialOrd, PartialEq, Debug, Clone, Hash)]
+pub enum ArrayLen<'a> {
+    Literal(usize),
+    Named(&'a str),
+}
+
 impl<'a> NLType<'a> {
     pub fn num_bits(&self) -> u16 {
         match self {
@@ -115,85 +133,344 @@ impl<'a> NLType<'a> {
             _ => false,
         }
     }
+
+    /// True for integers and floats.
+    pub fn is_numeric(&self) -> bool {
+        self.is_integer() || self.is_float()
+    }
+
+    /// True if this is an integer type that can hold the value. Nothing fits in a type that isn't an integer.
+    pub fn holds_integer(&self, value: i128) -> bool {
+        if !self.is_integer() {
+            return false;
+        }
+
+        let (min, max) = integer_range(self);
+        min <= value && value <= max
+    }
+
+    /// True for the types built into the language: booleans, integers, floats, and both kinds of string. Tuples,
+    /// arrays, structs, traits, enums, and `self` are made out of other types, so they don't count, and neither does
+    /// the lack of a type.
+    pub fn is_primitive(&self) -> bool {
+        match self {
+            NLType::Boolean | NLType::OwnedString | NLType::BorrowedString => true,
+            nl_type => nl_type.is_numeric(),
+        }
+    }
+
+    pub fn is_reference(&self) -> bool {
+        match self {
+            NLType::BorrowedString => true,
+            NLType::ReferencedStruct(_) => true,
+            NLType::MutableReferencedStruct(_) => true,
+            NLType::ReferencedTrait(_) => true,
+            NLType::MutableReferencedTrait(_) => true,
+            NLType::SelfReference => true,
+            NLType::MutableSelfReference => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_mutable_reference(&self) -> bool {
+        match self {
+            NLType::MutableReferencedStruct(_) => true,
+            NLType::MutableReferencedTrait(_) => true,
+            NLType::MutableSelfReference => true,
+            _ => false,
+        }
+    }
+
+    /// The type a reference points to, such as `Foo` for `&mut Foo`. Types that aren't references are given back as
+    /// they are. There is no owned form of `Self`, so both kinds of self reference become `&self`.
+    pub fn strip_reference(&self) -> NLType<'a> {
+        match self {
+            NLType::ReferencedStruct(name) | NLType::MutableReferencedStruct(name) => {
+                NLType::OwnedStruct(name)
+            }
+            NLType::ReferencedTrait(name) | NLType::MutableReferencedTrait(name) => {
+                NLType::OwnedTrait(name)
+            }
+            NLType::MutableSelfReference => NLType::SelfReference,
+            other => other.clone(),
+        }
+    }
+
+    /// Compares the types as if every mutable reference was an immutable one.
+    pub fn eq_ignoring_mutability(&self, other: &NLType) -> bool {
+        fn immutable<'a>(nl_type: &NLType<'a>) -> NLType<'a> {
+            match nl_type {
+                NLType::MutableReferencedStruct(name) => NLType::ReferencedStruct(name),
+                NLType::MutableReferencedTrait(name) => NLType::ReferencedTrait(name),
+                NLType::MutableSelfReference => NLType::SelfReference,
+                other => other.clone(),
+            }
+        }
+
+        immutable(self) == immutable(other)
+    }
+
+    /// The name of the struct or trait, whether it's owned or referenced.
+    pub fn base_name(&self) -> Option<&'a str> {
+        match self {
+            NLType::OwnedStruct(name)
+            | NLType::ReferencedStruct(name)
+            | NLType::MutableReferencedStruct(name)
+            | NLType::OwnedTrait(name)
+            | NLType::ReferencedTrait(name)
+            | NLType::MutableReferencedTrait(name) => Some(name),
+            _ => None,
+        }
+    }
+}
+
+/// An attribute written in front of an item, such as `#[derive(Debug)]`. The language gives attributes no meaning of
+/// its own. They're kept for tools to read, with their arguments left as they were written.
+#[derive(PartialOrd, PartialEq, Debug, Hash)]
+pub struct NLAttribute<'a> {
+    name: &'a str,
+    args: Vec<&'a str>,
+}
+
+impl<'a> NLAttribute<'a> {
+    pub fn get_name(&self) -> &'a str {
+        self.name
+    }
+    pub fn get_args(&self) -> &Vec<&'a str> {
+        &self.args
+    }
 }
 
+#[derive(Hash)]
 pub struct NLStructVariable<'a> {
     name: &'a str,
     my_type: NLType<'a>,
+    default: Option<NLOperation<'a>>,
+    attributes: Vec<NLAttribute<'a>>,
 }
 
 impl<'a> NLStructVariable<'a> {
-    pub fn get_name(&self) -> &str {
-        &self.name
+    pub fn get_name(&self) -> &'a str {
+        self.name
+    }
+    pub fn get_attributes(&self) -> &Vec<NLAttribute<'a>> {
+        &self.attributes
     }
-    pub fn get_type(&self) -> &NLType {
+    pub fn get_type(&self) -> &NLType<'a> {
         &self.my_type
     }
+    pub fn get_default(&self) -> &Option<NLOperation<'a>> {
+        &self.default
+    }
 }
 
-#[derive(PartialOrd, PartialEq, Debug)]
+#[derive(PartialOrd, PartialEq, Debug, Hash)]
 pub struct NLArgument<'a> {
     name: &'a str,
     nl_type: NLType<'a>,
+
+    // The value used when a call leaves the argument out, such as the `"hello"` in `greeting: str = "hello"`.
+    default: Option<NLOperation<'a>>,
 }
 
 impl<'a> NLArgument<'a> {
-    pub fn get_name(&self) -> &str {
-        &self.name
+    pub fn get_name(&self) -> &'a str {
+        self.name
     }
-    pub fn get_type(&self) -> &NLType {
+    pub fn get_type(&self) -> &NLType<'a> {
         &self.nl_type
     }
+    pub fn get_default(&self) -> Option<&NLOperation<'a>> {
+        self.default.as_ref()
+    }
 }
 
-#[derive(PartialOrd, PartialEq, Debug)]
+// Operations in a block can be ended with a semicolon. The last operation is the value of the whole block, unless it
+// was ended with a semicolon, in which case the block has no value.
+#[derive(PartialOrd, PartialEq, Debug, Hash)]
 pub struct NLBlock<'a> {
     operations: Vec<NLOperation<'a>>,
+    has_result: bool,
 }
 
 impl<'a> NLBlock<'a> {
     pub fn get_operations(&self) -> &Vec<NLOperation<'a>> {
         &self.operations
     }
+
+    /// The operation that gives the block its value, if it has one.
+    pub fn get_result(&self) -> Option<&NLOperation<'a>> {
+        if self.has_result {
+            self.operations.last()
+        } else {
+            None
+        }
+    }
 }
 
+/// Whether something can be used from outside of the file it was declared in. Items are internal unless they say
+/// otherwise, and the methods, getters, setters, and variants inside of them default to the item's rule.
+#[derive(PartialOrd, PartialEq, Debug, Clone, Copy, Hash)]
+pub enum NLAccessRule {
+    Internal,
+    External,
+}
+
+impl Default for NLAccessRule {
+    fn default() -> Self {
+        NLAccessRule::Internal
+    }
+}
+
+// A generic type parameter, and the traits it's bound to, such as `T: Drawable + Debug`.
+pub type TypeParam<'a> = (&'a str, Vec<&'a str>);
+
+#[derive(Hash)]
 pub struct NLFunction<'a> {
     name: &'a str,
+    access: Option<NLAccessRule>,
+    type_params: Vec<TypeParam<'a>>,
     arguments: Vec<NLArgument<'a>>,
     return_type: NLType<'a>,
     block: Option<NLBlock<'a>>,
+    doc: Option<String>,
+    attributes: Vec<NLAttribute<'a>>,
 }
 
+#[derive(Hash)]
 pub enum NLImplementor<'a> {
     Method(NLFunction<'a>),
     Getter(NLGetter<'a>),
     Setter(NLSetter<'a>),
 }
 
-impl<'a> NLFunction<'a> {
+impl<'a> NLImplementor<'a> {
+    /// The name of the method, getter, or setter.
     pub fn get_name(&self) -> &str {
-        &self.name
+        match self {
+            NLImplementor::Method(method) => method.get_name(),
+            NLImplementor::Getter(getter) => getter.get_name(),
+            NLImplementor::Setter(setter) => setter.get_name(),
+        }
+    }
+
+    pub fn get_access(&self) -> NLAccessRule {
+        match self {
+            NLImplementor::Method(method) => method.get_access(),
+            NLImplementor::Getter(getter) => getter.get_access(),
+            NLImplementor::Setter(setter) => setter.get_access(),
+        }
+    }
+
+    // Gives the implementor the access rule of the item it's in, unless it was given one of its own.
+    fn inherit_access(&mut self, access: NLAccessRule) {
+        let own_access = match self {
+            NLImplementor::Method(method) => &mut method.access,
+            NLImplementor::Getter(getter) => &mut getter.access,
+            NLImplementor::Setter(setter) => &mut setter.access,
+        };
+        own_access.get_or_insert(access);
+    }
+}
+
+fn inherit_access(implementors: &mut [NLImplementor], access: NLAccessRule) {
+    for implementor in implementors {
+        implementor.inherit_access(access);
+    }
+}
+
+fn iter_methods<'f, 'a>(
+    implementors: &'f [NLImplementor<'a>],
+) -> impl Iterator<Item = &'f NLFunction<'a>> {
+    implementors
+        .iter()
+        .filter_map(|implementor| match implementor {
+            NLImplementor::Method(method) => Some(method),
+            _ => None,
+        })
+}
+
+fn iter_getters<'f, 'a>(
+    implementors: &'f [NLImplementor<'a>],
+) -> impl Iterator<Item = &'f NLGetter<'a>> {
+    implementors
+        .iter()
+        .filter_map(|implementor| match implementor {
+            NLImplementor::Getter(getter) => Some(getter),
+            _ => None,
+        })
+}
+
+fn iter_setters<'f, 'a>(
+    implementors: &'f [NLImplementor<'a>],
+) -> impl Iterator<Item = &'f NLSetter<'a>> {
+    implementors
+        .iter()
+        .filter_map(|implementor| match implementor {
+            NLImplementor::Setter(setter) => Some(setter),
+            _ => None,
+        })
+}
+
+impl<'a> NLFunction<'a> {
+    pub fn get_name(&self) -> &'a str {
+        self.name
+    }
+    pub fn qualified_name(&self, file: &NLFile) -> String {
+        qualify(file, self.name)
+    }
+    pub fn get_access(&self) -> NLAccessRule {
+        self.access.unwrap_or_default()
     }
-    pub fn get_arguments(&self) -> &Vec<NLArgument> {
+    pub fn get_doc(&self) -> Option<&str> {
+        self.doc.as_deref()
+    }
+    pub fn get_attributes(&self) -> &Vec<NLAttribute<'a>> {
+        &self.attributes
+    }
+    pub fn get_type_params(&self) -> &Vec<TypeParam<'a>> {
+        &self.type_params
+    }
+    pub fn get_arguments(&self) -> &Vec<NLArgument<'a>> {
         &self.arguments
     }
-    pub fn get_return_type(&self) -> &NLType {
+    pub fn get_return_type(&self) -> &NLType<'a> {
         &self.return_type
     }
-    pub fn get_block(&self) -> &Option<NLBlock> {
+    pub fn get_block(&self) -> &Option<NLBlock<'a>> {
         &self.block
     }
+
+    /// True for a trait method that only has a signature, which every implementation of the trait has to provide.
+    /// Trait methods with a body are defaults that implementations can leave out.
+    pub fn is_abstract(&self) -> bool {
+        self.block.is_none()
+    }
+
+    /// The type of `self` if this is a method called on an instance, or None for an associated function.
+    pub fn get_receiver(&self) -> Option<&NLType<'a>> {
+        match self.arguments.first() {
+            Some(argument) => match argument.nl_type {
+                NLType::SelfReference | NLType::MutableSelfReference => Some(&argument.nl_type),
+                _ => None,
+            },
+            None => None,
+        }
+    }
 }
 
-#[derive(PartialOrd, PartialEq, Debug)]
+#[derive(PartialOrd, PartialEq, Debug, Hash)]
 pub enum NLEncapsulationBlock<'a> {
     Some(NLBlock<'a>),
     None,
     Default,
 }
 
+// Implementors that weren't given an access rule are left without one until the item they're in gives them its own.
+#[derive(Hash)]
 pub struct NLGetter<'a> {
-    name: String,
+    name: &'a str,
+    access: Option<NLAccessRule>,
     args: Vec<NLArgument<'a>>,
     nl_type: NLType<'a>,
     block: NLEncapsulationBlock<'a>,
@@ -201,21 +478,26 @@ pub struct NLGetter<'a> {
 
 impl<'a> NLGetter<'a> {
     pub fn get_name(&self) -> &str {
-        &self.name
+        self.name
+    }
+    pub fn get_access(&self) -> NLAccessRule {
+        self.access.unwrap_or_default()
     }
-    pub fn get_arguments(&self) -> &Vec<NLArgument> {
+    pub fn get_arguments(&self) -> &Vec<NLArgument<'a>> {
         &self.args
     }
-    pub fn get_type(&self) -> &NLType {
+    pub fn get_type(&self) -> &NLType<'a> {
         &self.nl_type
     }
-    pub fn get_block(&self) -> &NLEncapsulationBlock {
+    pub fn get_block(&self) -> &NLEncapsulationBlock<'a> {
         &self.block
     }
 }
 
+#[derive(Hash)]
 pub struct NLSetter<'a> {
     name: &'a str,
+    access: Option<NLAccessRule>,
     args: Vec<NLArgument<'a>>,
     block: NLEncapsulationBlock<'a>,
 }
@@ -224,64 +506,166 @@ impl<'a> NLSetter<'a> {
     pub fn get_name(&self) -> &str {
         &self.name
     }
-    pub fn get_arguments(&self) -> &Vec<NLArgument> {
+    pub fn get_access(&self) -> NLAccessRule {
+        self.access.unwrap_or_default()
+    }
+    pub fn get_arguments(&self) -> &Vec<NLArgument<'a>> {
         &self.args
     }
-    pub fn get_block(&self) -> &NLEncapsulationBlock {
+    pub fn get_block(&self) -> &NLEncapsulationBlock<'a> {
         &self.block
     }
 }
 
+#[derive(Hash)]
 pub struct NLStruct<'a> {
     name: &'a str,
+    access: NLAccessRule,
     variables: Vec<NLStructVariable<'a>>,
     implementations: Vec<NLImplementation<'a>>,
+    doc: Option<String>,
+    attributes: Vec<NLAttribute<'a>>,
 }
 
 impl<'a> NLStruct<'a> {
-    pub fn get_name(&self) -> &str {
-        &self.name
+    pub fn get_name(&self) -> &'a str {
+        self.name
+    }
+    pub fn qualified_name(&self, file: &NLFile) -> String {
+        qualify(file, self.name)
+    }
+    pub fn get_access(&self) -> NLAccessRule {
+        self.access
     }
-    pub fn get_variables(&self) -> &Vec<NLStructVariable> {
+    pub fn get_doc(&self) -> Option<&str> {
+        self.doc.as_deref()
+    }
+    pub fn get_attributes(&self) -> &Vec<NLAttribute<'a>> {
+        &self.attributes
+    }
+    pub fn get_variables(&self) -> &Vec<NLStructVariable<'a>> {
         &self.variables
     }
-    pub fn get_implementations(&self) -> &Vec<NLImplementation> {
+    pub fn get_implementations(&self) -> &Vec<NLImplementation<'a>> {
         &self.implementations
     }
 }
 
+#[derive(Hash)]
 pub struct NLTrait<'a> {
     name: &'a str,
+    access: NLAccessRule,
     implementors: Vec<NLImplementor<'a>>,
+    doc: Option<String>,
 }
 
 impl<'a> NLTrait<'a> {
-    pub fn get_name(&self) -> &str {
-        &self.name
+    pub fn get_name(&self) -> &'a str {
+        self.name
+    }
+    pub fn qualified_name(&self, file: &NLFile) -> String {
+        qualify(file, self.name)
+    }
+    pub fn get_access(&self) -> NLAccessRule {
+        self.access
+    }
+    pub fn get_doc(&self) -> Option<&str> {
+        self.doc.as_deref()
     }
-    pub fn get_implementors(&self) -> &Vec<NLImplementor> {
+    pub fn get_implementors(&self) -> &Vec<NLImplementor<'a>> {
         &self.implementors
     }
+
+    pub fn iter_methods(&self) -> impl Iterator<Item = &NLFunction<'a>> {
+        iter_methods(&self.implementors)
+    }
+    pub fn iter_getters(&self) -> impl Iterator<Item = &NLGetter<'a>> {
+        iter_getters(&self.implementors)
+    }
+    pub fn iter_setters(&self) -> impl Iterator<Item = &NLSetter<'a>> {
+        iter_setters(&self.implementors)
+    }
 }
 
+// The name is what's being implemented, such as `Self` or `Circle` in `impl Drawable for Circle`. The trait name is
+// only set when the implementation used `for`.
+#[derive(Hash)]
 pub struct NLImplementation<'a> {
     name: &'a str,
+    trait_name: Option<&'a str>,
     implementors: Vec<NLImplementor<'a>>,
 }
 
 impl<'a> NLImplementation<'a> {
-    pub fn get_name(&self) -> &str {
-        &self.name
+    pub fn get_name(&self) -> &'a str {
+        self.name
+    }
+    pub fn get_trait_name(&self) -> Option<&'a str> {
+        self.trait_name
     }
-    pub fn get_implementors(&self) -> &Vec<NLImplementor> {
+    pub fn get_implementors(&self) -> &Vec<NLImplementor<'a>> {
         &self.implementors
     }
+
+    pub fn iter_methods(&self) -> impl Iterator<Item = &NLFunction<'a>> {
+        iter_methods(&self.implementors)
+    }
+    pub fn iter_getters(&self) -> impl Iterator<Item = &NLGetter<'a>> {
+        iter_getters(&self.implementors)
+    }
+    pub fn iter_setters(&self) -> impl Iterator<Item = &NLSetter<'a>> {
+        iter_setters(&self.implementors)
+    }
+
+    /// Methods that take `self` as their first argument.
+    pub fn instance_methods(&self) -> Vec<&NLFunction<'a>> {
+        self.methods_where(|method| method.get_receiver().is_some())
+    }
+
+    /// Methods that do not take `self`, such as constructors.
+    pub fn associated_functions(&self) -> Vec<&NLFunction<'a>> {
+        self.methods_where(|method| method.get_receiver().is_none())
+    }
+
+    fn methods_where(&self, filter: impl Fn(&NLFunction<'a>) -> bool) -> Vec<&NLFunction<'a>> {
+        self.iter_methods()
+            .filter(|method| filter(method))
+            .collect()
+    }
+}
+
+// How a variant carries its data, such as `None`, `Some(T)`, or `Move { x: i32, y: i32 }`.
+#[derive(PartialOrd, PartialEq, Debug, Clone, Copy, Hash)]
+pub enum VariantKind {
+    Unit,
+    Tuple,
+    Struct,
+}
+
+// The fields of a tuple variant can be left unnamed, such as `One(A, B)`.
+#[derive(PartialOrd, PartialEq, Debug, Hash)]
+pub struct VariantField<'a> {
+    name: Option<&'a str>,
+    nl_type: NLType<'a>,
+}
+
+impl<'a> VariantField<'a> {
+    pub fn get_name(&self) -> Option<&'a str> {
+        self.name
+    }
+    pub fn get_type(&self) -> &NLType<'a> {
+        &self.nl_type
+    }
 }
 
-#[derive(PartialOrd, PartialEq, Debug)]
+#[derive(PartialOrd, PartialEq, Debug, Hash)]
 pub struct EnumVariant<'a> {
     name: &'a str,
-    arguments: Vec<NLArgument<'a>>,
+    access: Option<NLAccessRule>, // Left unset until the enum gives the variant its own rule.
+    kind: VariantKind,
+    arguments: Vec<VariantField<'a>>,
+    discriminant: Option<NLOperation<'a>>,
+    attributes: Vec<NLAttribute<'a>>,
 }
 
 impl<'a> EnumVariant<'a> {
@@ -289,60 +673,220 @@ impl<'a> EnumVariant<'a> {
         self.name
     }
 
-    pub fn get_arguments(&self) -> &Vec<NLArgument<'a>> {
+    pub fn get_access(&self) -> NLAccessRule {
+        self.access.unwrap_or_default()
+    }
+
+    pub fn get_kind(&self) -> VariantKind {
+        self.kind
+    }
+    pub fn get_attributes(&self) -> &Vec<NLAttribute<'a>> {
+        &self.attributes
+    }
+    pub fn get_arguments(&self) -> &Vec<VariantField<'a>> {
         &self.arguments
     }
+
+    /// How many fields the variant carries. Unit variants have none.
+    pub fn arity(&self) -> usize {
+        self.arguments.len()
+    }
+
+    pub fn get_discriminant_expression(&self) -> &Option<NLOperation<'a>> {
+        &self.discriminant
+    }
+
+    /// The value of the discriminant, if one was given. The parser rejects discriminants that can't be evaluated at
+    /// compile time, so this is only `None` when the variant was left to be numbered implicitly.
+    pub fn get_discriminant(&self) -> Option<i64> {
+        match &self.discriminant {
+            Some(expression) => fold_integer_constant(expression),
+            None => None,
+        }
+    }
 }
 
+#[derive(Hash)]
 pub struct NLEnum<'a> {
     name: &'a str,
+    access: NLAccessRule,
     variants: Vec<EnumVariant<'a>>,
+    doc: Option<String>,
+    attributes: Vec<NLAttribute<'a>>,
 }
 
 impl<'a> NLEnum<'a> {
     pub fn get_name(&self) -> &str {
         self.name
     }
+    pub fn qualified_name(&self, file: &NLFile) -> String {
+        qualify(file, self.name)
+    }
+    pub fn get_access(&self) -> NLAccessRule {
+        self.access
+    }
+    pub fn get_doc(&self) -> Option<&str> {
+        self.doc.as_deref()
+    }
+    pub fn get_attributes(&self) -> &Vec<NLAttribute<'a>> {
+        &self.attributes
+    }
 
-    pub fn get_variants(&self) -> &Vec<EnumVariant> {
+    pub fn get_variants(&self) -> &Vec<EnumVariant<'a>> {
         &self.variants
     }
+
+    /// Finds a variant by name, along with its position in the enum. The position is counted from 0 in the order the
+    /// variants were written, whatever their discriminants are.
+    pub fn find_variant(&self, name: &str) -> Option<(usize, &EnumVariant<'a>)> {
+        self.variants
+            .iter()
+            .enumerate()
+            .find(|(_, variant)| variant.name == name)
+    }
 }
 
-enum RootDeceleration<'a> {
+/// A single root deceleration of a file. When reading a whole file, detached implementations are moved into the struct
+/// they name, but `parse_one_item` gives them back as they were written.
+#[derive(Hash)]
+pub enum RootItem<'a> {
     Struct(NLStruct<'a>),
     Trait(NLTrait<'a>),
     Function(NLFunction<'a>),
     Enum(NLEnum<'a>),
+    Implementation(NLImplementation<'a>),
+}
+
+// FNV-1a, which unlike the standard library's hasher isn't seeded differently on every run. Numbers are written as
+// little endian, and sizes as 64 bits, so the result doesn't depend on the platform either.
+struct FingerprintHasher(u64);
+
+impl Hasher for FingerprintHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x100_0000_01b3);
+        }
+    }
+
+    fn write_u16(&mut self, value: u16) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, value: u32) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_u128(&mut self, value: u128) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, value: usize) {
+        self.write_u64(value as u64);
+    }
+}
+
+/// A hash of the item, for caching what was built from it. It's the same on every run and on every platform, but it
+/// comes from the item's `Hash` implementation, so a new version of Rust or of this crate can change it. Caches should
+/// be thrown out when either changes. Only the structure and names of the item count, so blank space and plain
+/// comments don't change it. Doc comments are part of the item, so they do.
+pub fn ast_fingerprint(item: &RootItem) -> u64 {
+    let mut hasher = FingerprintHasher(0xcbf2_9ce4_8422_2325);
+    item.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl<'a> RootItem<'a> {
+    /// The name of the item. For an implementation, this is the name right after `impl`.
+    pub fn name(&self) -> &'a str {
+        match self {
+            RootItem::Struct(nl_struct) => nl_struct.name,
+            RootItem::Trait(nl_trait) => nl_trait.name,
+            RootItem::Function(function) => function.name,
+            RootItem::Enum(nl_enum) => nl_enum.name,
+            RootItem::Implementation(implementation) => implementation.name,
+        }
+    }
 }
 
-#[derive(PartialOrd, PartialEq, Debug)]
+#[derive(PartialOrd, PartialEq, Debug, Clone)]
 pub enum OpConstant<'a> {
     Boolean(bool),
-    Unsigned(u64, NLType<'a>),
-    Signed(i64, NLType<'a>),
+    // The bool is true when the type was written as a suffix, such as the `u8` of `5u8`. Integers without one were
+    // given a default type, and can be taken as any integer type their value fits in.
+    Unsigned(u64, NLType<'a>, bool),
+    Signed(i64, NLType<'a>, bool),
     Float32(f32),
     Float64(f64),
     String(String),
+    RawString(&'a str), // Raw strings have no escapes to process, so they can borrow straight from the source.
     // TODO add support for defining a constant enum.
 }
 
-#[derive(PartialOrd, PartialEq, Debug)]
+// Floats can't be hashed on their own, so we hash their bits instead.
+impl<'a> Hash for OpConstant<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+
+        match self {
+            OpConstant::Boolean(value) => value.hash(state),
+            OpConstant::Unsigned(value, nl_type, is_typed) => {
+                (value, nl_type, is_typed).hash(state)
+            }
+            OpConstant::Signed(value, nl_type, is_typed) => (value, nl_type, is_typed).hash(state),
+            OpConstant::Float32(value) => value.to_bits().hash(state),
+            OpConstant::Float64(value) => value.to_bits().hash(state),
+            OpConstant::String(value) => value.hash(state),
+            OpConstant::RawString(value) => value.hash(state),
+        }
+    }
+}
+
+#[derive(PartialOrd, PartialEq, Debug, Hash)]
 pub struct OpVariable<'a> {
     name: &'a str,
 }
 
 impl<'a> OpVariable<'a> {
-    pub fn get_name(&self) -> &str {
+    pub fn get_name(&self) -> &'a str {
         self.name
     }
 }
 
-#[derive(PartialOrd, PartialEq, Debug)]
+// Pulls the named fields out of a struct, such as `Point { x, y, .. }`.
+#[derive(PartialOrd, PartialEq, Debug, Hash)]
+pub struct StructPattern<'a> {
+    nl_struct: &'a str,
+    fields: Vec<&'a str>,
+    has_rest: bool,
+}
+
+impl<'a> StructPattern<'a> {
+    pub fn get_struct(&self) -> &str {
+        self.nl_struct
+    }
+    pub fn get_fields(&self) -> &Vec<&'a str> {
+        &self.fields
+    }
+    pub fn has_rest(&self) -> bool {
+        self.has_rest
+    }
+}
+
+#[derive(PartialOrd, PartialEq, Debug, Hash)]
 pub struct OpAssignment<'a> {
     is_new: bool,
     to_assign: Vec<OpVariable<'a>>,
     type_assignments: Vec<NLType<'a>>,
+    struct_pattern: Option<StructPattern<'a>>,
     assignment: Box<NLOperation<'a>>,
 }
 
@@ -350,18 +894,21 @@ impl<'a> OpAssignment<'a> {
     pub fn is_new(&self) -> bool {
         self.is_new
     }
-    pub fn get_variable_to_assign(&self) -> &Vec<OpVariable> {
+    pub fn get_struct_pattern(&self) -> &Option<StructPattern<'a>> {
+        &self.struct_pattern
+    }
+    pub fn get_variable_to_assign(&self) -> &Vec<OpVariable<'a>> {
         &self.to_assign
     }
-    pub fn get_types(&self) -> &Vec<NLType> {
+    pub fn get_types(&self) -> &Vec<NLType<'a>> {
         &self.type_assignments
     }
-    pub fn get_value(&self) -> &Box<NLOperation> {
+    pub fn get_value(&self) -> &Box<NLOperation<'a>> {
         &self.assignment
     }
 }
 
-#[derive(PartialOrd, PartialEq, Debug)]
+#[derive(PartialOrd, PartialEq, Debug, Hash)]
 pub enum OpOperator<'a> {
     CompareEqual((Box<NLOperation<'a>>, Box<NLOperation<'a>>)),
     CompareNotEqual((Box<NLOperation<'a>>, Box<NLOperation<'a>>)),
@@ -397,68 +944,246 @@ pub enum OpOperator<'a> {
     Range((Box<NLOperation<'a>>, Box<NLOperation<'a>>)),
 }
 
-#[derive(PartialOrd, PartialEq, Debug)]
+// The Debug output of the control flow structs only shows their structure, so anything added to them later, such as
+// where they were in the source, stays out of it.
+#[derive(PartialOrd, PartialEq, Hash)]
 pub struct IfStatement<'a> {
     condition: Box<NLOperation<'a>>,
     true_block: NLBlock<'a>,
-    false_block: NLBlock<'a>,
+
+    // None when there was no else at all, which is different from an empty `else {}`.
+    false_block: Option<NLBlock<'a>>,
+}
+
+impl<'a> IfStatement<'a> {
+    pub fn get_condition(&self) -> &NLOperation<'a> {
+        &self.condition
+    }
+    pub fn get_true_block(&self) -> &NLBlock<'a> {
+        &self.true_block
+    }
+    pub fn get_false_block(&self) -> Option<&NLBlock<'a>> {
+        self.false_block.as_ref()
+    }
+
+    /// True if the if statement had an else block, even an empty one.
+    pub fn has_else_block(&self) -> bool {
+        self.false_block.is_some()
+    }
+}
+
+impl<'a> Debug for IfStatement<'a> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        f.debug_struct("IfStatement")
+            .field("condition", &self.condition)
+            .field("true_block", &self.true_block)
+            .field("false_block", &self.false_block)
+            .finish_non_exhaustive()
+    }
 }
 
-#[derive(PartialOrd, PartialEq, Debug)]
+#[derive(PartialOrd, PartialEq, Hash)]
 pub struct WhileLoop<'a> {
     condition: Box<NLOperation<'a>>,
     block: NLBlock<'a>,
 }
 
-#[derive(PartialOrd, PartialEq, Debug)]
-pub struct ForLoop<'a> {
-    variable: OpVariable<'a>,
-    iterator: Box<NLOperation<'a>>,
-    block: NLBlock<'a>,
+impl<'a> WhileLoop<'a> {
+    pub fn get_condition(&self) -> &NLOperation<'a> {
+        &self.condition
+    }
+    pub fn get_block(&self) -> &NLBlock<'a> {
+        &self.block
+    }
 }
 
-#[derive(PartialOrd, PartialEq, Debug)]
-struct MatchEnumBranch<'a> {
-    nl_enum: &'a str,
-    variant: &'a str,
-    variables: Vec<&'a str>,
+impl<'a> Debug for WhileLoop<'a> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        f.debug_struct("WhileLoop")
+            .field("condition", &self.condition)
+            .field("block", &self.block)
+            .finish_non_exhaustive()
+    }
 }
 
-#[derive(PartialOrd, PartialEq, Debug)]
-enum MatchBranch<'a> {
-    Enum(MatchEnumBranch<'a>),
-    Constant(OpConstant<'a>),
-    Range((i128, i128)),
-    AllOther, // TODO implement.
+// Runs the true block only if the value is the pattern's variant, with the variant's data given to the pattern's
+// variables, such as `if let Shape::Circle(radius) = shape { ... }`.
+#[derive(PartialOrd, PartialEq, Debug, Hash)]
+pub struct IfLet<'a> {
+    pattern: MatchEnumBranch<'a>,
+    value: Box<NLOperation<'a>>,
+    true_block: NLBlock<'a>,
+    false_block: NLBlock<'a>,
 }
 
-#[derive(PartialOrd, PartialEq, Debug)]
-pub struct Match<'a> {
-    input: Box<NLOperation<'a>>,
-    branches: Vec<(MatchBranch<'a>, NLOperation<'a>)>,
-}
+impl<'a> IfLet<'a> {
+    pub fn get_value(&self) -> &NLOperation<'a> {
+        &self.value
+    }
+    pub fn get_variables(&self) -> &Vec<&'a str> {
+        &self.pattern.variables
+    }
+    pub fn get_true_block(&self) -> &NLBlock<'a> {
+        &self.true_block
+    }
+    pub fn get_false_block(&self) -> &NLBlock<'a> {
+        &self.false_block
+    }
+}
+
+// Runs the block for as long as the value keeps matching the pattern.
+#[derive(PartialOrd, PartialEq, Debug, Hash)]
+pub struct WhileLet<'a> {
+    pattern: MatchEnumBranch<'a>,
+    value: Box<NLOperation<'a>>,
+    block: NLBlock<'a>,
+}
 
-#[derive(PartialOrd, PartialEq, Debug)]
+impl<'a> WhileLet<'a> {
+    pub fn get_value(&self) -> &NLOperation<'a> {
+        &self.value
+    }
+    pub fn get_variables(&self) -> &Vec<&'a str> {
+        &self.pattern.variables
+    }
+    pub fn get_block(&self) -> &NLBlock<'a> {
+        &self.block
+    }
+}
+
+#[derive(PartialOrd, PartialEq, Hash)]
+pub struct ForLoop<'a> {
+    variable: OpVariable<'a>,
+    iterator: Box<NLOperation<'a>>,
+    block: NLBlock<'a>,
+}
+
+impl<'a> ForLoop<'a> {
+    pub fn get_variable(&self) -> &OpVariable<'a> {
+        &self.variable
+    }
+    pub fn get_iterator(&self) -> &NLOperation<'a> {
+        &self.iterator
+    }
+    pub fn get_block(&self) -> &NLBlock<'a> {
+        &self.block
+    }
+}
+
+impl<'a> Debug for ForLoop<'a> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        f.debug_struct("ForLoop")
+            .field("variable", &self.variable)
+            .field("iterator", &self.iterator)
+            .field("block", &self.block)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(PartialOrd, PartialEq, Debug, Hash)]
+struct MatchEnumBranch<'a> {
+    nl_enum: &'a str,
+    variant: &'a str,
+    variables: Vec<&'a str>,
+}
+
+#[derive(PartialOrd, PartialEq, Debug, Hash)]
+enum MatchBranch<'a> {
+    Enum(MatchEnumBranch<'a>),
+    // Constant and range branches can bind the matched value to a name, such as `n @ 0..10`.
+    Constant((Option<&'a str>, OpConstant<'a>)),
+    Range((Option<&'a str>, i128, i128)),
+    // Each element of the tuple is matched against its own pattern, such as `(0, y)`.
+    Tuple(Vec<MatchBranch<'a>>),
+    // Matches anything and gives it a name. Only used inside of tuple patterns for now.
+    Binding(&'a str),
+    AllOther, // TODO implement.
+}
+
+#[derive(PartialOrd, PartialEq, Debug, Hash)]
+pub struct Match<'a> {
+    input: Box<NLOperation<'a>>,
+    branches: Vec<MatchArm<'a>>,
+}
+
+// The pattern, an optional guard that must also be true, and the operation to run.
+type MatchArm<'a> = (MatchBranch<'a>, Option<NLOperation<'a>>, NLOperation<'a>);
+
+#[derive(PartialOrd, PartialEq, Debug, Hash)]
 pub struct FunctionCall<'a> {
     path: &'a str,
-    arguments: Vec<&'a str>,
+    arguments: Vec<NLOperation<'a>>,
 }
 
-#[derive(PartialOrd, PartialEq, Debug)]
+impl<'a> FunctionCall<'a> {
+    pub fn get_path(&self) -> &'a str {
+        self.path
+    }
+    pub fn get_arguments(&self) -> &Vec<NLOperation<'a>> {
+        &self.arguments
+    }
+}
+
+// Builds a struct out of its fields, such as `Point { x: 1, ..origin }`. Fields that aren't given are taken from the
+// base, if there is one.
+#[derive(PartialOrd, PartialEq, Debug, Hash)]
+pub struct StructLiteral<'a> {
+    nl_struct: &'a str,
+    fields: Vec<(&'a str, NLOperation<'a>)>,
+    base: Option<Box<NLOperation<'a>>>,
+}
+
+impl<'a> StructLiteral<'a> {
+    pub fn get_struct(&self) -> &'a str {
+        self.nl_struct
+    }
+    pub fn get_fields(&self) -> &Vec<(&'a str, NLOperation<'a>)> {
+        &self.fields
+    }
+    pub fn get_base(&self) -> &Option<Box<NLOperation<'a>>> {
+        &self.base
+    }
+}
+
+#[derive(PartialOrd, PartialEq, Debug, Hash)]
 pub enum NLOperation<'a> {
     Block(NLBlock<'a>),
     Constant(OpConstant<'a>),
     Assign(OpAssignment<'a>),
     VariableAccess(OpVariable<'a>),
-    Tuple(Vec<NLOperation<'a>>),
+    Tuple(Vec<NLOperation<'a>>), // Never empty. `()` is read as Unit instead.
+    Unit,
     Operator(OpOperator<'a>),
     If(IfStatement<'a>),
+    IfLet(IfLet<'a>),
     Loop(NLBlock<'a>),
     WhileLoop(WhileLoop<'a>),
+    WhileLet(WhileLet<'a>),
     ForLoop(ForLoop<'a>),
-    Break,
+    // A loop with a label, such as `'outer: loop { ... }`, so that a `break` inside of a nested loop can exit it.
+    Labeled {
+        label: &'a str,
+        operation: Box<NLOperation<'a>>,
+    },
+    // Without a label, a break exits the innermost loop. The value becomes the value of the loop.
+    Break {
+        label: Option<&'a str>,
+        value: Option<Box<NLOperation<'a>>>,
+    },
+    Return(Option<Box<NLOperation<'a>>>),
     Match(Match<'a>),
     FunctionCall(FunctionCall<'a>),
+    StructLiteral(StructLiteral<'a>),
+    Cast {
+        value: Box<NLOperation<'a>>,
+        target: NLType<'a>,
+    },
+    ArrayLiteral(Vec<NLOperation<'a>>),
+    // An array with the value repeated, such as `[0; 8]`.
+    ArrayRepeat {
+        value: Box<NLOperation<'a>>,
+        count: Box<NLOperation<'a>>,
+    },
 }
 
 pub struct NLFile<'a> {
@@ -467,62 +1192,1488 @@ pub struct NLFile<'a> {
     traits: Vec<NLTrait<'a>>,
     functions: Vec<NLFunction<'a>>,
     enums: Vec<NLEnum<'a>>,
+
+    // Implementations written apart from their struct. They're moved into it once the whole file has been read.
+    impls: Vec<NLImplementation<'a>>,
 }
 
 impl<'a> NLFile<'a> {
     pub fn get_name(&self) -> &str {
         &self.name
     }
-    pub fn get_structs(&self) -> &Vec<NLStruct> {
+
+    /// The name of the file without its extension, such as `my_mod` for `my_mod.nl`.
+    pub fn source_name(&self) -> &str {
+        Path::new(&self.name)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or(&self.name)
+    }
+    pub fn get_structs(&self) -> &Vec<NLStruct<'a>> {
         &self.structs
     }
-    pub fn get_traits(&self) -> &Vec<NLTrait> {
+    pub fn get_traits(&self) -> &Vec<NLTrait<'a>> {
         &self.traits
     }
-    pub fn get_functions(&self) -> &Vec<NLFunction> {
+    pub fn get_functions(&self) -> &Vec<NLFunction<'a>> {
         &self.functions
     }
-    pub fn get_enums(&self) -> &Vec<NLEnum> {
+    pub fn get_enums(&self) -> &Vec<NLEnum<'a>> {
         &self.enums
     }
+
+    pub fn iter_structs(&self) -> impl Iterator<Item = &NLStruct<'a>> {
+        self.structs.iter()
+    }
+    pub fn iter_traits(&self) -> impl Iterator<Item = &NLTrait<'a>> {
+        self.traits.iter()
+    }
+    pub fn iter_functions(&self) -> impl Iterator<Item = &NLFunction<'a>> {
+        self.functions.iter()
+    }
+    pub fn iter_enums(&self) -> impl Iterator<Item = &NLEnum<'a>> {
+        self.enums.iter()
+    }
+
+    /// Iterates over every root deceleration in the file. Structs come first, then traits, functions, and enums.
+    pub fn iter_items(&self) -> impl Iterator<Item = NLItemRef<'_, 'a>> {
+        self.iter_structs()
+            .map(NLItemRef::Struct)
+            .chain(self.iter_traits().map(NLItemRef::Trait))
+            .chain(self.iter_functions().map(NLItemRef::Function))
+            .chain(self.iter_enums().map(NLItemRef::Enum))
+    }
+
+    // When more than one item shares a name, the first one in the file is the one we find.
+    pub fn find_struct(&self, name: &str) -> Option<&NLStruct<'a>> {
+        self.iter_structs().find(|nl_struct| nl_struct.name == name)
+    }
+    pub fn find_trait(&self, name: &str) -> Option<&NLTrait<'a>> {
+        self.iter_traits().find(|nl_trait| nl_trait.name == name)
+    }
+    pub fn find_function(&self, name: &str) -> Option<&NLFunction<'a>> {
+        self.iter_functions().find(|function| function.name == name)
+    }
+    pub fn find_enum(&self, name: &str) -> Option<&NLEnum<'a>> {
+        self.iter_enums().find(|nl_enum| nl_enum.name == name)
+    }
+
+    /// Finds an item of any kind by name. Items are searched in the same order as `iter_items`, and the first match
+    /// wins, so a struct shadows a function of the same name.
+    pub fn find_item(&self, name: &str) -> Option<NLItemRef<'_, 'a>> {
+        self.iter_items().find(|item| item.get_name() == name)
+    }
+}
+
+// Items are qualified by the file they were written in, such as `my_mod::foo`. These are used for symbol names, so two
+// files can each have their own `foo` without a collision.
+fn qualify(file: &NLFile, name: &str) -> String {
+    format!("{}::{}", file.source_name(), name)
+}
+
+/// A reference to any of the root decelerations of a file.
+#[derive(Clone, Copy)]
+pub enum NLItemRef<'f, 'a> {
+    Struct(&'f NLStruct<'a>),
+    Trait(&'f NLTrait<'a>),
+    Function(&'f NLFunction<'a>),
+    Enum(&'f NLEnum<'a>),
+}
+
+impl<'f, 'a> NLItemRef<'f, 'a> {
+    pub fn get_name(&self) -> &'a str {
+        match self {
+            NLItemRef::Struct(nl_struct) => nl_struct.name,
+            NLItemRef::Trait(nl_trait) => nl_trait.name,
+            NLItemRef::Function(function) => function.name,
+            NLItemRef::Enum(nl_enum) => nl_enum.name,
+        }
+    }
+    pub fn get_access(&self) -> NLAccessRule {
+        match self {
+            NLItemRef::Struct(nl_struct) => nl_struct.access,
+            NLItemRef::Trait(nl_trait) => nl_trait.access,
+            NLItemRef::Function(function) => function.get_access(),
+            NLItemRef::Enum(nl_enum) => nl_enum.access,
+        }
+    }
+    pub fn qualified_name(&self, file: &NLFile) -> String {
+        qualify(file, self.get_name())
+    }
+}
+
+/// Compares the structure of two files, without caring about the order their items were written in. Gives back a
+/// description of the first difference found, such as `struct Foo missing in b`. Doc comments are not compared.
+pub fn files_equivalent(a: &NLFile, b: &NLFile) -> Result<(), String> {
+    fn find_matching<'f, 'a>(file: &'f NLFile<'a>, item: &NLItemRef) -> Option<NLItemRef<'f, 'a>> {
+        let name = item.get_name();
+        match item {
+            NLItemRef::Struct(_) => file.find_struct(name).map(NLItemRef::Struct),
+            NLItemRef::Trait(_) => file.find_trait(name).map(NLItemRef::Trait),
+            NLItemRef::Function(_) => file.find_function(name).map(NLItemRef::Function),
+            NLItemRef::Enum(_) => file.find_enum(name).map(NLItemRef::Enum),
+        }
+    }
+
+    fn kind(item: &NLItemRef) -> &'static str {
+        match item {
+            NLItemRef::Struct(_) => "struct",
+            NLItemRef::Trait(_) => "trait",
+            NLItemRef::Function(_) => "function",
+            NLItemRef::Enum(_) => "enum",
+        }
+    }
+
+    for item in a.iter_items() {
+        let other = match find_matching(b, &item) {
+            Some(other) => other,
+            None => return Err(format!("{} {} missing in b", kind(&item), item.get_name())),
+        };
+
+        if let Err(difference) = items_equivalent(&item, &other) {
+            return Err(format!(
+                "{} {} differs in {}",
+                kind(&item),
+                item.get_name(),
+                difference
+            ));
+        }
+    }
+
+    for item in b.iter_items() {
+        if find_matching(a, &item).is_none() {
+            return Err(format!("{} {} missing in a", kind(&item), item.get_name()));
+        }
+    }
+
+    Ok(())
+}
+
+// Items are only compared against items of the same kind and name. The error says what part of them differs.
+fn items_equivalent(a: &NLItemRef, b: &NLItemRef) -> Result<(), &'static str> {
+    if a.get_access() != b.get_access() {
+        return Err("its access rule");
+    }
+
+    match (a, b) {
+        (NLItemRef::Struct(a), NLItemRef::Struct(b)) => {
+            if a.attributes != b.attributes {
+                return Err("its attributes");
+            }
+
+            let variables_match = a.variables.len() == b.variables.len()
+                && a.variables.iter().zip(b.variables.iter()).all(|(a, b)| {
+                    a.name == b.name
+                        && a.my_type == b.my_type
+                        && a.default == b.default
+                        && a.attributes == b.attributes
+                });
+            if !variables_match {
+                return Err("its variables");
+            }
+
+            let implementations_match = a.implementations.len() == b.implementations.len()
+                && a.implementations
+                    .iter()
+                    .zip(b.implementations.iter())
+                    .all(|(a, b)| {
+                        a.name == b.name
+                            && a.trait_name == b.trait_name
+                            && implementors_equivalent(&a.implementors, &b.implementors)
+                    });
+            if !implementations_match {
+                return Err("its implementations");
+            }
+
+            Ok(())
+        }
+        (NLItemRef::Trait(a), NLItemRef::Trait(b)) => {
+            if implementors_equivalent(&a.implementors, &b.implementors) {
+                Ok(())
+            } else {
+                Err("its methods")
+            }
+        }
+        (NLItemRef::Function(a), NLItemRef::Function(b)) => functions_equivalent(a, b),
+        (NLItemRef::Enum(a), NLItemRef::Enum(b)) => {
+            if a.attributes != b.attributes {
+                Err("its attributes")
+            } else if a.variants != b.variants {
+                Err("its variants")
+            } else {
+                Ok(())
+            }
+        }
+        _ => Err("its kind"),
+    }
+}
+
+fn functions_equivalent(a: &NLFunction, b: &NLFunction) -> Result<(), &'static str> {
+    if a.attributes != b.attributes {
+        Err("its attributes")
+    } else if a.type_params != b.type_params {
+        Err("its type parameters")
+    } else if a.arguments != b.arguments {
+        Err("its arguments")
+    } else if a.return_type != b.return_type {
+        Err("its return type")
+    } else if a.block != b.block {
+        Err("its body")
+    } else {
+        Ok(())
+    }
+}
+
+fn implementors_equivalent(a: &[NLImplementor], b: &[NLImplementor]) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b.iter()).all(|pair| {
+            pair.0.get_access() == pair.1.get_access()
+                && match pair {
+                    (NLImplementor::Method(a), NLImplementor::Method(b)) => {
+                        a.name == b.name && functions_equivalent(a, b).is_ok()
+                    }
+                    (NLImplementor::Getter(a), NLImplementor::Getter(b)) => {
+                        a.name == b.name
+                            && a.args == b.args
+                            && a.nl_type == b.nl_type
+                            && a.block == b.block
+                    }
+                    (NLImplementor::Setter(a), NLImplementor::Setter(b)) => {
+                        a.name == b.name && a.args == b.args && a.block == b.block
+                    }
+                    _ => false,
+                }
+        })
+}
+
+/// A range of bytes in the source code, from `start` up to but not including `end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Something read from the source code, along with where it was found.
+#[derive(Debug, PartialEq)]
+pub struct Spanned<T> {
+    pub span: Span,
+    pub item: T,
+}
+
+/// Like `NLFile`, but every root deceleration knows where it is in the source code.
+pub struct SpannedFile<'a> {
+    name: String,
+    structs: Vec<Spanned<NLStruct<'a>>>,
+    traits: Vec<Spanned<NLTrait<'a>>>,
+    functions: Vec<Spanned<NLFunction<'a>>>,
+    enums: Vec<Spanned<NLEnum<'a>>>,
+}
+
+impl<'a> SpannedFile<'a> {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+    pub fn get_structs(&self) -> &Vec<Spanned<NLStruct<'a>>> {
+        &self.structs
+    }
+    pub fn get_traits(&self) -> &Vec<Spanned<NLTrait<'a>>> {
+        &self.traits
+    }
+    pub fn get_functions(&self) -> &Vec<Spanned<NLFunction<'a>>> {
+        &self.functions
+    }
+    pub fn get_enums(&self) -> &Vec<Spanned<NLEnum<'a>>> {
+        &self.enums
+    }
+}
+
+/// Stands in for an identifier, and is much cheaper to compare than its text. Symbols are handed out by a
+/// `SymbolTable` and only mean something to the table that made them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(u32);
+
+/// Gives every distinct identifier its own `Symbol`, and can turn symbols back into text.
+#[derive(Debug, Default)]
+pub struct SymbolTable<'a> {
+    symbols: HashMap<&'a str, Symbol>,
+    names: Vec<&'a str>,
+}
+
+impl<'a> SymbolTable<'a> {
+    pub fn new() -> SymbolTable<'a> {
+        SymbolTable::default()
+    }
+
+    /// Makes a table with the names of every struct, trait, function, and enum in the file already interned.
+    pub fn from_file(file: &NLFile<'a>) -> SymbolTable<'a> {
+        let mut table = SymbolTable::new();
+
+        let names = file
+            .structs
+            .iter()
+            .map(|nl_struct| nl_struct.name)
+            .chain(file.traits.iter().map(|nl_trait| nl_trait.name))
+            .chain(file.functions.iter().map(|function| function.name))
+            .chain(file.enums.iter().map(|nl_enum| nl_enum.name));
+
+        for name in names {
+            table.intern(name);
+        }
+
+        table
+    }
+
+    /// Gets the symbol for a name, giving it a new one if we haven't seen it before.
+    pub fn intern(&mut self, name: &'a str) -> Symbol {
+        if let Some(symbol) = self.symbols.get(name) {
+            return *symbol;
+        }
+
+        let symbol = Symbol(self.names.len() as u32);
+        self.names.push(name);
+        self.symbols.insert(name, symbol);
+
+        symbol
+    }
+
+    /// Gets the symbol for a name without interning it.
+    pub fn get_symbol(&self, name: &str) -> Option<Symbol> {
+        self.symbols.get(name).copied()
+    }
+
+    pub fn get_name(&self, symbol: Symbol) -> &'a str {
+        self.names[symbol.0 as usize]
+    }
+}
+
+/// A file along with lookups of its items, and the fields of its structs, by `Symbol`. Made by `parse_interned`.
+pub struct InternedFile<'a> {
+    file: NLFile<'a>,
+
+    // The index of each item in the file's list of its kind. Like `NLFile`, the first item with a name wins.
+    structs: HashMap<Symbol, usize>,
+    traits: HashMap<Symbol, usize>,
+    functions: HashMap<Symbol, usize>,
+    enums: HashMap<Symbol, usize>,
+
+    // The index of each field, keyed by its struct and then its own name.
+    fields: HashMap<(Symbol, Symbol), usize>,
+}
+
+impl<'a> InternedFile<'a> {
+    pub fn get_file(&self) -> &NLFile<'a> {
+        &self.file
+    }
+
+    pub fn find_struct(&self, name: Symbol) -> Option<&NLStruct<'a>> {
+        self.structs
+            .get(&name)
+            .map(|index| &self.file.structs[*index])
+    }
+    pub fn find_trait(&self, name: Symbol) -> Option<&NLTrait<'a>> {
+        self.traits
+            .get(&name)
+            .map(|index| &self.file.traits[*index])
+    }
+    pub fn find_function(&self, name: Symbol) -> Option<&NLFunction<'a>> {
+        self.functions
+            .get(&name)
+            .map(|index| &self.file.functions[*index])
+    }
+    pub fn find_enum(&self, name: Symbol) -> Option<&NLEnum<'a>> {
+        self.enums.get(&name).map(|index| &self.file.enums[*index])
+    }
+
+    pub fn find_field(&self, nl_struct: Symbol, field: Symbol) -> Option<&NLStructVariable<'a>> {
+        let index = self.fields.get(&(nl_struct, field))?;
+        let nl_struct = self.find_struct(nl_struct)?;
+        nl_struct.variables.get(*index)
+    }
+}
+
+// Interns every name, keeping the index of the first item to use each one.
+fn index_names<'a>(
+    table: &mut SymbolTable<'a>,
+    names: impl Iterator<Item = &'a str>,
+) -> HashMap<Symbol, usize> {
+    let mut indices = HashMap::new();
+    for (index, name) in names.enumerate() {
+        indices.entry(table.intern(name)).or_insert(index);
+    }
+
+    indices
+}
+
+// Every name that has already been seen, once for each extra time it shows up.
+fn find_duplicate_names<'a>(names: impl Iterator<Item = &'a str>) -> Vec<&'a str> {
+    let mut seen = HashSet::new();
+    names.filter(|name| !seen.insert(*name)).collect()
+}
+
+/// Reports every name that is declared more than once. Structs, traits, and enums share one namespace for types, while
+/// functions have their own. Fields must be unique within their struct, and variants within their enum.
+pub fn validate_unique_names(file: &NLFile) -> Result<(), Vec<ParseError>> {
+    let mut errors = Vec::new();
+
+    let type_names = file
+        .structs
+        .iter()
+        .map(|nl_struct| nl_struct.name)
+        .chain(file.traits.iter().map(|nl_trait| nl_trait.name))
+        .chain(file.enums.iter().map(|nl_enum| nl_enum.name));
+    for name in find_duplicate_names(type_names) {
+        errors.push(ParseError::new(format!(
+            "The type `{}` is declared more than once.",
+            name
+        )));
+    }
+
+    let function_names = file.functions.iter().map(|function| function.name);
+    for name in find_duplicate_names(function_names) {
+        errors.push(ParseError::new(format!(
+            "The function `{}` is declared more than once.",
+            name
+        )));
+    }
+
+    for nl_struct in file.structs.iter() {
+        let field_names = nl_struct.variables.iter().map(|variable| variable.name);
+        for name in find_duplicate_names(field_names) {
+            errors.push(ParseError::new(format!(
+                "The field `{}` is declared more than once in struct `{}`.",
+                name, nl_struct.name
+            )));
+        }
+    }
+
+    for nl_enum in file.enums.iter() {
+        let variant_names = nl_enum.variants.iter().map(|variant| variant.name);
+        for name in find_duplicate_names(variant_names) {
+            errors.push(ParseError::new(format!(
+                "The variant `{}` is declared more than once in enum `{}`.",
+                name, nl_enum.name
+            )));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Returns true if execution can never run off the end of the block, because it hits a `return`, a `break`, or a
+/// `loop` that is never broken out of.
+pub fn block_always_diverges(block: &NLBlock) -> bool {
+    block.operations.iter().any(operation_always_diverges)
+}
+
+fn operation_always_diverges(operation: &NLOperation) -> bool {
+    match operation {
+        NLOperation::Return(_) | NLOperation::Break { .. } => true,
+        NLOperation::Block(block) => block_always_diverges(block),
+        // Without an else block, the code after the if statement can still be reached.
+        NLOperation::If(statement) => {
+            block_always_diverges(&statement.true_block)
+                && statement
+                    .false_block
+                    .as_ref()
+                    .is_some_and(block_always_diverges)
+        }
+        NLOperation::IfLet(statement) => {
+            block_always_diverges(&statement.true_block)
+                && block_always_diverges(&statement.false_block)
+        }
+        NLOperation::Match(nl_match) => {
+            !nl_match.branches.is_empty()
+                && nl_match
+                    .branches
+                    .iter()
+                    .all(|(_, _, operation)| operation_always_diverges(operation))
+        }
+        NLOperation::Loop(block) => !block_breaks(block, &[]),
+        NLOperation::Labeled { operation, .. } => operation_always_diverges(operation),
+        _ => false,
+    }
+}
+
+// Checks if a loop body contains a break that would exit that loop. The inner labels are those of the loops nested
+// between the body and the break, with None for loops that don't have one.
+fn block_breaks(block: &NLBlock, inner_labels: &[Option<&str>]) -> bool {
+    block
+        .operations
+        .iter()
+        .any(|operation| operation_breaks(operation, inner_labels))
+}
+
+fn operation_breaks(operation: &NLOperation, inner_labels: &[Option<&str>]) -> bool {
+    // Unlabeled breaks inside of nested loops belong to those loops, not ours.
+    let nested = |label: Option<&str>, block: &NLBlock| {
+        block_breaks(block, &[inner_labels, &[label]].concat())
+    };
+
+    match operation {
+        // A labeled break exits every loop until it reaches the one it names.
+        NLOperation::Break { label: None, .. } => inner_labels.is_empty(),
+        NLOperation::Break { label, .. } => !inner_labels.contains(label),
+        NLOperation::Block(block) => block_breaks(block, inner_labels),
+        NLOperation::Assign(assignment) => operation_breaks(&assignment.assignment, inner_labels),
+        NLOperation::If(statement) => {
+            operation_breaks(&statement.condition, inner_labels)
+                || block_breaks(&statement.true_block, inner_labels)
+                || statement
+                    .false_block
+                    .as_ref()
+                    .is_some_and(|block| block_breaks(block, inner_labels))
+        }
+        NLOperation::IfLet(statement) => {
+            operation_breaks(&statement.value, inner_labels)
+                || block_breaks(&statement.true_block, inner_labels)
+                || block_breaks(&statement.false_block, inner_labels)
+        }
+        NLOperation::Match(nl_match) => nl_match
+            .branches
+            .iter()
+            .any(|(_, _, operation)| operation_breaks(operation, inner_labels)),
+        NLOperation::Loop(block) => nested(None, block),
+        NLOperation::WhileLoop(while_loop) => nested(None, &while_loop.block),
+        NLOperation::WhileLet(while_let) => nested(None, &while_let.block),
+        NLOperation::ForLoop(for_loop) => nested(None, &for_loop.block),
+        NLOperation::Labeled { label, operation } => {
+            operation_breaks(operation, &[inner_labels, &[Some(*label)]].concat())
+        }
+        _ => false,
+    }
+}
+
+/// Evaluates an integer expression made only of constants, such as `1 << 3`. Returns None if the expression depends
+/// on anything that isn't known at compile time, or if the math overflows its type. The rules are the same as the ones
+/// `fold_constants` follows, so `250u8 + 10u8` can't be evaluated.
+pub fn fold_integer_constant(operation: &NLOperation) -> Option<i64> {
+    match constant_value(operation)? {
+        OpConstant::Signed(value, _, _) => Some(value),
+        OpConstant::Unsigned(value, _, _) => i64::try_from(value).ok(),
+        _ => None,
+    }
+}
+
+// The constant an operation evaluates to, if it only depends on constants.
+fn constant_value<'a>(operation: &NLOperation<'a>) -> Option<OpConstant<'a>> {
+    match operation {
+        NLOperation::Constant(constant) => Some(constant.clone()),
+        NLOperation::Operator(operator) => fold_operator(operator),
+        // Parentheses are read as a tuple with one element.
+        NLOperation::Tuple(elements) if elements.len() == 1 => constant_value(&elements[0]),
+        _ => None,
+    }
+}
+
+/// Evaluates every operator whose operands are all constants, such as turning `2 + 3 * 4` into `14`. Operators inside
+/// of blocks, tuples, arrays, and returns are folded too. Anything that depends on something that isn't constant is
+/// left as it is, along with math that mixes types, overflows its type, or divides by zero.
+pub fn fold_constants(operation: NLOperation) -> NLOperation {
+    fn fold_all(operations: Vec<NLOperation>) -> Vec<NLOperation> {
+        operations.into_iter().map(fold_constants).collect()
+    }
+
+    match operation {
+        NLOperation::Operator(mut operator) => {
+            for operand in operator_operands(&mut operator) {
+                let value = std::mem::replace(&mut **operand, NLOperation::Unit);
+                **operand = fold_constants(value);
+            }
+
+            match fold_operator(&operator) {
+                Some(constant) => NLOperation::Constant(constant),
+                None => NLOperation::Operator(operator),
+            }
+        }
+        NLOperation::Block(block) => NLOperation::Block(NLBlock {
+            operations: fold_all(block.operations),
+            has_result: block.has_result,
+        }),
+        NLOperation::Tuple(elements) => {
+            let mut elements = fold_all(elements);
+
+            // Parentheses are read as a tuple with one element, which is just the constant inside once it's folded.
+            match elements.as_slice() {
+                [NLOperation::Constant(_)] => elements.remove(0),
+                _ => NLOperation::Tuple(elements),
+            }
+        }
+        NLOperation::ArrayLiteral(elements) => NLOperation::ArrayLiteral(fold_all(elements)),
+        NLOperation::Return(value) => {
+            NLOperation::Return(value.map(|value| Box::new(fold_constants(*value))))
+        }
+        operation => operation,
+    }
+}
+
+fn operator_operands<'o, 'a>(
+    operator: &'o mut OpOperator<'a>,
+) -> Vec<&'o mut Box<NLOperation<'a>>> {
+    use OpOperator::*;
+
+    match operator {
+        LogicalNegate(value) | ArithmeticNegate(value) | BitNegate(value) | PropError(value) => {
+            vec![value]
+        }
+        CompareEqual((a, b))
+        | CompareNotEqual((a, b))
+        | CompareGreater((a, b))
+        | CompareLess((a, b))
+        | CompareGreaterEqual((a, b))
+        | CompareLessEqual((a, b))
+        | LogicalAnd((a, b))
+        | LogicalOr((a, b))
+        | LogicalXor((a, b))
+        | BitAnd((a, b))
+        | BitOr((a, b))
+        | BitXor((a, b))
+        | BitLeftShift((a, b))
+        | BitRightShift((a, b))
+        | ArithmeticMod((a, b))
+        | ArithmeticAdd((a, b))
+        | ArithmeticSub((a, b))
+        | ArithmeticMul((a, b))
+        | ArithmeticDiv((a, b))
+        | Range((a, b)) => vec![a, b],
+    }
+}
+
+// The value of an operator, if its operands are all constants. Operands that are still operators are evaluated along
+// the way, so expressions can be evaluated without being folded first.
+fn fold_operator<'a>(operator: &OpOperator<'a>) -> Option<OpConstant<'a>> {
+    use OpOperator::*;
+
+    type Pair<'o, 'a> = &'o (Box<NLOperation<'a>>, Box<NLOperation<'a>>);
+
+    fn values<'a>((a, b): Pair<'_, 'a>) -> Option<(OpConstant<'a>, OpConstant<'a>)> {
+        Some((constant_value(a)?, constant_value(b)?))
+    }
+
+    fn boolean(constant: &OpConstant) -> Option<bool> {
+        match constant {
+            OpConstant::Boolean(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    // An integer constant, along with its type and whether that type was written as a suffix.
+    type Integer<'c, 'a> = (i128, &'c NLType<'a>, bool);
+
+    fn integer<'c, 'a>(constant: &'c OpConstant<'a>) -> Option<Integer<'c, 'a>> {
+        match constant {
+            OpConstant::Signed(value, nl_type, is_typed) => {
+                Some((*value as i128, nl_type, *is_typed))
+            }
+            OpConstant::Unsigned(value, nl_type, is_typed) => {
+                Some((*value as i128, nl_type, *is_typed))
+            }
+            _ => None,
+        }
+    }
+
+    // Both sides have to be the same type of integer, or the checker is the one that should complain. Like the checker,
+    // a side without a suffix can take the type of the other side if it fits. The result is typed if either side was.
+    fn integers<'c, 'a>(
+        a: &'c OpConstant<'a>,
+        b: &'c OpConstant<'a>,
+    ) -> Option<(i128, i128, &'c NLType<'a>, bool)> {
+        let (a, a_type, a_typed) = integer(a)?;
+        let (b, b_type, b_typed) = integer(b)?;
+
+        let nl_type = if a_type == b_type {
+            a_type
+        } else if !a_typed && b_type.holds_integer(a) {
+            b_type
+        } else if !b_typed && a_type.holds_integer(b) {
+            a_type
+        } else {
+            return None;
+        };
+
+        Some((a, b, nl_type, a_typed || b_typed))
+    }
+
+    fn integer_constant<'a>(
+        value: Option<i128>,
+        nl_type: &NLType<'a>,
+        is_typed: bool,
+    ) -> Option<OpConstant<'a>> {
+        let value = value?;
+        let (min, max) = integer_range(nl_type);
+
+        if value < min || value > max {
+            None
+        } else if nl_type.is_signed() {
+            Some(OpConstant::Signed(value as i64, nl_type.clone(), is_typed))
+        } else {
+            Some(OpConstant::Unsigned(
+                value as u64,
+                nl_type.clone(),
+                is_typed,
+            ))
+        }
+    }
+
+    fn arithmetic<'a>(
+        pair: Pair<'_, 'a>,
+        on_integers: fn(i128, i128) -> Option<i128>,
+        on_floats: fn(f64, f64) -> Option<f64>,
+    ) -> Option<OpConstant<'a>> {
+        let (a, b) = values(pair)?;
+        if let Some((a, b, nl_type, is_typed)) = integers(&a, &b) {
+            return integer_constant(on_integers(a, b), nl_type, is_typed);
+        }
+
+        match (a, b) {
+            (OpConstant::Float32(a), OpConstant::Float32(b)) => {
+                Some(OpConstant::Float32(on_floats(a as f64, b as f64)? as f32))
+            }
+            (OpConstant::Float64(a), OpConstant::Float64(b)) => {
+                Some(OpConstant::Float64(on_floats(a, b)?))
+            }
+            _ => None,
+        }
+    }
+
+    fn compare<'a>(pair: Pair<'_, 'a>, test: fn(Ordering) -> bool) -> Option<OpConstant<'a>> {
+        let (a, b) = values(pair)?;
+        let ordering = match (&a, &b) {
+            (OpConstant::Float32(a), OpConstant::Float32(b)) => a.partial_cmp(b)?,
+            (OpConstant::Float64(a), OpConstant::Float64(b)) => a.partial_cmp(b)?,
+            (OpConstant::Boolean(a), OpConstant::Boolean(b)) => a.cmp(b),
+            _ => {
+                let (a, b, _, _) = integers(&a, &b)?;
+                a.cmp(&b)
+            }
+        };
+
+        Some(OpConstant::Boolean(test(ordering)))
+    }
+
+    fn logical<'a>(pair: Pair<'_, 'a>, function: fn(bool, bool) -> bool) -> Option<OpConstant<'a>> {
+        let (a, b) = values(pair)?;
+        Some(OpConstant::Boolean(function(boolean(&a)?, boolean(&b)?)))
+    }
+
+    fn bitwise<'a>(pair: Pair<'_, 'a>, function: fn(i128, i128) -> i128) -> Option<OpConstant<'a>> {
+        let (a, b) = values(pair)?;
+        let (a, b, nl_type, is_typed) = integers(&a, &b)?;
+        integer_constant(Some(function(a, b)), nl_type, is_typed)
+    }
+
+    // The amount to shift by can be any type of integer, but shifting by the whole width or more isn't allowed.
+    fn shift<'a>(pair: Pair<'_, 'a>, function: fn(i128, u32) -> i128) -> Option<OpConstant<'a>> {
+        let (value, amount) = values(pair)?;
+        let (value, nl_type, is_typed) = integer(&value)?;
+        let (amount, _, _) = integer(&amount)?;
+
+        if amount < 0 || amount >= nl_type.num_bits() as i128 {
+            return None;
+        }
+
+        integer_constant(Some(function(value, amount as u32)), nl_type, is_typed)
+    }
+
+    match operator {
+        LogicalNegate(value) => Some(OpConstant::Boolean(!boolean(&constant_value(value)?)?)),
+        LogicalAnd(pair) => logical(pair, |a, b| a && b),
+        LogicalOr(pair) => logical(pair, |a, b| a || b),
+        LogicalXor(pair) => logical(pair, |a, b| a != b),
+
+        CompareEqual(pair) => compare(pair, Ordering::is_eq),
+        CompareNotEqual(pair) => compare(pair, Ordering::is_ne),
+        CompareGreater(pair) => compare(pair, Ordering::is_gt),
+        CompareLess(pair) => compare(pair, Ordering::is_lt),
+        CompareGreaterEqual(pair) => compare(pair, Ordering::is_ge),
+        CompareLessEqual(pair) => compare(pair, Ordering::is_le),
+
+        ArithmeticNegate(value) => match constant_value(value)? {
+            OpConstant::Float32(value) => Some(OpConstant::Float32(-value)),
+            OpConstant::Float64(value) => Some(OpConstant::Float64(-value)),
+            value => {
+                let (value, nl_type, is_typed) = integer(&value)?;
+                integer_constant(Some(-value), nl_type, is_typed)
+            }
+        },
+        // Flipping every bit is the same as counting down from the top of the type's range.
+        BitNegate(value) => {
+            let value = constant_value(value)?;
+            let (value, nl_type, is_typed) = integer(&value)?;
+            let (min, max) = integer_range(nl_type);
+            integer_constant(Some(max + min - value), nl_type, is_typed)
+        }
+
+        ArithmeticAdd(pair) => arithmetic(pair, i128::checked_add, |a, b| Some(a + b)),
+        ArithmeticSub(pair) => arithmetic(pair, i128::checked_sub, |a, b| Some(a - b)),
+        ArithmeticMul(pair) => arithmetic(pair, i128::checked_mul, |a, b| Some(a * b)),
+        // Dividing by zero is left for the program to deal with when it runs.
+        ArithmeticDiv(pair) => arithmetic(pair, i128::checked_div, |a, b| {
+            Some(a / b).filter(|_| b != 0.0)
+        }),
+        ArithmeticMod(pair) => arithmetic(pair, i128::checked_rem, |a, b| {
+            Some(a % b).filter(|_| b != 0.0)
+        }),
+
+        BitAnd(pair) => bitwise(pair, |a, b| a & b),
+        BitOr(pair) => bitwise(pair, |a, b| a | b),
+        BitXor(pair) => bitwise(pair, |a, b| a ^ b),
+        BitLeftShift(pair) => shift(pair, |value, amount| value << amount),
+        BitRightShift(pair) => shift(pair, |value, amount| value >> amount),
+
+        PropError(_) | Range(_) => None,
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum SemanticTokenType {
+    Keyword,
+    Type,
+    Function,
+    Variable,
+    String,
+    Number,
+    Comment,
+}
+
+/// A span of source code to be highlighted by an editor. Lines and columns are counted in characters, starting from 0.
+#[derive(PartialEq, Debug)]
+pub struct SemanticToken {
+    line: usize,
+    start: usize,
+    length: usize,
+    token_type: SemanticTokenType,
+}
+
+impl SemanticToken {
+    pub fn get_line(&self) -> usize {
+        self.line
+    }
+    pub fn get_start(&self) -> usize {
+        self.start
+    }
+    pub fn get_length(&self) -> usize {
+        self.length
+    }
+    pub fn get_type(&self) -> SemanticTokenType {
+        self.token_type
+    }
+}
+
+// Words the language gives a meaning of its own. None of them can be the name of a function being called, so
+// `if (ready) {}` isn't read as calling a function named `if`.
+const KEYWORDS: [&str; 24] = [
+    "struct", "trait", "impl", "enum", "fn", "met", "get", "set", "let", "if", "else", "loop",
+    "while", "for", "in", "break", "return", "match", "as", "dyn", "mut", "self", "true", "false",
+];
+
+/// Splits the source into tokens for syntax highlighting. This does not need the source to be valid, so it can be run
+/// on files that are still being edited.
+pub fn semantic_tokens(input: &str) -> Vec<SemanticToken> {
+    const PRIMITIVES: [&str; 13] = [
+        "i8", "i16", "i32", "i64", "u8", "u16", "u32", "u64", "f32", "f64", "bool", "str", "String",
+    ];
+    // Names that come after these are the names of functions.
+    const FUNCTION_INTRODUCERS: [&str; 4] = ["fn", "met", "get", "set"];
+    // Names that come after these are the names of types.
+    const TYPE_INTRODUCERS: [&str; 9] = [
+        "struct", "trait", "enum", "impl", "dyn", "mut", "as", ":", "->",
+    ];
+
+    let chars: Vec<char> = input.chars().collect();
+
+    // The line and column of every character.
+    let mut positions = Vec::with_capacity(chars.len());
+    let (mut line, mut column) = (0, 0);
+    for c in chars.iter() {
+        positions.push((line, column));
+        if *c == '\n' {
+            line += 1;
+            column = 0;
+        } else {
+            column += 1;
+        }
+    }
+
+    let mut tokens = Vec::new();
+
+    // Tokens can't span multiple lines, so comments and strings get split up.
+    let mut push_token = |start: usize, end: usize, token_type: SemanticTokenType| {
+        let mut line_start = start;
+        for index in start..=end {
+            if index == end || chars[index] == '\n' {
+                if index > line_start {
+                    let (line, start) = positions[line_start];
+                    tokens.push(SemanticToken {
+                        line,
+                        start,
+                        length: index - line_start,
+                        token_type,
+                    });
+                }
+                line_start = index + 1;
+            }
+        }
+    };
+
+    // Finds the next character that isn't whitespace, along with the one after it.
+    let look_ahead = |index: usize| {
+        let mut visible = chars[index..].iter().skip_while(|c| c.is_whitespace());
+        (visible.next().copied(), visible.next().copied())
+    };
+
+    let mut previous = String::new();
+    let mut index = 0;
+    while index < chars.len() {
+        let start = index;
+        let c = chars[index];
+        let next = chars.get(index + 1).copied();
+
+        if c.is_whitespace() {
+            index += 1;
+        } else if c == '/' && next == Some('/') {
+            while index < chars.len() && chars[index] != '\n' {
+                index += 1;
+            }
+            push_token(start, index, SemanticTokenType::Comment);
+        } else if c == '/' && next == Some('*') {
+            index += 2;
+            while index < chars.len()
+                && !(chars[index] == '*' && chars.get(index + 1) == Some(&'/'))
+            {
+                index += 1;
+            }
+            index = (index + 2).min(chars.len());
+            push_token(start, index, SemanticTokenType::Comment);
+        } else if c == '"' {
+            index += 1;
+            while index < chars.len() && chars[index] != '"' {
+                // Skip over whatever is escaped, in case it's a quote.
+                if chars[index] == '\\' {
+                    index += 1;
+                }
+                index += 1;
+            }
+            index = (index + 1).min(chars.len());
+            push_token(start, index, SemanticTokenType::String);
+            previous = String::from("\"");
+        } else if c.is_ascii_digit()
+            || (c == '.'
+                && next.map_or(false, |next| next.is_ascii_digit())
+                && (start == 0 || chars[start - 1] != '.'))
+        {
+            let is_hex = c == '0' && (next == Some('x') || next == Some('X'));
+
+            index += 1;
+            while index < chars.len() {
+                let c = chars[index];
+                let is_exponent_sign = (c == '+' || c == '-')
+                    && !is_hex
+                    && (chars[index - 1] == 'e' || chars[index - 1] == 'E');
+
+                // Two dots is a range, so the number stops there.
+                let is_decimal_point = c == '.' && chars.get(index + 1) != Some(&'.');
+
+                if c.is_alphanumeric() || c == '_' || is_decimal_point || is_exponent_sign {
+                    index += 1;
+                } else {
+                    break;
+                }
+            }
+            push_token(start, index, SemanticTokenType::Number);
+            previous = chars[start..index].iter().collect();
+        } else if c != '.' && is_name(c) {
+            while index < chars.len() && is_method_char(chars[index]) {
+                index += 1;
+            }
+            let word: String = chars[start..index].iter().collect();
+
+            let (after, after_that) = look_ahead(index);
+            let is_call = after == Some('(');
+            let is_path = after == Some(':') && after_that == Some(':');
+
+            let token_type = if KEYWORDS.contains(&word.as_str()) {
+                SemanticTokenType::Keyword
+            } else if PRIMITIVES.contains(&word.as_str()) {
+                SemanticTokenType::Type
+            } else if FUNCTION_INTRODUCERS.contains(&previous.as_str()) || is_call {
+                SemanticTokenType::Function
+            } else if TYPE_INTRODUCERS.contains(&previous.as_str()) || is_path {
+                SemanticTokenType::Type
+            } else {
+                SemanticTokenType::Variable
+            };
+
+            push_token(start, index, token_type);
+            previous = word;
+        } else if c == '-' && next == Some('>') {
+            index += 2;
+            previous = String::from("->");
+        } else if c == ':' && next == Some(':') {
+            // Only the segment before a path separator is a type, not the one after it.
+            index += 2;
+            previous = String::from("::");
+        } else {
+            index += 1;
+            previous = c.to_string();
+        }
+    }
+
+    tokens
+}
+
+#[derive(Debug)]
+pub struct ParseError {
+    message: String,
+
+    // Where in the source the error happened, in bytes. Errors that aren't about a specific spot don't have one.
+    offset: Option<usize>,
+
+    // The line and column of the offset, both counted from 1.
+    location: Option<(usize, usize)>,
+}
+
+// The line and column of a byte offset into the source, both counted from 1. Columns are counted in characters.
+fn line_and_column(source: &str, offset: usize) -> (usize, usize) {
+    let line_start = source[..offset].rfind('\n').map_or(0, |index| index + 1);
+    let line_number = source[..offset].matches('\n').count() + 1;
+    let column = source[line_start..offset].chars().count() + 1;
+
+    (line_number, column)
+}
+
+// Quotes a string for JSON, escaping anything that can't appear in it as is.
+fn json_string(string: &str) -> String {
+    let mut quoted = String::with_capacity(string.len() + 2);
+    quoted.push('"');
+
+    for c in string.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+impl ParseError {
+    fn new(message: String) -> ParseError {
+        ParseError {
+            message,
+            offset: None,
+            location: None,
+        }
+    }
+
+    fn from_nom(input: &str, error: VerboseError<&str>) -> ParseError {
+        // The first error is the innermost one, which is the closest to what actually went wrong. Errors can point at
+        // just a piece of the input, such as a name, so we find where that piece starts rather than its length.
+        let offset = error
+            .errors
+            .first()
+            .map(|(remaining, _)| input.offset(remaining));
+        let location = offset.map(|offset| line_and_column(input, offset));
+
+        ParseError {
+            message: describe_error(input, error),
+            offset,
+            location,
+        }
+    }
+
+    /// The error as a JSON object, for editors and other tools that would rather not pick apart the message. It has
+    /// the `message`, `severity`, and the `offset`, `line`, and `column` it happened at, which are null when the error
+    /// isn't about a specific spot. Lines and columns are counted from 1.
+    pub fn to_json(&self) -> String {
+        fn number(value: Option<usize>) -> String {
+            value.map_or_else(|| String::from("null"), |value| value.to_string())
+        }
+
+        // Everything that stops a file from being parsed is an error. There are no warnings yet.
+        format!(
+            "{{\"message\":{},\"severity\":\"error\",\"offset\":{},\"line\":{},\"column\":{}}}",
+            json_string(&self.message),
+            number(self.offset),
+            number(self.location.map(|(line, _)| line)),
+            number(self.location.map(|(_, column)| column)),
+        )
+    }
+
+    /// Renders the error with the line of source it happened on and a `^` under the column, like rustc does. The
+    /// source must be the same text that was parsed.
+    pub fn pretty(&self, source: &str) -> String {
+        let offset = match self.offset {
+            Some(offset) if source.is_char_boundary(offset) => offset,
+            _ => return self.message.clone(),
+        };
+
+        let line_start = source[..offset].rfind('\n').map_or(0, |index| index + 1);
+        let line_end = source[offset..]
+            .find('\n')
+            .map_or(source.len(), |index| offset + index);
+        let (line_number, column) = line_and_column(source, offset);
+
+        // Tabs are kept so the caret lines up no matter how wide the tabs are drawn.
+        let padding: String = source[line_start..offset]
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+
+        let gutter = " ".repeat(line_number.to_string().len());
+
+        let mut pretty = format!("{}--> line {}, column {}\n", gutter, line_number, column);
+        pretty += &format!("{} |\n", gutter);
+        pretty += &format!("{} | {}\n", line_number, &source[line_start..line_end]);
+        pretty += &format!("{} | {}^\n\n", gutter, padding);
+        pretty += &self.message;
+
+        pretty
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn description(&self) -> &str {
+        &self.message
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// How many times the major parsers were run while reading a file. A parser that runs far more often than there are
+/// operations in the file is a sign of expensive backtracking.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ParseStats {
+    pub read_operation: usize,
+    pub read_sub_operation: usize,
+    pub read_expression: usize,
+    pub read_function_call: usize,
+    pub read_assignment: usize,
+    pub read_constant: usize,
+    pub read_variable_access: usize,
+}
+
+/// Settings for `parse_string_with_options` and the other `_with_options` entry points.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseOptions {
+    /// How many operations can be nested inside of each other, such as brackets inside of brackets. Each level of
+    /// nesting takes more of the stack to read, so deeply nested code is rejected rather than overflowing it.
+    pub max_depth: usize,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions { max_depth: 128 }
+    }
+}
+
+thread_local! {
+    // Only present while `parse_string_with_stats` is running, so normal parsing skips the bookkeeping.
+    static PARSE_STATS: RefCell<Option<ParseStats>> = RefCell::new(None);
+
+    // How deeply nested the operation being read is, and how deep it's allowed to go. These are only changed through
+    // `OptionsGuard` and `NestingLevel`, which put them back when they're dropped.
+    static DEPTH: Cell<usize> = Cell::new(0);
+    static MAX_DEPTH: Cell<usize> = Cell::new(ParseOptions::default().max_depth);
+
+    // Whether a name followed by braces can be read as a struct literal. Only changed through `with_struct_literals`.
+    static STRUCT_LITERALS: Cell<bool> = Cell::new(true);
+}
+
+// Puts the options in place for as long as it's alive. The old ones are restored when it's dropped, even if the parser
+// panics, so they can't leak into the next parse on the same thread.
+struct OptionsGuard {
+    depth: usize,
+    max_depth: usize,
+}
+
+impl OptionsGuard {
+    fn new(options: &ParseOptions) -> OptionsGuard {
+        OptionsGuard {
+            depth: DEPTH.with(|depth| depth.replace(0)),
+            max_depth: MAX_DEPTH.with(|max_depth| max_depth.replace(options.max_depth)),
+        }
+    }
+}
+
+impl Drop for OptionsGuard {
+    fn drop(&mut self) {
+        DEPTH.with(|depth| depth.set(self.depth));
+        MAX_DEPTH.with(|max_depth| max_depth.set(self.max_depth));
+    }
+}
+
+// One level of nesting, which is given back when it's dropped.
+struct NestingLevel;
+
+impl NestingLevel {
+    // Gives the level along with how deep we are now.
+    fn enter() -> (NestingLevel, usize) {
+        let depth = DEPTH.with(|depth| {
+            depth.set(depth.get() + 1);
+            depth.get()
+        });
+
+        (NestingLevel, depth)
+    }
+}
+
+impl Drop for NestingLevel {
+    fn drop(&mut self) {
+        DEPTH.with(|depth| depth.set(depth.get() - 1));
+    }
+}
+
+// Runs a parser that can end up calling itself, one level of nesting deeper than the caller.
+fn nested<'a, O>(
+    input: &'a str,
+    parser: impl FnOnce(&'a str) -> ParserResult<'a, O>,
+) -> ParserResult<'a, O> {
+    let (_level, depth) = NestingLevel::enter();
+
+    if depth > MAX_DEPTH.with(Cell::get) {
+        Err(verbose_failure(input, "Expression nesting too deep."))
+    } else {
+        parser(input)
+    }
+}
+
+// Puts back whether struct literals were allowed when it's dropped.
+struct StructLiteralsGuard(bool);
+
+impl Drop for StructLiteralsGuard {
+    fn drop(&mut self) {
+        STRUCT_LITERALS.with(|allowed| allowed.set(self.0));
+    }
+}
+
+// Runs a parser with struct literals allowed or not. The braces after the condition of an `if`, `while`, `for` or
+// `match` belong to it, so `if ready {}` can't be read as building a struct named `ready`. Brackets of any kind make
+// it clear where a literal ends, so they allow struct literals again.
+fn with_struct_literals<'a, O>(
+    allowed: bool,
+    parser: impl Fn(&'a str) -> ParserResult<'a, O>,
+) -> impl Fn(&'a str) -> ParserResult<'a, O> {
+    move |input| {
+        let _guard = StructLiteralsGuard(STRUCT_LITERALS.with(|old| old.replace(allowed)));
+        parser(input)
+    }
+}
+
+fn count_parser(counter: fn(&mut ParseStats) -> &mut usize) {
+    PARSE_STATS.with(|stats| {
+        if let Some(stats) = stats.borrow_mut().as_mut() {
+            *counter(stats) += 1;
+        }
+    });
+}
+
+fn verbose_error<'a>(input: &'a str, message: &'static str) -> NomErr<VerboseError<&'a str>> {
+    let vek = VerboseErrorKind::Context(message);
+
+    let ve = VerboseError {
+        errors: vec![(input, vek)],
+    };
+
+    NomErr::Error(ve)
+}
+
+// An error that names the code it's about, such as `Integer literal 300 does not fit in u8.` Nom only takes static
+// context messages, so the words before and after the code are kept as two contexts that both point at it.
+fn naming_error<'a>(
+    code: &'a str,
+    before: &'static str,
+    after: &'static str,
+) -> NomErr<VerboseError<&'a str>> {
+    NomErr::Error(VerboseError {
+        errors: vec![
+            (code, VerboseErrorKind::Context(before)),
+            (code, VerboseErrorKind::Context(after)),
+        ],
+    })
+}
+
+fn naming_failure<'a>(
+    code: &'a str,
+    before: &'static str,
+    after: &'static str,
+) -> NomErr<VerboseError<&'a str>> {
+    match naming_error(code, before, after) {
+        NomErr::Error(error) => NomErr::Failure(error),
+        other => other,
+    }
+}
+
+// Like `convert_error`, but an error from `naming_error` has its message put together around the code it points at.
+// Nothing else starts an error with two contexts on the same piece of code, since nom only adds to the end of one.
+fn describe_error(input: &str, mut error: VerboseError<&str>) -> String {
+    let message = match error.errors.as_slice() {
+        [(code, VerboseErrorKind::Context(before)), (other, VerboseErrorKind::Context(after)), ..]
+            if code.as_ptr() == other.as_ptr() && code.len() == other.len() =>
+        {
+            format!("{}{}{}", before, code, after)
+        }
+        _ => return convert_error(input, error),
+    };
+
+    error.errors.drain(..2);
+    if error.errors.is_empty() {
+        message
+    } else {
+        format!("{}\n{}", message, convert_error(input, error))
+    }
+}
+
+fn read_comment(input: &str) -> ParserResult<&str> {
+    alt((
+        // The last line of a file doesn't need to end in a new line.
+        preceded(
+            tag("//"),
+            alt((terminated(take_until("\n"), tag("\n")), rest)),
+        ),
+        preceded(tag("/*"), terminated(take_until("*/"), tag("*/"))),
+    ))(input)
+}
+
+fn read_comments(input: &str) -> ParserResult<&str> {
+    recognize(many0_count(terminated(read_comment, multispace0)))(input)
+}
+
+// Doc comments are `///` and `/** */`. Like in Rust, `////` and `/***` are just normal comments.
+fn read_doc_comment(input: &str) -> ParserResult<&str> {
+    alt((
+        preceded(
+            terminated(tag("///"), not(char('/'))),
+            terminated(take_until("\n"), tag("\n")),
+        ),
+        preceded(
+            terminated(tag("/**"), not(one_of("*/"))),
+            terminated(take_until("*/"), tag("*/")),
+        ),
+    ))(input)
+}
+
+// Like `blank`, but stops at doc comments so they can be given to the item after them.
+fn blank_before_docs(input: &str) -> ParserResult<()> {
+    let plain_comment = preceded(not(read_doc_comment), read_comment);
+
+    value(
+        (),
+        preceded(
+            multispace0,
+            many0_count(terminated(plain_comment, multispace0)),
+        ),
+    )(input)
+}
+
+// Reads the doc comments in front of an item. Each comment becomes its own line of the documentation.
+fn read_doc_comments(input: &str) -> ParserResult<Option<String>> {
+    let (input, _) = blank_before_docs(input)?;
+    let (input, lines) = many0(terminated(read_doc_comment, blank_before_docs))(input)?;
+
+    if lines.is_empty() {
+        Ok((input, None))
+    } else {
+        let lines: Vec<&str> = lines.iter().map(|line| line.trim()).collect();
+        Ok((input, Some(lines.join("\n"))))
+    }
 }
 
-#[derive(Debug)]
-pub struct ParseError {
-    message: String,
-}
+// An attribute such as `#[test]` or `#[derive(Debug, Hash)]`. Arguments are taken as written, up to the next comma or
+// closing parenthesis.
+fn read_attribute(input: &str) -> ParserResult<NLAttribute> {
+    fn read_arg(input: &str) -> ParserResult<&str> {
+        let (input, _) = blank(input)?;
+        let (input, arg) =
+            take_while1(|c: char| !c.is_whitespace() && !",()[]".contains(c))(input)?;
+        let (input, _) = blank(input)?;
 
-impl std::error::Error for ParseError {
-    fn description(&self) -> &str {
-        &self.message
+        Ok((input, arg))
     }
-}
 
-impl std::fmt::Display for ParseError {
-    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
-        write!(f, "{}", self.message)
+    // Once we've seen `#[`, this can only be an attribute. Mistakes inside of it are failures, so they're reported
+    // here rather than as whatever the parser tries next.
+    fn read_closing<'a>(
+        opener: &'a str,
+        closer: char,
+        message: &'static str,
+    ) -> impl Fn(&'a str) -> ParserResult<'a, char> {
+        move |input| {
+            read_closer(opener, closer)(input).map_err(|error| match error {
+                NomErr::Error(_) => verbose_failure(input.trim_start(), message),
+                other => other,
+            })
+        }
     }
-}
 
-fn verbose_error<'a>(input: &'a str, message: &'static str) -> NomErr<VerboseError<&'a str>> {
-    let vek = VerboseErrorKind::Context(message);
+    fn read_args(input: &str) -> ParserResult<Vec<&str>> {
+        let (after_paren, _) = char('(')(input)?;
+        let (after_args, args) = comma_separated0(read_arg)(after_paren)?;
+        let (after_args, _) = read_closing(
+            input,
+            ')',
+            "Expected a `,` or `)` after the attribute's argument.",
+        )(after_args)?;
 
-    let ve = VerboseError {
-        errors: vec![(input, vek)],
-    };
+        Ok((after_args, args))
+    }
 
-    NomErr::Error(ve)
+    let (input, _) = char('#')(input)?;
+    let opener = input;
+    let (input, _) = char('[')(input)?;
+    let (input, _) = blank(input)?;
+    let (input, name) = read_method_name(input)
+        .map_err(|_| verbose_failure(input, "Expected the name of an attribute."))?;
+    let (input, _) = blank(input)?;
+    let (input, args) = opt(read_args)(input)?;
+    let args = args.unwrap_or_default();
+    let (input, _) = read_closing(opener, ']', "Expected a `]` to close the attribute.")(input)?;
+
+    Ok((input, NLAttribute { name, args }))
 }
 
-fn read_comment(input: &str) -> ParserResult<&str> {
-    alt((
-        preceded(tag("//"), terminated(take_until("\n"), tag("\n"))),
-        preceded(tag("/*"), terminated(take_until("*/"), tag("*/"))),
-    ))(input)
+fn read_attributes(input: &str) -> ParserResult<Vec<NLAttribute>> {
+    let (input, _) = blank(input)?;
+    many0(terminated(read_attribute, blank))(input)
 }
 
-fn read_comments(input: &str) -> ParserResult<&str> {
-    recognize(many0_count(terminated(read_comment, multispace0)))(input)
+// Reads the doc comments and attributes in front of an item. Like in Rust, they can be mixed in any order.
+fn read_item_prefix(input: &str) -> ParserResult<(Option<String>, Vec<NLAttribute>)> {
+    let (mut input, mut doc) = read_doc_comments(input)?;
+    let mut attributes = Vec::new();
+
+    loop {
+        let (after_attributes, mut more_attributes) =
+            many0(terminated(read_attribute, blank_before_docs))(input)?;
+        if more_attributes.is_empty() {
+            break;
+        }
+        attributes.append(&mut more_attributes);
+
+        let (after_doc, more_doc) = read_doc_comments(after_attributes)?;
+        input = after_doc;
+        if let Some(more_doc) = more_doc {
+            doc = match doc {
+                Some(doc) => Some(format!("{}\n{}", doc, more_doc)),
+                None => Some(more_doc),
+            };
+        }
+    }
+
+    Ok((input, (doc, attributes)))
 }
 
 fn blank(input: &str) -> ParserResult<()> {
@@ -533,18 +2684,19 @@ fn is_name(c: char) -> bool {
     match c {
         '_' => true,
         '.' => true, // Used for scoped names.
-        _ => (c >= 'a' && c <= 'z') || (c >= 'A' && c <= 'Z'),
+        // Includes non-ASCII letters, so names can be written in any language.
+        _ => c.is_alphabetic(),
     }
 }
 
 fn read_struct_or_trait_name(input: &str) -> ParserResult<&str> {
-    delimited(blank, alphanumeric1, blank)(input)
+    delimited(blank, take_while1(|c: char| c.is_alphanumeric()), blank)(input)
 }
 
 fn is_method_char(input: char) -> bool {
     match input {
         '_' => true,
-        _ => is_alphanumeric(input as u8),
+        _ => input.is_alphanumeric(),
     }
 }
 
@@ -552,41 +2704,166 @@ fn read_method_name(input: &str) -> ParserResult<&str> {
     delimited(blank, take_while1(is_method_char), blank)(input)
 }
 
-fn read_tuple_of_variable_names(input: &str) -> ParserResult<Vec<&str>> {
-    let (input, tuple_str) = delimited(char('('), take_while(|c| c != ')'), char(')'))(input)?;
-
-    let (tuple_str, mut variables) = many0(terminated(
-        read_variable_name,
-        tuple((blank, char(','), blank)),
-    ))(tuple_str)?;
+// Reads zero or more items separated by commas, such as the arguments of a call. A single trailing comma is allowed
+// after the last item. The closing bracket is left for the caller, so items can have brackets of their own.
+fn comma_separated0<'a, O, F>(mut parser: F) -> impl FnMut(&'a str) -> ParserResult<'a, Vec<O>>
+where
+    F: FnMut(&'a str) -> ParserResult<'a, O>,
+{
+    move |mut input: &'a str| {
+        let mut items = Vec::new();
+
+        loop {
+            match parser(input) {
+                Ok((after_item, item)) => {
+                    items.push(item);
+                    input = after_item;
+                }
+                Err(NomErr::Error(_)) => break,
+                Err(error) => return Err(error),
+            }
 
-    let (_, last_var) = opt(terminated(read_variable_name, blank))(tuple_str)?;
-    match last_var {
-        Some(var) => {
-            variables.push(var);
+            match preceded(blank, char(','))(input) {
+                Ok((after_comma, _)) => input = after_comma,
+                Err(NomErr::Error(_)) => break,
+                Err(error) => return Err(error),
+            }
         }
-        _ => {} // Do nothing if there was no argument.
+
+        Ok((input, items))
     }
+}
+
+fn read_tuple_of_variable_names(input: &str) -> ParserResult<Vec<&str>> {
+    let (input, _) = char('(')(input)?;
+    let (input, variables) = comma_separated0(read_variable_name)(input)?;
+    let (input, _) = blank(input)?;
+    let (input, _) = char(')')(input)?;
 
     Ok((input, variables))
 }
 
 fn read_tuple(input: &str) -> ParserResult<NLOperation> {
-    let (input, _) = blank(input)?;
-    let (input, tuple_str) = delimited(char('('), take_while(|c| c != ')'), char(')'))(input)?;
+    let (opener, _) = blank(input)?;
+    let (input, _) = char('(')(opener)?;
+
+    // Elements can be tuples of their own, so we read straight through to the closing parenthesis.
+    let (input, tuple) = comma_separated0(read_operation)(input)?;
+    let (input, _) = read_closer(opener, ')')(input)?;
+
+    // An empty tuple has no value at all, so it's the same thing as Unit.
+    if tuple.is_empty() {
+        Ok((input, NLOperation::Unit))
+    } else {
+        Ok((input, NLOperation::Tuple(tuple)))
+    }
+}
 
-    let (tuple_str, mut tuple) =
-        many0(terminated(read_operation, tuple((blank, char(','), blank))))(tuple_str)?;
+fn read_array_literal(input: &str) -> ParserResult<NLOperation> {
+    let (opener, _) = blank(input)?;
+    let (input, _) = char('[')(opener)?;
 
-    let (_, last_item) = opt(terminated(read_operation, blank))(tuple_str)?;
-    match last_item {
-        Some(item) => {
-            tuple.push(item);
+    // Elements can have brackets of their own, so we read straight through to the closing one.
+    let (input, first) = opt(read_operation)(input)?;
+    let first = match first {
+        Some(first) => first,
+        None => {
+            let (input, _) = read_closer(opener, ']')(input)?;
+
+            return Ok((input, NLOperation::ArrayLiteral(vec![])));
         }
-        _ => {} // Do nothing if there was no argument.
+    };
+
+    let (input, _) = blank(input)?;
+    let (input, is_repeat) = opt(char(';'))(input)?;
+    if is_repeat.is_some() {
+        let (input, count) = read_operation(input)?;
+        let (input, _) = read_closer(opener, ']')(input)?;
+
+        return Ok((
+            input,
+            NLOperation::ArrayRepeat {
+                value: Box::new(first),
+                count: Box::new(count),
+            },
+        ));
     }
 
-    Ok((input, NLOperation::Tuple(tuple)))
+    let (input, elements) = opt(preceded(char(','), comma_separated0(read_operation)))(input)?;
+    let mut elements = elements.unwrap_or_default();
+    elements.insert(0, first);
+
+    let (input, _) = read_closer(opener, ']')(input)?;
+
+    Ok((input, NLOperation::ArrayLiteral(elements)))
+}
+
+// Variable names are allowed to contain dots, so we have to make sure we don't mistake `..` for a field.
+fn read_field_name(input: &str) -> ParserResult<&str> {
+    verify(read_variable_name, |name: &str| !name.starts_with('.'))(input)
+}
+
+fn read_struct_pattern(input: &str) -> ParserResult<StructPattern> {
+    let (input, nl_struct) = read_struct_or_trait_name(input)?;
+    let (input, _) = char('{')(input)?;
+
+    let (input, fields) = comma_separated0(read_field_name)(input)?;
+
+    let (input, _) = blank(input)?;
+    let (input, has_rest) = opt(tag(".."))(input)?;
+    let has_rest = has_rest.is_some();
+
+    let (input, _) = blank(input)?;
+    let (input, _) = char('}')(input)?;
+
+    Ok((
+        input,
+        StructPattern {
+            nl_struct,
+            fields,
+            has_rest,
+        },
+    ))
+}
+
+fn read_struct_literal(input: &str) -> ParserResult<NLOperation> {
+    fn read_field(input: &str) -> ParserResult<(&str, NLOperation)> {
+        let (input, name) = read_field_name(input)?;
+        let (input, _) = blank(input)?;
+        let (input, _) = char(':')(input)?;
+        let (input, value) = read_operation(input)?;
+
+        Ok((input, (name, value)))
+    }
+
+    if !STRUCT_LITERALS.with(Cell::get) {
+        return Err(verbose_error(input, "Struct literals aren't allowed here."));
+    }
+
+    let (opener, nl_struct) = read_struct_or_trait_name(input)?;
+    let (input, _) = char('{')(opener)?;
+    let (input, fields) = comma_separated0(read_field)(input)?;
+
+    let (input, _) = blank(input)?;
+    let (input, base) = opt(preceded(tag(".."), read_operation))(input)?;
+
+    let (input, _) = blank(input)?;
+    if base.is_some() && input.starts_with(',') {
+        return Err(verbose_failure(
+            &input[..1],
+            "The `..` base of a struct literal must come after all of its fields.",
+        ));
+    }
+    let (input, _) = read_closer(opener, '}')(input)?;
+
+    Ok((
+        input,
+        NLOperation::StructLiteral(StructLiteral {
+            nl_struct,
+            fields,
+            base: base.map(Box::new),
+        }),
+    ))
 }
 
 fn read_single_variable(input: &str) -> ParserResult<Vec<&str>> {
@@ -603,10 +2880,11 @@ fn read_boolean_constant(input: &str) -> ParserResult<OpConstant> {
     }
 }
 
-// TODO this is to be used for casting variable types, not constant types.
+// The `as u64` that can follow an operation to change its type.
 fn read_cast(input: &str) -> ParserResult<NLType> {
     let (input, _) = blank(input)?;
     let (input, _) = tag("as")(input)?;
+    let (input, _) = not(satisfy(is_method_char))(input)?;
     let (input, _) = blank(input)?;
 
     read_variable_type(input)
@@ -615,13 +2893,27 @@ fn read_cast(input: &str) -> ParserResult<NLType> {
 struct ParsedInteger<'a> {
     text: &'a str,
     radix: u32,
+    is_negative: bool,
 }
 
 fn parse_decimal(input: &str) -> ParserResult<ParsedInteger> {
+    // Underscores can only go between digits.
     let (input, text) =
-        recognize(many1(terminated(one_of("-0123456789"), many0(char('_')))))(input)?;
+        recognize(tuple((digit1, many0(preceded(many1(char('_')), digit1)))))(input)?;
+
+    if input.starts_with('_') {
+        return Err(naming_failure(
+            text,
+            "Integer literal ",
+            " cannot end with an underscore.",
+        ));
+    }
 
-    let product = ParsedInteger { text, radix: 10 };
+    let product = ParsedInteger {
+        text,
+        radix: 10,
+        is_negative: false,
+    };
     Ok((input, product))
 }
 
@@ -634,17 +2926,40 @@ fn parse_hexadecimal(input: &str) -> ParserResult<ParsedInteger> {
         ))),
     )(input)?;
 
-    let product = ParsedInteger { text, radix: 16 };
+    let product = ParsedInteger {
+        text,
+        radix: 16,
+        is_negative: false,
+    };
     Ok((input, product))
 }
 
+// A decimal digit right after a binary or octal number is one that radix doesn't have, such as the 2 in `0b12`. Left
+// alone, the number would end early and the digit would be read as something else.
+fn reject_digit_after<'a>(
+    input: &'a str,
+    after: &'static str,
+) -> Result<(), NomErr<VerboseError<&'a str>>> {
+    match input.chars().next() {
+        Some(digit) if digit.is_ascii_digit() => {
+            Err(naming_failure(&input[..1], "The digit ", after))
+        }
+        _ => Ok(()),
+    }
+}
+
 fn parse_octal(input: &str) -> ParserResult<ParsedInteger> {
     let (input, text) = preceded(
         alt((tag("0o"), tag("0O"))),
         recognize(many1(terminated(one_of("01234567"), many0(char('_'))))),
     )(input)?;
+    reject_digit_after(input, " can't be used in an octal number.")?;
 
-    let product = ParsedInteger { text, radix: 8 };
+    let product = ParsedInteger {
+        text,
+        radix: 8,
+        is_negative: false,
+    };
     Ok((input, product))
 }
 
@@ -653,13 +2968,27 @@ fn parse_binary(input: &str) -> ParserResult<ParsedInteger> {
         alt((tag("0b"), tag("0B"))),
         recognize(many1(terminated(one_of("01"), many0(char('_'))))),
     )(input)?;
+    reject_digit_after(input, " can't be used in a binary number.")?;
 
-    let product = ParsedInteger { text, radix: 2 };
+    let product = ParsedInteger {
+        text,
+        radix: 2,
+        is_negative: false,
+    };
     Ok((input, product))
 }
 
 fn parse_integer(input: &str) -> ParserResult<ParsedInteger> {
-    alt((parse_hexadecimal, parse_binary, parse_octal, parse_decimal))(input)
+    // Only a single sign at the front, so `1-2` is a subtraction. It goes before the radix prefix, as in `-0x10`.
+    let (input, sign) = opt(char('-'))(input)?;
+    let (input, integer) =
+        alt((parse_hexadecimal, parse_binary, parse_octal, parse_decimal))(input)?;
+
+    let product = ParsedInteger {
+        is_negative: sign.is_some(),
+        ..integer
+    };
+    Ok((input, product))
 }
 
 fn parse_float(input: &str) -> ParserResult<&str> {
@@ -682,11 +3011,32 @@ fn parse_float(input: &str) -> ParserResult<&str> {
             opt(one_of("+-")),
             parse_decimal,
         ))),
-        recognize(tuple((opt(char('-')), parse_decimal, char('.'), opt(parse_decimal)))),
+        // Two dots in a row is a range, not a float with nothing after the decimal point.
+        recognize(tuple((
+            opt(char('-')),
+            parse_decimal,
+            terminated(char('.'), not(char('.'))),
+            opt(parse_decimal),
+        ))),
     ))(input)
 }
 
 fn read_numerical_constant(input: &str) -> ParserResult<OpConstant> {
+    read_numerical_constant_with_default(input, NLType::I32)
+}
+
+/// Reads a number, using the default type for integers that weren't given a type suffix. This lets a type checker
+/// re-read a constant once it knows what type is expected of it. Defaults that aren't integer types are ignored and
+/// the integer is read as an i32.
+pub fn read_numerical_constant_with_default<'a>(
+    input: &'a str,
+    default: NLType<'a>,
+) -> ParserResult<'a, OpConstant<'a>> {
+    let default = if default.is_integer() {
+        default
+    } else {
+        NLType::I32
+    };
 
     // Try to read as a float first.
     let float_attempt = parse_float(input);
@@ -698,7 +3048,9 @@ fn read_numerical_constant(input: &str) -> ParserResult<OpConstant> {
         where
             T: std::str::FromStr,
         {
-            let value = input.parse::<T>();
+            // Underscores are only there to make the number easier to read.
+            let digits: String = input.chars().filter(|c| *c != '_').collect();
+            let value = digits.parse::<T>();
             match value {
                 Ok(value) => {
                     // Its a valid integer.
@@ -715,7 +3067,7 @@ fn read_numerical_constant(input: &str) -> ParserResult<OpConstant> {
         }
 
         // Figure out the type.
-        match read_variable_type_primitive_no_whitespace(input) {
+        let (input, constant) = match read_variable_type_primitive_no_whitespace(input) {
             Ok((input, nl_type)) => match nl_type {
                 // It must be a floating point type.
                 NLType::F32 => {
@@ -735,39 +3087,183 @@ fn read_numerical_constant(input: &str) -> ParserResult<OpConstant> {
                 // If unspecified, assume 32bit.
                 let (_, number) = parse_number::<f32>(number)?;
                 Ok((input, OpConstant::Float32(number)))
-            }, 
+            },
+        }?;
+
+        // Catch things like `1.2.3`, while still allowing for float ranges such as `1.5..2.5`.
+        if input.starts_with('.') && !input.starts_with("..") {
+            return Err(verbose_failure(
+                input,
+                "Malformed float: a number can only have one decimal point.",
+            ));
         }
+
+        Ok((input, constant))
     } else {
         // We attempt to read an integer.
-        let (input, integer) = parse_integer(input)?;
+        let (after_integer, integer) = parse_integer(input)?;
+
+        // Keep the prefix, so errors show the literal as it was written.
+        let literal = &input[..input.len() - after_integer.len()];
+        let input = after_integer;
 
         // Figure out the type.
-        let (input, nl_type) = match read_variable_type_primitive_no_whitespace(input) {
+        let (input, nl_type, is_typed) = match read_variable_type_primitive_no_whitespace(input) {
             Ok((input, nl_type)) => match nl_type {
                 // It can't be a boolean type.
                 NLType::Boolean => Err(verbose_error(
                     input,
                     "Cannot represent a number as a boolean.",
                 )),
-                _ => Ok((input, nl_type)), // Okay we're good. Use the type.
+                _ => Ok((input, nl_type, true)), // Okay we're good. Use the type.
             },
-            Err(_) => Ok((input, NLType::I32)), // If unspecified, assume 32bit.
+            Err(_) => Ok((input, default, false)), // If unspecified, use the default.
         }?;
 
+        // Underscores are only there to make the number easier to read. The sign comes after any radix prefix, so it's
+        // put back in front of the digits here.
+        let mut digits = String::new();
+        if integer.is_negative {
+            digits.push('-');
+        }
+        digits.extend(integer.text.chars().filter(|c| *c != '_'));
+
+        // Whole numbers can still be given a float type, such as `5f32`.
+        if nl_type.is_float() {
+            if integer.radix != 10 {
+                return Err(naming_failure(
+                    literal,
+                    "Only decimal numbers can be given a floating point type, but ",
+                    " was not decimal.",
+                ));
+            }
+
+            let constant = match nl_type {
+                NLType::F64 => digits.parse::<f64>().map(OpConstant::Float64).ok(),
+                _ => digits.parse::<f32>().map(OpConstant::Float32).ok(),
+            };
+
+            return match constant {
+                Some(constant) => Ok((input, constant)),
+                None => Err(naming_failure(literal, "Malformed float: ", "")),
+            };
+        }
+
+        if nl_type.is_unsigned() && integer.is_negative {
+            return Err(naming_failure(
+                literal,
+                "Integer literal ",
+                " is negative, so it cannot be unsigned.",
+            ));
+        }
+
         if nl_type.is_signed() {
-            match i64::from_str_radix(integer.text, integer.radix) {
-                Ok(number) => Ok((input, OpConstant::Signed(number, nl_type))),
-                Err(_error) => Err(verbose_error(input, "Failed to parse integer.")),
+            match i64::from_str_radix(&digits, integer.radix) {
+                Ok(number) => {
+                    check_integer_fits(literal, number as i128, &nl_type)?;
+                    Ok((input, OpConstant::Signed(number, nl_type, is_typed)))
+                }
+                Err(error) => Err(integer_parse_error(literal, &error, &nl_type)),
             }
         } else {
-            match u64::from_str_radix(integer.text, integer.radix) {
-                Ok(number) => Ok((input, OpConstant::Unsigned(number, nl_type))),
-                Err(_error) => Err(verbose_error(input, "Failed to parse integer.")),
+            match u64::from_str_radix(&digits, integer.radix) {
+                Ok(number) => {
+                    check_integer_fits(literal, number as i128, &nl_type)?;
+                    Ok((input, OpConstant::Unsigned(number, nl_type, is_typed)))
+                }
+                Err(error) => Err(integer_parse_error(literal, &error, &nl_type)),
             }
         }
     }
 }
 
+fn integer_does_not_fit<'a>(literal: &'a str, nl_type: &NLType) -> NomErr<VerboseError<&'a str>> {
+    let after = match nl_type {
+        NLType::I8 => " does not fit in i8.",
+        NLType::I16 => " does not fit in i16.",
+        NLType::I32 => " does not fit in i32.",
+        NLType::I64 => " does not fit in i64.",
+        NLType::U8 => " does not fit in u8.",
+        NLType::U16 => " does not fit in u16.",
+        NLType::U32 => " does not fit in u32.",
+        NLType::U64 => " does not fit in u64.",
+        _ => " does not fit in its type.",
+    };
+
+    naming_failure(literal, "Integer literal ", after)
+}
+
+// We know for sure that this is a number, so these are failures rather than errors. That keeps the parser from trying
+// to read the literal as something else and hiding the real problem.
+fn verbose_failure<'a>(input: &'a str, message: &'static str) -> NomErr<VerboseError<&'a str>> {
+    match verbose_error(input, message) {
+        NomErr::Error(error) => NomErr::Failure(error),
+        other => other,
+    }
+}
+
+// Reads the closing delimiter of a pair, such as the `}` of a block. If we run out of input first, the error points at
+// the opener instead, which is much closer to the mistake than the end of the file is.
+fn read_closer<'a>(opener: &'a str, closer: char) -> impl Fn(&'a str) -> ParserResult<'a, char> {
+    move |input| {
+        let (input, _) = blank(input)?;
+
+        if input.is_empty() {
+            let message = match closer {
+                ')' => "Unterminated `(`, opened here.",
+                ']' => "Unterminated `[`, opened here.",
+                _ => "Unterminated `{`, opened here.",
+            };
+
+            return Err(verbose_failure(&opener[..1], message));
+        }
+
+        char(closer)(input)
+    }
+}
+
+// The smallest and largest values an integer type can hold.
+fn integer_range(nl_type: &NLType) -> (i128, i128) {
+    let bits = nl_type.num_bits() as u32;
+    if nl_type.is_signed() {
+        (-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1)
+    } else {
+        (0, (1i128 << bits) - 1)
+    }
+}
+
+fn check_integer_fits<'a>(
+    literal: &'a str,
+    value: i128,
+    nl_type: &NLType,
+) -> Result<(), NomErr<VerboseError<&'a str>>> {
+    if !nl_type.is_integer() {
+        return Ok(());
+    }
+
+    let (min, max) = integer_range(nl_type);
+    if value < min || value > max {
+        Err(integer_does_not_fit(literal, nl_type))
+    } else {
+        Ok(())
+    }
+}
+
+fn integer_parse_error<'a>(
+    literal: &'a str,
+    error: &std::num::ParseIntError,
+    nl_type: &NLType,
+) -> NomErr<VerboseError<&'a str>> {
+    use std::num::IntErrorKind;
+
+    match error.kind() {
+        IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => {
+            integer_does_not_fit(literal, nl_type)
+        }
+        _ => verbose_error(literal, "Failed to parse integer."),
+    }
+}
+
 fn read_string_constant(input: &str) -> ParserResult<OpConstant> {
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     enum StringFragment<'a> {
@@ -778,13 +3274,27 @@ fn read_string_constant(input: &str) -> ParserResult<OpConstant> {
 
     fn parse_fragment(input: &str) -> ParserResult<StringFragment> {
         fn parse_unicode_char(input: &str) -> ParserResult<char> {
+            let (after_u, _) = char('u')(input)?;
+
+            // Once we've seen the `u` this can't be anything but a unicode escape, so bad ones are failures.
             let parse_hex = take_while_m_n(1, 6, |c: char| c.is_ascii_hexdigit());
-            let parse_delimited_hex = preceded(
-                char('u'),
-                delimited(char('{'), parse_hex, char('}')),
-            );
-            let parse_u32 = map_res(parse_delimited_hex, move |hex| u32::from_str_radix(hex, 16));
-            map_opt(parse_u32, |value| std::char::from_u32(value))(input)
+            let hex: ParserResult<&str> = delimited(char('{'), parse_hex, char('}'))(after_u);
+            let (after_escape, hex) = hex.map_err(|_| {
+                verbose_failure(
+                    input,
+                    "Malformed unicode escape. Expected up to six hexadecimal digits, such as \\u{1F600}.",
+                )
+            })?;
+
+            let escape = &input[..input.len() - after_escape.len()];
+            let character = u32::from_str_radix(hex, 16)
+                .ok()
+                .and_then(std::char::from_u32)
+                .ok_or_else(|| {
+                    naming_failure(escape, "Unicode escape \\", " is not a valid character.")
+                })?;
+
+            Ok((after_escape, character))
         }
         
         fn parse_escaped_char(input: &str) -> ParserResult<char> {
@@ -834,22 +3344,44 @@ fn read_string_constant(input: &str) -> ParserResult<OpConstant> {
     Ok((input, OpConstant::String(string)))
 }
 
+// Raw strings look like `r"..."` or `r#"..."#`. The number of `#`s is up to the writer, and the string only ends
+// at a quote followed by that many `#`s, so quotes can be put inside of it.
+fn read_raw_string_constant(input: &str) -> ParserResult<OpConstant> {
+    let (input, _) = char('r')(input)?;
+    let (input, hashes) = take_while(|c| c == '#')(input)?;
+    let (input, _) = char('"')(input)?;
+
+    let closing = format!("\"{}", hashes);
+    match input.find(&closing) {
+        Some(end) => Ok((
+            &input[end + closing.len()..],
+            OpConstant::RawString(&input[..end]),
+        )),
+        None => Err(verbose_failure(input, "Raw string is never closed.")),
+    }
+}
+
 fn read_constant_raw(input: &str) -> ParserResult<OpConstant> {
     let (input, _) = blank(input)?;
     let (input, constant) = alt((
         read_boolean_constant,
         read_numerical_constant,
         read_string_constant,
+        read_raw_string_constant,
     ))(input)?;
     Ok((input, constant))
 }
 
 fn read_constant(input: &str) -> ParserResult<NLOperation> {
+    count_parser(|stats| &mut stats.read_constant);
+
     let (input, constant) = read_constant_raw(input)?;
     Ok((input, NLOperation::Constant(constant)))
 }
 
 fn read_assignment(input: &str) -> ParserResult<NLOperation> {
+    count_parser(|stats| &mut stats.read_assignment);
+
     // Are we defining?
     let (input, _) = blank(input)?;
     let (input, is_new) = opt(tag("let"))(input)?;
@@ -857,7 +3389,18 @@ fn read_assignment(input: &str) -> ParserResult<NLOperation> {
 
     // What is our name?
     let (input, _) = blank(input)?;
-    let (input, names) = alt((read_tuple_of_variable_names, read_single_variable))(input)?;
+
+    // New variables can be pulled out of a struct.
+    let (input, struct_pattern) = if is_new {
+        opt(read_struct_pattern)(input)?
+    } else {
+        (input, None)
+    };
+
+    let (input, names) = match &struct_pattern {
+        Some(pattern) => (input, pattern.fields.clone()),
+        None => alt((read_tuple_of_variable_names, read_single_variable))(input)?,
+    };
 
     let mut variables = Vec::new();
     variables.reserve(names.len());
@@ -882,174 +3425,284 @@ fn read_assignment(input: &str) -> ParserResult<NLOperation> {
         (input, assignment)
     };
 
-    // Consume equal sign.
+    // Consume equal sign, which can have an operator in front of it, such as `+=`.
     let (input, _) = blank(input)?;
-    let (input, _) = char('=')(input)?;
+    let (input, compound_operator) =
+        opt(terminated(take_compound_operator_symbol, char('=')))(input)?;
+    let input = match compound_operator {
+        Some(_) => input,
+        None => char('=')(input)?.0,
+    };
     let (input, _) = blank(input)?;
 
     // What's the value we are assigning to?
     let (input, _) = blank(input)?;
     let (input, assignment) = read_operation(input)?;
 
+    // `x += 1` is the same as `x = x + 1`.
+    let assignment = match compound_operator {
+        Some(operator) => {
+            if is_new {
+                return Err(verbose_failure(
+                    operator,
+                    "Compound assignments can't declare a new variable.",
+                ));
+            }
+
+            if variables.len() != 1 || has_type_assignment {
+                return Err(verbose_failure(
+                    operator,
+                    "Compound assignments can only assign to a single variable, without a type.",
+                ));
+            }
+
+            let variable = NLOperation::VariableAccess(OpVariable {
+                name: variables[0].name,
+            });
+            NLOperation::Operator(build_binary_operator(
+                operator,
+                Box::new(variable),
+                Box::new(assignment),
+            ))
+        }
+        None => assignment,
+    };
+
     let assignment = OpAssignment {
         is_new,
         to_assign: variables,
         type_assignments,
+        struct_pattern,
         assignment: Box::new(assignment),
     };
 
     Ok((input, NLOperation::Assign(assignment)))
 }
 
-fn take_operator_symbol(input: &str) -> ParserResult<&str> {
-    fn is_operator_symbol(c: char) -> bool {
-        match c {
-            '=' | '!' | '~' | '|' | '&' | '^' | '%' | '+' | '-' | '*' | '/' | '<' | '>' | '.' => {
-                true
-            }
-            _ => false,
-        }
-    }
-
-    take_while1(is_operator_symbol)(input)
+// Operators are matched against a known set, longest first, so that `a * -b` reads as `*` followed by a negated `b`
+// rather than a single `*-` operator.
+fn take_binary_operator_symbol(input: &str) -> ParserResult<&str> {
+    alt((
+        alt((
+            tag("=="),
+            tag("!="),
+            tag(">="),
+            tag("<="),
+            tag("&&"),
+            tag("||"),
+            tag("^^"),
+            tag("<<"),
+            tag(">>"),
+            tag(".."),
+        )),
+        alt((
+            tag(">"),
+            tag("<"),
+            tag("&"),
+            tag("|"),
+            tag("^"),
+            tag("+"),
+            tag("-"),
+            tag("%"),
+            tag("/"),
+            tag("*"),
+        )),
+    ))(input)
 }
 
-fn read_urinary_operator(input: &str) -> ParserResult<NLOperation> {
-    let (input, _) = blank(input)?;
-    let (input, operator) = take_operator_symbol(input)?;
-
-    let (input, _) = blank(input)?;
-    let (input, operand) = read_operation(input)?;
-    let operand = Box::new(operand);
-
-    match operator {
-        "!" => {
-            let operator = OpOperator::LogicalNegate(operand);
-            Ok((input, NLOperation::Operator(operator)))
-        }
-        "~" => {
-            let operator = OpOperator::BitNegate(operand);
-            Ok((input, NLOperation::Operator(operator)))
-        }
-        "-" => {
-            let operator = OpOperator::ArithmeticNegate(operand);
-            Ok((input, NLOperation::Operator(operator)))
-        }
-
-        _ => Err(verbose_error(input, "unknown operator")),
-    }
+// The operators that can be put in front of an `=`. Comparisons are left out so that `x <= y` isn't read as an
+// assignment.
+fn take_compound_operator_symbol(input: &str) -> ParserResult<&str> {
+    alt((
+        tag("<<"),
+        tag(">>"),
+        tag("&"),
+        tag("|"),
+        tag("^"),
+        tag("+"),
+        tag("-"),
+        tag("%"),
+        tag("/"),
+        tag("*"),
+    ))(input)
 }
 
-fn read_binary_operator(input: &str) -> ParserResult<NLOperation> {
-    let (input, _) = blank(input)?;
-    let (input, operand_a) = read_sub_operation(input)?;
-    let operand_a = Box::new(operand_a);
+fn take_urinary_operator_symbol(input: &str) -> ParserResult<&str> {
+    alt((tag("!"), tag("~"), tag("-")))(input)
+}
 
+fn read_urinary_operator(input: &str) -> ParserResult<NLOperation> {
     let (input, _) = blank(input)?;
-    let (input, operator) = take_operator_symbol(input)?;
+    let (input, operator) = take_urinary_operator_symbol(input)?;
 
+    // Only the operand right after the operator is taken, so `-a + b` is `(-a) + b`.
     let (input, _) = blank(input)?;
-    let (input, operand_b) = read_sub_operation(input)?;
-    let operand_b = Box::new(operand_b);
-
-    match operator {
-        // Logical operators.
-        "==" => {
-            let operator = OpOperator::CompareEqual((operand_a, operand_b));
-            Ok((input, NLOperation::Operator(operator)))
-        }
-        "!=" => {
-            let operator = OpOperator::CompareNotEqual((operand_a, operand_b));
-            Ok((input, NLOperation::Operator(operator)))
-        }
-        // TODO create formal errors for => and =< operators to help the noobs.
-        ">=" => {
-            let operator = OpOperator::CompareGreaterEqual((operand_a, operand_b));
-            Ok((input, NLOperation::Operator(operator)))
-        }
-        "<=" => {
-            let operator = OpOperator::CompareLessEqual((operand_a, operand_b));
-            Ok((input, NLOperation::Operator(operator)))
-        }
-
-        ">" => {
-            let operator = OpOperator::CompareGreater((operand_a, operand_b));
-            Ok((input, NLOperation::Operator(operator)))
-        }
-        "<" => {
-            let operator = OpOperator::CompareLess((operand_a, operand_b));
-            Ok((input, NLOperation::Operator(operator)))
-        }
-        "&&" => {
-            let operator = OpOperator::LogicalAnd((operand_a, operand_b));
-            Ok((input, NLOperation::Operator(operator)))
-        }
-        "||" => {
-            let operator = OpOperator::LogicalOr((operand_a, operand_b));
-            Ok((input, NLOperation::Operator(operator)))
-        }
-        "^^" => {
-            let operator = OpOperator::LogicalXor((operand_a, operand_b));
-            Ok((input, NLOperation::Operator(operator)))
-        }
+    let (input, operand) = nested(input, read_sub_operation)?;
+    let operand = Box::new(operand);
 
-        // Bitwise operators.
-        "&" => {
-            let operator = OpOperator::BitAnd((operand_a, operand_b));
-            Ok((input, NLOperation::Operator(operator)))
-        }
-        "|" => {
-            let operator = OpOperator::BitOr((operand_a, operand_b));
-            Ok((input, NLOperation::Operator(operator)))
-        }
-        "^" => {
-            let operator = OpOperator::BitXor((operand_a, operand_b));
+    match operator {
+        "!" => {
+            let operator = OpOperator::LogicalNegate(operand);
             Ok((input, NLOperation::Operator(operator)))
         }
-        "<<" => {
-            let operator = OpOperator::BitLeftShift((operand_a, operand_b));
+        "~" => {
+            let operator = OpOperator::BitNegate(operand);
             Ok((input, NLOperation::Operator(operator)))
         }
-        ">>" => {
-            let operator = OpOperator::BitRightShift((operand_a, operand_b));
+        "-" => {
+            let operator = OpOperator::ArithmeticNegate(operand);
             Ok((input, NLOperation::Operator(operator)))
         }
 
+        _ => Err(verbose_error(input, "unknown operator")),
+    }
+}
+
+// How tightly a binary operator binds to its operands. Higher numbers bind tighter.
+fn binary_operator_precedence(operator: &str) -> u8 {
+    match operator {
+        "*" | "/" | "%" => 10,
+        "+" | "-" => 9,
+        "<<" | ">>" => 8,
+        "&" => 7,
+        "^" => 6,
+        "|" => 5,
+        "==" | "!=" | ">=" | "<=" | ">" | "<" => 4,
+        "&&" => 3,
+        "^^" => 2,
+        "||" => 1,
+        ".." => 0,
+        _ => panic!("Got an operator that take_binary_operator_symbol should never return."),
+    }
+}
+
+fn build_binary_operator<'a>(
+    operator: &str,
+    operand_a: Box<NLOperation<'a>>,
+    operand_b: Box<NLOperation<'a>>,
+) -> OpOperator<'a> {
+    match operator {
+        // Logical operators.
+        "==" => OpOperator::CompareEqual((operand_a, operand_b)),
+        "!=" => OpOperator::CompareNotEqual((operand_a, operand_b)),
+        ">=" => OpOperator::CompareGreaterEqual((operand_a, operand_b)),
+        "<=" => OpOperator::CompareLessEqual((operand_a, operand_b)),
+
+        ">" => OpOperator::CompareGreater((operand_a, operand_b)),
+        "<" => OpOperator::CompareLess((operand_a, operand_b)),
+        "&&" => OpOperator::LogicalAnd((operand_a, operand_b)),
+        "||" => OpOperator::LogicalOr((operand_a, operand_b)),
+        "^^" => OpOperator::LogicalXor((operand_a, operand_b)),
+
+        // Bitwise operators.
+        "&" => OpOperator::BitAnd((operand_a, operand_b)),
+        "|" => OpOperator::BitOr((operand_a, operand_b)),
+        "^" => OpOperator::BitXor((operand_a, operand_b)),
+        "<<" => OpOperator::BitLeftShift((operand_a, operand_b)),
+        ">>" => OpOperator::BitRightShift((operand_a, operand_b)),
+
         // Arithmetic operators.
-        "+" => {
-            let operator = OpOperator::ArithmeticAdd((operand_a, operand_b));
-            Ok((input, NLOperation::Operator(operator)))
-        }
-        "-" => {
-            let operator = OpOperator::ArithmeticSub((operand_a, operand_b));
-            Ok((input, NLOperation::Operator(operator)))
-        }
-        "%" => {
-            let operator = OpOperator::ArithmeticMod((operand_a, operand_b));
-            Ok((input, NLOperation::Operator(operator)))
-        }
-        "/" => {
-            let operator = OpOperator::ArithmeticDiv((operand_a, operand_b));
-            Ok((input, NLOperation::Operator(operator)))
-        }
-        "*" => {
-            let operator = OpOperator::ArithmeticMul((operand_a, operand_b));
-            Ok((input, NLOperation::Operator(operator)))
+        "+" => OpOperator::ArithmeticAdd((operand_a, operand_b)),
+        "-" => OpOperator::ArithmeticSub((operand_a, operand_b)),
+        "%" => OpOperator::ArithmeticMod((operand_a, operand_b)),
+        "/" => OpOperator::ArithmeticDiv((operand_a, operand_b)),
+        "*" => OpOperator::ArithmeticMul((operand_a, operand_b)),
+        ".." => OpOperator::Range((operand_a, operand_b)),
+
+        _ => panic!("Got an operator that take_binary_operator_symbol should never return."),
+    }
+}
+
+// Reads an operand followed by any number of binary operators and their operands. Each operand is only read once,
+// and the operators are grouped by their precedence as we go, so long chains don't cause any backtracking.
+fn read_expression(input: &str) -> ParserResult<NLOperation> {
+    count_parser(|stats| &mut stats.read_expression);
+
+    read_binary_operator(input, 0, false)
+}
+
+// Operators that are easy to write backwards, and what was most likely meant instead.
+fn check_mistaken_operator(input: &str, is_guard: bool) -> Result<(), NomErr<VerboseError<&str>>> {
+    let message = if input.starts_with("=<") {
+        "`=<` is not an operator. Did you mean `<=`?"
+    } else if input.starts_with("=>") && !is_guard {
+        "`=>` is not a comparison. Did you mean `>=`?"
+    } else {
+        return Ok(());
+    };
+
+    Err(verbose_failure(&input[..2], message))
+}
+
+// Reads operators until one binds looser than the minimum precedence, leaving it for the caller. The `=>` after a
+// match guard ends the guard, but anywhere else it's a mistake.
+fn read_binary_operator(
+    input: &str,
+    minimum_precedence: u8,
+    is_guard: bool,
+) -> ParserResult<NLOperation> {
+    let (input, _) = blank(input)?;
+    let (mut input, mut operand_a) = match read_cast_operation(input) {
+        Ok(operand) => operand,
+        // Something like a lone `+` would otherwise be reported as a name we failed to read.
+        Err(NomErr::Error(error)) => {
+            return match take_binary_operator_symbol(input) {
+                Ok((after_operator, _)) => Err(naming_error(
+                    &input[..input.len() - after_operator.len()],
+                    "The operator ",
+                    " is missing an operand.",
+                )),
+                Err(_) => Err(NomErr::Error(error)),
+            };
         }
-        ".." => {
-            let operator = OpOperator::Range((operand_a, operand_b));
-            Ok((input, NLOperation::Operator(operator)))
+        Err(error) => return Err(error),
+    };
+
+    loop {
+        let (after_blank, _) = blank(input)?;
+        check_mistaken_operator(after_blank, is_guard)?;
+
+        let (after_operator, operator) = match take_binary_operator_symbol(after_blank) {
+            Ok(operator) => operator,
+            Err(NomErr::Error(_)) => break,
+            Err(error) => return Err(error),
+        };
+
+        let precedence = binary_operator_precedence(operator);
+        if precedence < minimum_precedence {
+            break;
         }
 
-        _ => Err(verbose_error(input, "unknown operator")),
+        // Operators of the same precedence group to the left, so the right side only takes tighter ones.
+        let (after_operand, operand_b) =
+            read_binary_operator(after_operator, precedence + 1, is_guard)?;
+
+        let operator = build_binary_operator(operator, Box::new(operand_a), Box::new(operand_b));
+        operand_a = NLOperation::Operator(operator);
+        input = after_operand;
     }
+
+    Ok((input, operand_a))
+}
+
+// The `let Enum::Variant(a, b) =` of an `if let` or `while let`.
+fn read_let_pattern(input: &str) -> ParserResult<MatchEnumBranch> {
+    let (input, _) = blank(input)?;
+    let (input, _) = tag("let")(input)?;
+    let (input, _) = not(satisfy(is_method_char))(input)?;
+    let (input, pattern) = read_enum_pattern(input)?;
+    let (input, _) = blank(input)?;
+    let (input, _) = char('=')(input)?;
+
+    Ok((input, pattern))
 }
 
 fn read_if_statement(input: &str) -> ParserResult<NLOperation> {
     let (input, _) = blank(input)?;
     let (input, _) = tag("if")(input)?;
+    let (input, pattern) = opt(read_let_pattern)(input)?;
     let (input, _) = blank(input)?;
-    let (input, condition) = read_operation(input)?;
+    let (input, condition) = with_struct_literals(false, read_operation)(input)?;
     let (input, _) = blank(input)?;
     let (input, true_block) = read_code_block(input)?;
     let (input, _) = blank(input)?;
@@ -1064,9 +3717,9 @@ fn read_if_statement(input: &str) -> ParserResult<NLOperation> {
             _ => panic!("Got something other than a block when it should have been a block."),
         };
 
-        (input, block)
+        (input, Some(block))
     } else {
-        (input, NLBlock { operations: vec![] })
+        (input, None)
     };
 
     let true_block = match true_block {
@@ -1074,6 +3727,22 @@ fn read_if_statement(input: &str) -> ParserResult<NLOperation> {
         _ => panic!("Got something other than a block when it should have been a block."),
     };
 
+    if let Some(pattern) = pattern {
+        return Ok((
+            input,
+            NLOperation::IfLet(IfLet {
+                pattern,
+                value: Box::new(condition),
+                true_block,
+                // A missing else block is the same as an empty one here.
+                false_block: false_block.unwrap_or(NLBlock {
+                    operations: vec![],
+                    has_result: false,
+                }),
+            }),
+        ));
+    }
+
     Ok((
         input,
         NLOperation::If(IfStatement {
@@ -1096,11 +3765,23 @@ fn read_basic_loop(input: &str) -> ParserResult<NLOperation> {
 fn read_while_loop(input: &str) -> ParserResult<NLOperation> {
     let (input, _) = blank(input)?;
     let (input, _) = tag("while")(input)?;
+    let (input, pattern) = opt(read_let_pattern)(input)?;
     let (input, _) = blank(input)?;
-    let (input, condition) = read_operation(input)?;
+    let (input, condition) = with_struct_literals(false, read_operation)(input)?;
     let (input, _) = blank(input)?;
     let (input, block) = read_code_block_raw(input)?;
 
+    if let Some(pattern) = pattern {
+        return Ok((
+            input,
+            NLOperation::WhileLet(WhileLet {
+                pattern,
+                value: Box::new(condition),
+                block,
+            }),
+        ));
+    }
+
     Ok((
         input,
         NLOperation::WhileLoop(WhileLoop {
@@ -1118,7 +3799,7 @@ fn read_for_loop(input: &str) -> ParserResult<NLOperation> {
     let (input, _) = blank(input)?;
     let (input, _) = tag("in")(input)?;
     let (input, _) = blank(input)?;
-    let (input, iterator) = read_operation(input)?;
+    let (input, iterator) = with_struct_literals(false, read_operation)(input)?;
     let (input, _) = blank(input)?;
     let (input, block) = read_code_block_raw(input)?;
 
@@ -1132,14 +3813,60 @@ fn read_for_loop(input: &str) -> ParserResult<NLOperation> {
     ))
 }
 
+fn read_label(input: &str) -> ParserResult<&str> {
+    let (input, _) = blank(input)?;
+    let (input, _) = char('\'')(input)?;
+    take_while1(is_method_char)(input)
+}
+
+fn read_labeled_loop(input: &str) -> ParserResult<NLOperation> {
+    let (input, label) = read_label(input)?;
+    let (input, _) = blank(input)?;
+    let (input, _) = char(':')(input)?;
+
+    let (input, operation) = match alt((read_basic_loop, read_while_loop, read_for_loop))(input) {
+        Err(NomErr::Error(_)) => Err(verbose_failure(input, "Only loops can be labeled.")),
+        result => result,
+    }?;
+
+    Ok((
+        input,
+        NLOperation::Labeled {
+            label,
+            operation: Box::new(operation),
+        },
+    ))
+}
+
 fn read_break_keyword(input: &str) -> ParserResult<NLOperation> {
-    let (input, break_keyword) = opt(tag("break"))(input)?;
+    let (input, _) = blank(input)?;
+    let (input, _) = tag("break")(input)?;
 
-    if break_keyword.is_some() {
-        Ok((input, NLOperation::Break))
-    } else {
-        Err(verbose_error(input, "This is not a break operation."))
-    }
+    // Names like `breakfast` are variables, not a break followed by garbage.
+    let (input, _) = not(satisfy(is_method_char))(input)?;
+    let (input, label) = opt(read_label)(input)?;
+
+    // A loop can be broken out of with a value, which becomes the value of the loop.
+    let (input, value) = opt(read_operation)(input)?;
+
+    Ok((
+        input,
+        NLOperation::Break {
+            label,
+            value: value.map(Box::new),
+        },
+    ))
+}
+
+fn read_return_keyword(input: &str) -> ParserResult<NLOperation> {
+    let (input, _) = blank(input)?;
+    let (input, _) = tag("return")(input)?;
+
+    // Names like `return_value` are variables, not a return followed by garbage.
+    let (input, _) = not(satisfy(is_method_char))(input)?;
+    let (input, value) = opt(read_operation)(input)?;
+
+    Ok((input, NLOperation::Return(value.map(Box::new))))
 }
 
 fn read_variable_access_raw(input: &str) -> ParserResult<OpVariable> {
@@ -1150,23 +3877,25 @@ fn read_variable_access_raw(input: &str) -> ParserResult<OpVariable> {
 }
 
 fn read_variable_access(input: &str) -> ParserResult<NLOperation> {
+    count_parser(|stats| &mut stats.read_variable_access);
+
     let (input, variable) = read_variable_access_raw(input)?;
 
     Ok((input, NLOperation::VariableAccess(variable)))
 }
 
 fn read_function_call(input: &str) -> ParserResult<NLOperation> {
-    let (input, _) = blank(input)?;
-    let (input, path) = read_variable_name(input)?;
-    let (input, _) = blank(input)?;
-    let (input, arg_input) = delimited(char('('), take_while(|c| c != ')'), char(')'))(input)?;
+    count_parser(|stats| &mut stats.read_function_call);
 
-    let (arg_input, mut arguments) = many0(terminated(read_variable_name, char(',')))(arg_input)?;
+    // Otherwise `let (a, b) = ...` would look like a call to a function named `let`, and so on for every keyword.
+    let (input, _) = blank(input)?;
+    let (input, path) = verify(read_variable_name, |path: &str| !KEYWORDS.contains(&path))(input)?;
+    let (opener, _) = blank(input)?;
+    let (input, _) = char('(')(opener)?;
 
-    let (_, last_arg) = opt(read_variable_name)(arg_input)?;
-    if let Some(arg) = last_arg {
-        arguments.push(arg);
-    };
+    // Arguments can be any expression, such as `add(a + 1, 2)`.
+    let (input, arguments) = comma_separated0(read_operation)(input)?;
+    let (input, _) = read_closer(opener, ')')(input)?;
 
     Ok((
         input,
@@ -1174,106 +3903,222 @@ fn read_function_call(input: &str) -> ParserResult<NLOperation> {
     ))
 }
 
+// An enum variant and the names given to its data, such as `Shape::Circle(radius)`.
+fn read_enum_pattern(input: &str) -> ParserResult<MatchEnumBranch> {
+    let (input, _) = blank(input)?;
+    let (input, nl_enum) = read_variable_name(input)?;
+    let (input, _) = blank(input)?;
+    let (input, _) = tag("::")(input)?;
+    let (input, _) = blank(input)?;
+    let (input, variant) = read_variable_name(input)?;
+    let (input, _) = blank(input)?;
+
+    let (input, variables) = opt(delimited(
+        char('('),
+        comma_separated0(read_variable_name),
+        preceded(blank, char(')')),
+    ))(input)?;
+    let variables = variables.unwrap_or_default();
+
+    Ok((
+        input,
+        MatchEnumBranch {
+            nl_enum,
+            variant,
+            variables,
+        },
+    ))
+}
+
 fn read_match(input: &str) -> ParserResult<NLOperation> {
     let (input, _) = blank(input)?;
     let (input, _) = tag("match")(input)?;
     let (input, _) = blank(input)?;
-    let (input, input_operation) = read_operation(input)?;
+    let (input, input_operation) = with_struct_literals(false, read_operation)(input)?;
 
-    let (input, _) = blank(input)?;
-    let (input, _) = char('{')(input)?;
+    let (opener, _) = blank(input)?;
+    let (input, _) = char('{')(opener)?;
 
-    fn read_branch_body(input: &str) -> ParserResult<NLOperation> {
-        let (input, _) = blank(input)?;
-        let (input, _) = tag("=>")(input)?;
+    fn read_guard(input: &str) -> ParserResult<NLOperation> {
         let (input, _) = blank(input)?;
+        let (input, _) = tag("if")(input)?;
+        let (input, _) = not(satisfy(is_method_char))(input)?;
 
-        read_operation(input)
+        read_binary_operator(input, 0, true)
     }
 
-    fn read_enum_branch(input: &str) -> ParserResult<(MatchBranch, NLOperation)> {
-        let (input, _) = blank(input)?;
-        let (input, nl_enum) = read_variable_name(input)?;
-        let (input, _) = blank(input)?;
-        let (input, _) = tag("::")(input)?;
-        let (input, _) = blank(input)?;
-        let (input, variant) = read_variable_name(input)?;
+    // The name given to the matched value, such as the `n` in `n @ 5`.
+    fn read_binding(input: &str) -> ParserResult<&str> {
+        let (input, name) = read_variable_name(input)?;
         let (input, _) = blank(input)?;
+        let (input, _) = char('@')(input)?;
 
-        let (input, var_input) =
-            opt(delimited(char('('), take_while(|c| c != ')'), char(')')))(input)?;
+        Ok((input, name))
+    }
 
-        let variables = if let Some(var_input) = var_input {
-            let (var_input, mut variables) =
-                many0(terminated(read_variable_name, char(',')))(var_input)?;
-            let (_, last_arg) = opt(read_variable_name)(var_input)?;
-            if let Some(arg) = last_arg {
-                variables.push(arg);
-            };
+    // The guard comes between the pattern and the arrow.
+    fn read_branch_body(input: &str) -> ParserResult<(Option<NLOperation>, NLOperation)> {
+        let (input, guard) = opt(read_guard)(input)?;
 
-            variables
-        } else {
-            Vec::new()
-        };
+        let (input, _) = blank(input)?;
+        if let Ok((_, arrow)) = read_spaced_arrow('=')(input) {
+            return Err(verbose_failure(
+                arrow,
+                "Match branches use `=>`, without a space in the middle.",
+            ));
+        }
+        let (input, _) = tag("=>")(input)?;
+        let (input, _) = blank(input)?;
 
-        let (input, operation) = read_branch_body(input)?;
+        let (input, operation) = read_operation(input)?;
 
-        let match_branch = MatchBranch::Enum(MatchEnumBranch {
-            nl_enum,
-            variant,
-            variables,
-        });
+        Ok((input, (guard, operation)))
+    }
+
+    fn read_enum_branch(input: &str) -> ParserResult<MatchArm> {
+        let (input, pattern) = read_enum_pattern(input)?;
+        let (input, (guard, operation)) = read_branch_body(input)?;
 
-        Ok((input, (match_branch, operation)))
+        Ok((input, (MatchBranch::Enum(pattern), guard, operation)))
     }
 
-    fn read_constant_branch(input: &str) -> ParserResult<(MatchBranch, NLOperation)> {
+    fn read_constant_pattern(input: &str) -> ParserResult<MatchBranch> {
+        let (input, binding) = opt(read_binding)(input)?;
         let (input, _) = blank(input)?;
         let (input, constant) = read_constant_raw(input)?;
-        let (input, _) = blank(input)?;
-
-        let (input, operation) = read_branch_body(input)?;
 
-        Ok((input, (MatchBranch::Constant(constant), operation)))
+        Ok((input, MatchBranch::Constant((binding, constant))))
     }
 
-    fn read_range_branch(input: &str) -> ParserResult<(MatchBranch, NLOperation)> {
+    fn read_range_pattern(input: &str) -> ParserResult<MatchBranch> {
+        fn read_bound(input: &str) -> ParserResult<i128> {
+            let (after_bound, bound) = parse_integer(input)?;
+
+            // Underscores are only there to make the number easier to read.
+            let digits: String = bound.text.chars().filter(|c| *c != '_').collect();
+            let value = i128::from_str_radix(&digits, bound.radix).map_err(|_| {
+                naming_failure(
+                    &input[..input.len() - after_bound.len()],
+                    "Range bound ",
+                    " is too large.",
+                )
+            })?;
+
+            if bound.is_negative {
+                Ok((after_bound, -value))
+            } else {
+                Ok((after_bound, value))
+            }
+        }
+
+        fn not_literal<T>(input: &str) -> ParserResult<T> {
+            Err(verbose_failure(
+                input,
+                "Range bounds in match branches must be integer literals.",
+            ))
+        }
+
+        let (input, binding) = opt(read_binding)(input)?;
+
         let (input, _) = blank(input)?;
-        let (input, lower) = digit1(input)?;
-        let (_, lower) = parse_integer(lower)?;
+        let (input, lower) = match read_bound(input) {
+            Ok(bound) => bound,
+            Err(NomErr::Error(error)) => {
+                // Names can contain dots, so something like `a..b` gets read as one name.
+                match read_variable_name(input) {
+                    Ok((_, name)) if name.contains("..") => return not_literal(input),
+                    _ => return Err(NomErr::Error(error)),
+                }
+            }
+            Err(error) => return Err(error),
+        };
 
         let (input, _) = blank(input)?;
         let (input, _) = tag("..")(input)?;
 
+        // Now that we've seen the `..`, this can only be a range.
         let (input, _) = blank(input)?;
-        let (input, higher) = digit1(input)?;
-        let (_, higher) = parse_integer(higher)?;
+        let (input, higher) = match read_bound(input) {
+            Ok(bound) => bound,
+            Err(NomErr::Error(_)) => return not_literal(input),
+            Err(error) => return Err(error),
+        };
+
+        Ok((input, MatchBranch::Range((binding, lower, higher))))
+    }
 
+    fn read_wildcard_pattern(input: &str) -> ParserResult<MatchBranch> {
         let (input, _) = blank(input)?;
-        let (input, operation) = read_branch_body(input)?;
+        let (input, _) = char('_')(input)?;
+        let (input, _) = not(satisfy(is_method_char))(input)?;
 
-        // TODO make work with the new implementation.
-        unimplemented!()
-        // Ok((input, (MatchBranch::Range((lower, higher)), operation)))
+        Ok((input, MatchBranch::AllOther))
     }
 
-    fn read_branch(input: &str) -> ParserResult<(MatchBranch, NLOperation)> {
-        alt((read_range_branch, read_constant_branch, read_enum_branch))(input)
+    fn read_binding_pattern(input: &str) -> ParserResult<MatchBranch> {
+        let (input, name) = read_variable_name(input)?;
+
+        Ok((input, MatchBranch::Binding(name)))
     }
 
-    let (input, _) = blank(input)?;
-    let (input, mut branches) = many0(terminated(read_branch, char(',')))(input)?;
+    // Tuple patterns can be nested, so unlike tuple operations we can't just look for the next `)`.
+    fn read_tuple_pattern(input: &str) -> ParserResult<MatchBranch> {
+        fn read_element(input: &str) -> ParserResult<MatchBranch> {
+            alt((
+                read_tuple_pattern,
+                read_range_pattern,
+                read_constant_pattern,
+                read_wildcard_pattern,
+                read_binding_pattern,
+            ))(input)
+        }
 
-    let (input, _) = blank(input)?;
-    let (input, last_branch) = opt(read_branch)(input)?;
+        let (input, _) = blank(input)?;
+        let (input, _) = char('(')(input)?;
+        let (input, elements) = comma_separated0(read_element)(input)?;
+        let (input, _) = blank(input)?;
+        let (input, _) = char(')')(input)?;
 
-    if let Some(arg) = last_branch {
-        branches.push(arg);
+        Ok((input, MatchBranch::Tuple(elements)))
     }
 
-    let (input, _) = blank(input)?;
+    fn read_pattern_branch(input: &str) -> ParserResult<MatchArm> {
+        let (input, pattern) = alt((
+            read_range_pattern,
+            read_constant_pattern,
+            read_tuple_pattern,
+            read_wildcard_pattern,
+        ))(input)?;
+        let (input, _) = blank(input)?;
 
-    let (input, _) = char('}')(input)?;
+        let (input, (guard, operation)) = read_branch_body(input)?;
+
+        Ok((input, (pattern, guard, operation)))
+    }
+
+    fn read_branch(input: &str) -> ParserResult<MatchArm> {
+        alt((read_pattern_branch, read_enum_branch))(input)
+    }
+
+    // A branch with a block for its body doesn't need a comma after it, but any other branch does unless it's last.
+    fn read_separated_branch(input: &str) -> ParserResult<MatchArm> {
+        let (input, branch) = read_branch(input)?;
+        let (input, _) = blank(input)?;
+        let (input, comma) = opt(char(','))(input)?;
+
+        let is_block = matches!(branch.2, NLOperation::Block(_));
+        if comma.is_none() && !is_block && char::<_, VerboseError<&str>>('}')(input).is_err() {
+            return Err(verbose_failure(
+                input,
+                "Expected a `,` between match branches.",
+            ));
+        }
+
+        Ok((input, branch))
+    }
+
+    let (input, branches) = many0(read_separated_branch)(input)?;
+    let (input, _) = read_closer(opener, '}')(input)?;
 
     Ok((
         input,
@@ -1285,15 +4130,28 @@ fn read_match(input: &str) -> ParserResult<NLOperation> {
 }
 
 fn read_code_block_raw(input: &str) -> ParserResult<NLBlock> {
-    let (input, _) = blank(input)?;
-    let (input, _) = char('{')(input)?;
+    let (opener, _) = blank(input)?;
+    let (input, _) = char('{')(opener)?;
 
-    let (input, operations) = many0(read_operation)(input)?;
+    let (input, operations) =
+        many0(tuple((read_operation, opt(preceded(blank, char(';'))))))(input)?;
 
-    let (input, _) = blank(input)?;
-    let (input, _) = char('}')(input)?;
+    // Only the last operation can be the value of the block, and only if it isn't ended with a semicolon.
+    let has_result = matches!(operations.last(), Some((_, None)));
+    let operations = operations
+        .into_iter()
+        .map(|(operation, _)| operation)
+        .collect();
+
+    let (input, _) = read_closer(opener, '}')(input)?;
 
-    Ok((input, NLBlock { operations }))
+    Ok((
+        input,
+        NLBlock {
+            operations,
+            has_result,
+        },
+    ))
 }
 
 fn read_code_block(input: &str) -> ParserResult<NLOperation> {
@@ -1303,10 +4161,14 @@ fn read_code_block(input: &str) -> ParserResult<NLOperation> {
 }
 
 fn read_sub_operation(input: &str) -> ParserResult<NLOperation> {
+    count_parser(|stats| &mut stats.read_sub_operation);
+
     alt((
-        read_code_block,
-        read_tuple,
-        read_function_call,
+        with_struct_literals(true, read_code_block),
+        with_struct_literals(true, read_tuple),
+        with_struct_literals(true, read_array_literal),
+        with_struct_literals(true, read_function_call),
+        read_struct_literal,
         read_assignment,
         read_constant,
         read_urinary_operator,
@@ -1314,23 +4176,40 @@ fn read_sub_operation(input: &str) -> ParserResult<NLOperation> {
     ))(input)
 }
 
+// Casts bind tighter than any binary operator, so `a + b as i64` only casts `b`. They can be chained, such as
+// `a as i32 as i64`.
+fn read_cast_operation(input: &str) -> ParserResult<NLOperation> {
+    let (input, operation) = read_sub_operation(input)?;
+    let (input, targets) = many0(read_cast)(input)?;
+
+    let operation = targets
+        .into_iter()
+        .fold(operation, |value, target| NLOperation::Cast {
+            value: Box::new(value),
+            target,
+        });
+
+    Ok((input, operation))
+}
+
 fn read_operation(input: &str) -> ParserResult<NLOperation> {
-    alt((
-        read_code_block,
-        read_if_statement,
-        read_match,
-        read_break_keyword,
-        read_basic_loop,
-        read_while_loop,
-        read_for_loop,
-        read_tuple,
-        read_function_call,
-        read_assignment,
-        read_binary_operator,
-        read_constant,
-        read_urinary_operator,
-        read_variable_access,
-    ))(input)
+    count_parser(|stats| &mut stats.read_operation);
+
+    nested(
+        input,
+        alt((
+            read_code_block,
+            read_if_statement,
+            read_match,
+            read_break_keyword,
+            read_return_keyword,
+            read_labeled_loop,
+            read_basic_loop,
+            read_while_loop,
+            read_for_loop,
+            read_expression,
+        )),
+    )
 }
 
 fn read_argument_declaration(input: &str) -> ParserResult<NLArgument> {
@@ -1344,8 +4223,14 @@ fn read_argument_declaration(input: &str) -> ParserResult<NLArgument> {
             let (input, _) = blank(input)?;
             let (input, nl_type) = read_variable_type(input)?;
             let (input, _) = blank(input)?;
+            let (input, default) = opt(preceded(char('='), read_operation))(input)?;
+            let (input, _) = blank(input)?;
 
-            let arg = NLArgument { name, nl_type };
+            let arg = NLArgument {
+                name,
+                nl_type,
+                default,
+            };
 
             Ok((input, arg))
         }
@@ -1362,6 +4247,7 @@ fn read_argument_declaration(input: &str) -> ParserResult<NLArgument> {
                     let arg = NLArgument {
                         name: "self",
                         nl_type: NLType::SelfReference,
+                        default: None,
                     };
 
                     return Ok((input, arg));
@@ -1375,6 +4261,7 @@ fn read_argument_declaration(input: &str) -> ParserResult<NLArgument> {
                     let arg = NLArgument {
                         name: "self",
                         nl_type: NLType::MutableSelfReference,
+                        default: None,
                     };
 
                     return Ok((input, arg));
@@ -1394,24 +4281,53 @@ fn read_argument_declaration(input: &str) -> ParserResult<NLArgument> {
 }
 
 fn read_argument_deceleration_list(input: &str) -> ParserResult<Vec<NLArgument>> {
-    let (input, arg_input) = delimited(char('('), take_while(|c| c != ')'), char(')'))(input)?;
+    // Argument types can have parentheses of their own, so we read straight through to the closing one.
+    let (input, _) = char('(')(input)?;
+    let (input, arguments) = comma_separated0(read_argument_declaration)(input)?;
+    let (input, _) = blank(input)?;
+    let (input, _) = char(')')(input)?;
+
+    Ok((input, arguments))
+}
 
-    let (arg_input, mut arguments) =
-        many0(terminated(read_argument_declaration, char(',')))(arg_input)?;
+// Such as `<T: Drawable + Debug, U>`. A parameter without a `:` isn't bound to any traits.
+fn read_type_params(input: &str) -> ParserResult<Vec<TypeParam>> {
+    fn read_type_param(input: &str) -> ParserResult<TypeParam> {
+        let (input, name) = read_struct_or_trait_name(input)?;
+        let (input, bounds) = opt(preceded(char(':'), read_bounds))(input)?;
 
-    let (_, last_arg) = opt(terminated(read_argument_declaration, blank))(arg_input)?;
-    match last_arg {
-        Some(arg) => {
-            arguments.push(arg);
-        }
-        _ => {} // Do nothing if there was no argument.
+        Ok((input, (name, bounds.unwrap_or_default())))
     }
 
-    Ok((input, arguments))
+    fn read_bounds(input: &str) -> ParserResult<Vec<&str>> {
+        let (input, first) = read_struct_or_trait_name(input)?;
+        let (input, mut bounds) = many0(preceded(char('+'), read_struct_or_trait_name))(input)?;
+        bounds.insert(0, first);
+
+        Ok((input, bounds))
+    }
+
+    let (input, _) = char('<')(input)?;
+    let (input, params) = comma_separated0(read_type_param)(input)?;
+    let (input, _) = blank(input)?;
+    let (input, _) = char('>')(input)?;
+
+    Ok((input, params))
+}
+
+// An arrow with a space in the middle, such as `- >`. Those are never valid, so they're only read to report them.
+fn read_spaced_arrow(first: char) -> impl Fn(&str) -> ParserResult<&str> {
+    move |input| recognize(tuple((char(first), multispace1, char('>'))))(input)
 }
 
 fn read_return_type(input: &str) -> ParserResult<NLType> {
     let (input, _) = blank(input)?;
+    if let Ok((_, arrow)) = read_spaced_arrow('-')(input) {
+        return Err(verbose_failure(
+            arrow,
+            "Return types are given with `->`, without a space in the middle.",
+        ));
+    }
     let (input, tagged) = opt(tag("->"))(input)?;
 
     if tagged.is_some() {
@@ -1425,11 +4341,27 @@ fn read_return_type(input: &str) -> ParserResult<NLType> {
     }
 }
 
-fn read_method(input: &str) -> ParserResult<NLImplementor> {
+// The `internal` or `external` in front of a deceleration.
+fn read_access_rule(input: &str) -> ParserResult<NLAccessRule> {
     let (input, _) = blank(input)?;
+    let (input, access) = alt((
+        value(NLAccessRule::Internal, tag("internal")),
+        value(NLAccessRule::External, tag("external")),
+    ))(input)?;
+    let (input, _) = not(satisfy(is_method_char))(input)?;
+    let (input, _) = blank(input)?;
+
+    Ok((input, access))
+}
+
+fn read_method(input: &str) -> ParserResult<NLImplementor> {
+    let (input, (doc, attributes)) = read_item_prefix(input)?;
+    let (input, access) = opt(read_access_rule)(input)?;
     let (input, _) = tag("met")(input)?;
     let (input, _) = blank(input)?;
     let (input, name) = read_method_name(input)?;
+    let (input, type_params) = opt(read_type_params)(input)?;
+    let type_params = type_params.unwrap_or_default();
     let (input, _) = blank(input)?;
     let (input, args) = read_argument_deceleration_list(input)?;
     let (input, _) = blank(input)?;
@@ -1446,9 +4378,13 @@ fn read_method(input: &str) -> ParserResult<NLImplementor> {
 
     let method = NLFunction {
         name,
+        access,
+        type_params,
         arguments: args,
         return_type,
         block,
+        doc,
+        attributes,
     };
 
     // No block, we expect a semicolon.
@@ -1461,11 +4397,14 @@ fn read_method(input: &str) -> ParserResult<NLImplementor> {
     }
 }
 
-fn read_function(input: &str) -> ParserResult<RootDeceleration> {
-    let (input, _) = blank(input)?;
+fn read_function(input: &str) -> ParserResult<RootItem> {
+    let (input, (doc, attributes)) = read_item_prefix(input)?;
+    let (input, access) = opt(read_access_rule)(input)?;
     let (input, _) = tag("fn")(input)?;
     let (input, _) = blank(input)?;
     let (input, name) = read_method_name(input)?;
+    let (input, type_params) = opt(read_type_params)(input)?;
+    let type_params = type_params.unwrap_or_default();
     let (input, _) = blank(input)?;
     let (input, args) = read_argument_deceleration_list(input)?;
     let (input, _) = blank(input)?;
@@ -1482,62 +4421,151 @@ fn read_function(input: &str) -> ParserResult<RootDeceleration> {
 
     let function = NLFunction {
         name,
+        access: Some(access.unwrap_or_default()),
+        type_params,
         arguments: args,
         return_type,
         block,
+        doc,
+        attributes,
     };
 
     // No block, we expect a semicolon.
     if function.block.is_none() {
         let (input, _) = char(';')(input)?;
 
-        Ok((input, RootDeceleration::Function(function)))
+        Ok((input, RootItem::Function(function)))
     } else {
-        Ok((input, RootDeceleration::Function(function)))
+        Ok((input, RootItem::Function(function)))
     }
 }
 
-fn read_variant_enum(input: &str) -> ParserResult<RootDeceleration> {
-    let (input, _) = blank(input)?;
+fn read_variant_enum(input: &str) -> ParserResult<RootItem> {
+    let (input, (doc, attributes)) = read_item_prefix(input)?;
+    let (input, access) = opt(read_access_rule)(input)?;
+    let access = access.unwrap_or_default();
     let (input, _) = tag("enum")(input)?;
     let (input, _) = blank(input)?;
     let (input, name) = read_method_name(input)?;
 
-    let (input, _) = blank(input)?;
-    let (input, _) = char('{')(input)?;
+    let (opener, _) = blank(input)?;
+    let (input, _) = char('{')(opener)?;
 
-    fn read_variant(input: &str) -> ParserResult<EnumVariant> {
+    // Tuple fields can be named, such as `One(a: A, B)`, but struct fields always are.
+    fn read_field<'a>(input: &'a str, name: Option<&'a str>) -> ParserResult<'a, VariantField<'a>> {
+        let (input, _) = blank(input)?;
+        let (input, nl_type) = read_variable_type(input)?;
+        let (input, _) = blank(input)?;
+
+        Ok((input, VariantField { name, nl_type }))
+    }
+
+    fn read_tuple_field(input: &str) -> ParserResult<VariantField> {
+        let (input, _) = blank(input)?;
+        let (input, name) = opt(terminated(read_variable_name, preceded(blank, char(':'))))(input)?;
+        read_field(input, name)
+    }
+
+    fn read_struct_field(input: &str) -> ParserResult<VariantField> {
         let (input, _) = blank(input)?;
         let (input, name) = read_variable_name(input)?;
         let (input, _) = blank(input)?;
+        let (input, _) = char(':')(input)?;
+        read_field(input, Some(name))
+    }
 
-        let (input, args) = opt(read_argument_deceleration_list)(input)?;
+    fn read_tuple_fields(input: &str) -> ParserResult<(VariantKind, Vec<VariantField>)> {
+        let (opener, _) = blank(input)?;
+        let (input, _) = char('(')(opener)?;
+        let (input, fields) = comma_separated0(read_tuple_field)(input)?;
+        let (input, _) = read_closer(opener, ')')(input)?;
 
-        let arguments = if let Some(args) = args {
-            args
-        } else {
-            Vec::new()
-        };
+        Ok((input, (VariantKind::Tuple, fields)))
+    }
+
+    fn read_struct_fields(input: &str) -> ParserResult<(VariantKind, Vec<VariantField>)> {
+        let (opener, _) = blank(input)?;
+        let (input, _) = char('{')(opener)?;
+        let (input, fields) = comma_separated0(read_struct_field)(input)?;
+        let (input, _) = read_closer(opener, '}')(input)?;
+
+        Ok((input, (VariantKind::Struct, fields)))
+    }
+
+    fn read_variant(input: &str) -> ParserResult<EnumVariant> {
+        let (input, attributes) = read_attributes(input)?;
+        let (input, variant_access) = opt(read_access_rule)(input)?;
+        let (input, name) = read_variable_name(input)?;
+        let (input, _) = blank(input)?;
+
+        let (input, fields) = opt(alt((read_tuple_fields, read_struct_fields)))(input)?;
+        let (kind, arguments) = fields.unwrap_or((VariantKind::Unit, Vec::new()));
+
+        // C style enums can give their variants an explicit value.
+        let (input, _) = blank(input)?;
+        let (input, discriminant) = opt(preceded(char('='), read_operation))(input)?;
+
+        Ok((
+            input,
+            EnumVariant {
+                name,
+                access: variant_access,
+                kind,
+                arguments,
+                discriminant,
+                attributes,
+            },
+        ))
+    }
+
+    let (input, mut variants) = comma_separated0(read_variant)(input)?;
+    let (input, _) = read_closer(opener, '}')(input)?;
+
+    for variant in variants.iter_mut() {
+        variant.access.get_or_insert(access);
+    }
+
+    let has_discriminant = variants
+        .iter()
+        .any(|variant| variant.discriminant.is_some());
 
-        Ok((input, EnumVariant { name, arguments }))
+    if has_discriminant {
+        // Point at the variant that carries data, since that's the one that needs to change.
+        if let Some(variant) = variants
+            .iter()
+            .find(|variant| !variant.arguments.is_empty())
+        {
+            return Err(verbose_error(
+                variant.name,
+                "Enums with explicit discriminants cannot have variants that carry data.",
+            ));
+        }
     }
 
-    let (input, _) = blank(input)?;
-    let (input, mut variants) = many0(terminated(read_variant, char(',')))(input)?;
-    let (input, _) = blank(input)?;
-    let (input, last_variant) = opt(read_variant)(input)?;
-    if let Some(arg) = last_variant {
-        variants.push(arg);
+    for variant in variants.iter() {
+        if variant.discriminant.is_some() && variant.get_discriminant().is_none() {
+            return Err(verbose_error(
+                variant.name,
+                "Enum discriminants must be integer constants that fit in an i64 and don't overflow their type.",
+            ));
+        }
     }
 
-    let (input, _) = blank(input)?;
-    let (input, _) = char('}')(input)?;
-
-    Ok((input, RootDeceleration::Enum(NLEnum { name, variants })))
+    Ok((
+        input,
+        RootItem::Enum(NLEnum {
+            name,
+            access,
+            variants,
+            doc,
+            attributes,
+        }),
+    ))
 }
 
 fn read_getter(input: &str) -> ParserResult<NLImplementor> {
     let (input, _) = blank(input)?;
+    let (input, access) = opt(read_access_rule)(input)?;
     let (input, _) = tag("get")(input)?;
     let (input, name) = read_method_name(input)?;
     let (input, _) = blank(input)?;
@@ -1548,7 +4576,8 @@ fn read_getter(input: &str) -> ParserResult<NLImplementor> {
         let (input, _) = char(';')(input)?;
 
         let getter = NLGetter {
-            name: String::from(name),
+            name,
+            access,
             args: vec![],
             nl_type,
             block: NLEncapsulationBlock::Default,
@@ -1571,7 +4600,8 @@ fn read_getter(input: &str) -> ParserResult<NLImplementor> {
         match block {
             Some(block) => {
                 let getter = NLGetter {
-                    name: String::from(name),
+                    name,
+                    access,
                     args,
                     nl_type,
                     block: NLEncapsulationBlock::Some(block),
@@ -1583,7 +4613,8 @@ fn read_getter(input: &str) -> ParserResult<NLImplementor> {
                 let (input, _) = char(';')(input)?;
 
                 let getter = NLGetter {
-                    name: String::from(name),
+                    name,
+                    access,
                     args,
                     nl_type,
                     block: NLEncapsulationBlock::None,
@@ -1597,6 +4628,7 @@ fn read_getter(input: &str) -> ParserResult<NLImplementor> {
 
 fn read_setter(input: &str) -> ParserResult<NLImplementor> {
     let (input, _) = blank(input)?;
+    let (input, access) = opt(read_access_rule)(input)?;
     let (input, _) = tag("set")(input)?;
     let (input, name) = read_method_name(input)?;
     let (input, _) = blank(input)?;
@@ -1606,6 +4638,7 @@ fn read_setter(input: &str) -> ParserResult<NLImplementor> {
     if is_default.is_some() {
         let setter = NLSetter {
             name,
+            access,
             args: vec![],
             block: NLEncapsulationBlock::Default,
         };
@@ -1627,6 +4660,7 @@ fn read_setter(input: &str) -> ParserResult<NLImplementor> {
             Some(block) => {
                 let setter = NLSetter {
                     name,
+                    access,
                     args,
                     block: NLEncapsulationBlock::Some(block),
                 };
@@ -1638,6 +4672,7 @@ fn read_setter(input: &str) -> ParserResult<NLImplementor> {
 
                 let setter = NLSetter {
                     name,
+                    access,
                     args,
                     block: NLEncapsulationBlock::None,
                 };
@@ -1649,24 +4684,31 @@ fn read_setter(input: &str) -> ParserResult<NLImplementor> {
 }
 
 // TODO make it so you can specify required traits.
-fn read_trait(input: &str) -> ParserResult<RootDeceleration> {
-    let (input, _) = blank(input)?;
+fn read_trait(input: &str) -> ParserResult<RootItem> {
+    let (input, doc) = read_doc_comments(input)?;
+    let (input, access) = opt(read_access_rule)(input)?;
+    let access = access.unwrap_or_default();
     let (input, _) = tag("trait")(input)?;
     let (input, _) = blank(input)?;
     let (input, name) = read_struct_or_trait_name(input)?;
 
-    let (input, _) = blank(input)?;
-    let (input, _) = char('{')(input)?;
-    let (input, _) = blank(input)?;
+    let (opener, _) = blank(input)?;
+    let (input, _) = char('{')(opener)?;
+    let (input, _) = blank_before_docs(input)?;
 
-    let (input, implementors) = many0(alt((read_method, read_getter, read_setter)))(input)?;
+    let (input, mut implementors) = many0(alt((read_method, read_getter, read_setter)))(input)?;
+    inherit_access(&mut implementors, access);
 
-    let (input, _) = blank(input)?;
-    let (input, _) = char('}')(input)?;
+    let (input, _) = read_closer(opener, '}')(input)?;
 
-    let new_trait = NLTrait { name, implementors };
+    let new_trait = NLTrait {
+        name,
+        access,
+        implementors,
+        doc,
+    };
 
-    Ok((input, RootDeceleration::Trait(new_trait)))
+    Ok((input, RootItem::Trait(new_trait)))
 }
 
 fn read_variable_name(input: &str) -> ParserResult<&str> {
@@ -1697,6 +4739,14 @@ fn identify_struct_or_trait_type(input: &str) -> ParserResult<NLType> {
 
     let (input, name) = read_struct_or_trait_name(input)?;
 
+    // There is no such thing as a reference to an owned string. Borrowing a string is what `str` is for.
+    if is_reference && name == "String" {
+        return Err(verbose_failure(
+            name,
+            "Cannot reference a String. Use str to borrow a string instead.",
+        ));
+    }
+
     if is_struct {
         // Its a struct.
         if is_reference {
@@ -1745,14 +4795,72 @@ fn read_variable_type_primitive_no_whitespace(input: &str) -> ParserResult<NLTyp
     }
 }
 
+fn read_array_type(input: &str) -> ParserResult<NLType> {
+    let (input, _) = char('[')(input)?;
+    let (input, element_type) = read_variable_type(input)?;
+    let (input, _) = blank(input)?;
+    let (input, _) = char(';')(input)?;
+    let (input, _) = blank(input)?;
+
+    let (input, length) = alt((
+        map_res(parse_integer, |length: ParsedInteger| {
+            // Underscores are only there to make the number easier to read. Keeping the sign makes negative lengths
+            // fail to parse.
+            let mut digits = String::new();
+            if length.is_negative {
+                digits.push('-');
+            }
+            digits.extend(length.text.chars().filter(|c| *c != '_'));
+            usize::from_str_radix(&digits, length.radix).map(ArrayLen::Literal)
+        }),
+        map(read_variable_name, ArrayLen::Named),
+    ))(input)?;
+
+    let (input, _) = blank(input)?;
+    let (input, _) = char(']')(input)?;
+
+    Ok((input, NLType::Array(Box::new(element_type), length)))
+}
+
+// Tuples can be nested, like `(i32, (bool, u8))`. An empty pair of parentheses is a tuple with no elements.
+fn read_tuple_type(input: &str) -> ParserResult<NLType> {
+    let (input, _) = char('(')(input)?;
+    let (input, elements) = comma_separated0(read_variable_type)(input)?;
+    let (input, _) = blank(input)?;
+    let (input, _) = char(')')(input)?;
+
+    Ok((input, NLType::Tuple(elements)))
+}
+
+// A function that can be passed around, such as `fn(i32) -> bool`. Leaving out the return type is the same as `-> ()`
+// for functions, so it returns None.
+fn read_function_type(input: &str) -> ParserResult<NLType> {
+    let (input, _) = tag("fn")(input)?;
+    let (opener, _) = blank(input)?;
+    let (input, _) = char('(')(opener)?;
+    let (input, arguments) = comma_separated0(read_variable_type)(input)?;
+    let (input, _) = read_closer(opener, ')')(input)?;
+    let (input, return_type) = read_return_type(input)?;
+
+    Ok((input, NLType::Function(arguments, Box::new(return_type))))
+}
+
 fn read_variable_type_no_whitespace(input: &str) -> ParserResult<NLType> {
     fn read_advanced_types(input: &str) -> ParserResult<NLType> {
         // Could it be a referenced string?
         let (input, _) = blank(input)?;
         let (input, is_referenced_string) = opt(preceded(blank, tag("str")))(input)?;
         let is_referenced_string = is_referenced_string.is_some();
+
+        // Or an owned one?
+        let (input, is_owned_string) =
+            opt(terminated(tag("String"), not(satisfy(is_method_char))))(input)?;
+        let is_owned_string = is_owned_string.is_some();
+
         if is_referenced_string {
             return Ok((input, NLType::BorrowedString));
+        } else if is_owned_string {
+            return Ok((input, NLType::OwnedString));
         } else {
             // Okay so we ether have Struct or Trait. Could even be a reference.
             return identify_struct_or_trait_type(input);
@@ -1760,6 +4868,9 @@ fn read_variable_type_no_whitespace(input: &str) -> ParserResult<NLType> {
     }
 
     alt((
+        read_array_type,
+        read_tuple_type,
+        read_function_type,
         read_variable_type_primitive_no_whitespace,
         read_advanced_types,
     ))(input)
@@ -1771,7 +4882,7 @@ fn read_variable_type(input: &str) -> ParserResult<NLType> {
 }
 
 fn read_struct_variable(input: &str) -> ParserResult<NLStructVariable> {
-    let (input, _) = blank(input)?;
+    let (input, attributes) = read_attributes(input)?;
     let (input, name) = read_variable_name(input)?;
 
     let (input, _) = blank(input)?;
@@ -1779,9 +4890,15 @@ fn read_struct_variable(input: &str) -> ParserResult<NLStructVariable> {
     let (input, _) = blank(input)?;
     let (input, nl_type) = read_variable_type(input)?;
 
+    // Is there a default value?
+    let (input, _) = blank(input)?;
+    let (input, default) = opt(preceded(char('='), read_operation))(input)?;
+
     let var = NLStructVariable {
         name,
         my_type: nl_type,
+        default,
+        attributes,
     };
 
     Ok((input, var))
@@ -1791,87 +4908,175 @@ fn read_implementation(input: &str) -> ParserResult<NLImplementation> {
     let (input, _) = blank(input)?;
     let (input, _) = tag("impl")(input)?;
     let (input, name) = read_struct_or_trait_name(input)?;
+
+    // With `impl Drawable for Circle`, the first name is the trait.
+    let (input, target) = opt(preceded(
+        terminated(tag("for"), not(satisfy(is_method_char))),
+        read_struct_or_trait_name,
+    ))(input)?;
+    let (name, trait_name) = match target {
+        Some(target) => (target, Some(name)),
+        None => (name, None),
+    };
+
+    let opener = input;
     let (input, _) = char('{')(input)?;
-    let (input, _) = blank(input)?;
+    let (input, _) = blank_before_docs(input)?;
     let (input, methods) = many0(alt((read_method, read_getter, read_setter)))(input)?;
-    let (input, _) = blank(input)?;
-    let (input, _) = char('}')(input)?;
+    let (input, _) = read_closer(opener, '}')(input)?;
 
     let implementation = NLImplementation {
         name,
+        trait_name,
         implementors: methods,
     };
 
     Ok((input, implementation))
 }
 
-fn read_struct(input: &str) -> ParserResult<RootDeceleration> {
-    let (input, _) = blank(input)?;
+fn read_struct(input: &str) -> ParserResult<RootItem> {
+    let (input, (doc, attributes)) = read_item_prefix(input)?;
+    let (input, access) = opt(read_access_rule)(input)?;
+    let access = access.unwrap_or_default();
     let (input, _) = tag("struct")(input)?;
     let (input, _) = blank(input)?;
     let (input, name) = read_struct_or_trait_name(input)?;
-    let (input, _) = blank(input)?;
-    let (input, _) = char('{')(input)?;
-    let (input, _) = blank(input)?;
-    let (input, mut variables) =
-        many0(terminated(read_struct_variable, tuple((blank, char(',')))))(input)?;
-    let (input, _) = blank(input)?;
-
-    // Need to read the last struct.
-    let (input, last_var) = opt(read_struct_variable)(input)?;
-    match last_var {
-        Some(var) => {
-            variables.push(var);
-        }
-        _ => {} // Do nothing if we didn't have a last one.
+    let (opener, _) = blank(input)?;
+    let (input, _) = char('{')(opener)?;
+    let (input, variables) = comma_separated0(read_struct_variable)(input)?;
+    let (input, _) = read_closer(opener, '}')(input)?;
+    // Something like `impl Drawable for Square` is for another struct, so it's left to be read as a detached
+    // implementation, which goes to the struct it names.
+    let (input, mut implementations) = many0(verify(
+        read_implementation,
+        |implementation: &NLImplementation| match implementation.trait_name {
+            Some(_) => implementation.name == "Self" || implementation.name == name,
+            None => true,
+        },
+    ))(input)?;
+    for implementation in implementations.iter_mut() {
+        inherit_access(&mut implementation.implementors, access);
     }
 
-    let (input, _) = blank(input)?;
-    let (input, _) = char('}')(input)?;
-    let (input, implementations) = many0(read_implementation)(input)?;
-
     let nl_struct = NLStruct {
         name,
+        access,
         variables,
         implementations,
+        doc,
+        attributes,
     };
 
-    Ok((input, RootDeceleration::Struct(nl_struct)))
+    Ok((input, RootItem::Struct(nl_struct)))
 }
 
-fn parse_file_root(input: &str) -> ParserResult<NLFile> {
-    let mut file = NLFile {
-        name: String::new(),
+// An implementation that isn't right after its struct, such as `impl Foo { ... }` or `impl Drawable for Foo { ... }`.
+fn read_detached_implementation(input: &str) -> ParserResult<RootItem> {
+    let (input, implementation) = read_implementation(input)?;
+
+    Ok((input, RootItem::Implementation(implementation)))
+}
+
+fn read_root_deceleration(input: &str) -> ParserResult<RootItem> {
+    alt((
+        read_struct,
+        read_trait,
+        read_function,
+        read_detached_implementation,
+        // When nothing matches, the error from the last parser is the one reported. Enums have the most detailed
+        // errors, so they go last.
+        read_variant_enum,
+    ))(input)
+}
+
+// Moves each detached implementation into the struct it names. Fails on the first one that names a struct the file
+// doesn't have.
+fn link_implementations<'a>(
+    structs: &mut [&mut NLStruct<'a>],
+    implementations: Vec<NLImplementation<'a>>,
+) -> Result<(), NomErr<VerboseError<&'a str>>> {
+    for mut implementation in implementations {
+        let nl_struct = structs
+            .iter_mut()
+            .find(|nl_struct| nl_struct.name == implementation.name);
+
+        match nl_struct {
+            Some(nl_struct) => {
+                inherit_access(&mut implementation.implementors, nl_struct.access);
+                nl_struct.implementations.push(implementation)
+            }
+            None => {
+                return Err(naming_failure(
+                    implementation.name,
+                    "Implementation for ",
+                    ", which is not a struct in this file.",
+                ))
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn link_file_implementations<'a>(
+    file: &mut NLFile<'a>,
+) -> Result<(), NomErr<VerboseError<&'a str>>> {
+    let implementations = std::mem::take(&mut file.impls);
+    let mut structs: Vec<&mut NLStruct<'a>> = file.structs.iter_mut().collect();
+
+    link_implementations(&mut structs, implementations)
+}
+
+fn new_file<'a>(name: &str) -> NLFile<'a> {
+    NLFile {
+        name: name.to_string(),
         structs: vec![],
         traits: vec![],
         functions: vec![],
         enums: vec![],
-    };
+        impls: vec![],
+    }
+}
+
+fn add_root_deceleration<'a>(file: &mut NLFile<'a>, root_def: RootItem<'a>) {
+    match root_def {
+        RootItem::Struct(nl_struct) => {
+            file.structs.push(nl_struct);
+        }
+        RootItem::Trait(nl_trait) => {
+            file.traits.push(nl_trait);
+        }
+        RootItem::Function(nl_func) => {
+            file.functions.push(nl_func);
+        }
+        RootItem::Enum(nl_enum) => {
+            file.enums.push(nl_enum);
+        }
+        RootItem::Implementation(implementation) => {
+            file.impls.push(implementation);
+        }
+    }
+}
+
+fn parse_file_root(input: &str) -> ParserResult<NLFile> {
+    let mut file = new_file("");
 
     if !input.is_empty() {
-        let (input, root_defs) = many1(alt((
-            read_struct,
-            read_trait,
-            read_function,
-            read_variant_enum,
-        )))(input)?;
+        let (input, root_defs) = many1(read_root_deceleration)(input)?;
+
+        // Reading stops at the first thing that isn't an item, which would otherwise be silently thrown away.
+        let (input, _) = blank(input)?;
+        if !input.is_empty() {
+            return Err(verbose_failure(
+                input,
+                "Unexpected text after the last item.",
+            ));
+        }
 
         for root_def in root_defs {
-            match root_def {
-                RootDeceleration::Struct(nl_struct) => {
-                    file.structs.push(nl_struct);
-                }
-                RootDeceleration::Trait(nl_trait) => {
-                    file.traits.push(nl_trait);
-                }
-                RootDeceleration::Function(nl_func) => {
-                    file.functions.push(nl_func);
-                }
-                RootDeceleration::Enum(nl_enum) => {
-                    file.enums.push(nl_enum);
-                }
-            }
+            add_root_deceleration(&mut file, root_def);
         }
+        link_file_implementations(&mut file)?;
 
         Ok((input, file))
     } else {
@@ -1879,24 +5084,84 @@ fn parse_file_root(input: &str) -> ParserResult<NLFile> {
     }
 }
 
+// Skips past a broken root deceleration to the next thing that looks like the start of one.
+fn skip_to_next_root_deceleration(input: &str) -> &str {
+    const KEYWORDS: [&str; 4] = ["struct", "trait", "fn", "enum"];
+
+    let mut remaining = input;
+    let mut previous = None;
+    while let Some(c) = remaining.chars().next() {
+        // Keywords inside of comments and strings don't start a new deceleration.
+        if let Ok((after, _)) = read_comment(remaining) {
+            remaining = after;
+            previous = Some(' ');
+            continue;
+        }
+
+        if let Ok((after, _)) = read_string_constant(remaining) {
+            remaining = after;
+            previous = Some('"');
+            continue;
+        }
+
+        // We never stop on the very first character, or we'd just fail on the same deceleration again.
+        let at_boundary = match previous {
+            Some(previous) => !is_method_char(previous),
+            None => false,
+        };
+
+        if at_boundary {
+            // Attributes belong to the item after them, so we stop in front of those too.
+            if remaining.starts_with("#[") {
+                return remaining;
+            }
+
+            for keyword in KEYWORDS.iter() {
+                if remaining.starts_with(keyword) {
+                    let after = remaining[keyword.len()..].chars().next();
+                    if after.map_or(true, |after| after.is_whitespace()) {
+                        return remaining;
+                    }
+                }
+            }
+        }
+
+        previous = Some(c);
+        remaining = &remaining[c.len_utf8()..];
+    }
+
+    // Nothing left to recover with.
+    remaining
+}
+
 pub fn parse_string<'a>(input: &'a str, file_name: &str) -> Result<NLFile<'a>, ParseError> {
+    parse_string_with_options(input, file_name, ParseOptions::default())
+}
+
+/// Parses the file just like `parse_string`, but with the given options instead of the defaults.
+pub fn parse_string_with_options<'a>(
+    input: &'a str,
+    file_name: &str,
+    options: ParseOptions,
+) -> Result<NLFile<'a>, ParseError> {
+    let _options = OptionsGuard::new(&options);
     let file = parse_file_root(input);
 
     match file {
         Result::Err(err) => {
             match err {
                 nom::Err::Error(e) | nom::Err::Failure(e) => {
-                    let message = convert_error(input, e);
+                    let error = ParseError::from_nom(input, e);
 
                     // Makes our error messages more readable when running tests.
                     #[cfg(test)]
-                    println!("{}", message);
+                    println!("{}", error);
 
-                    Err(ParseError { message })
+                    Err(error)
+                }
+                nom::Err::Incomplete(_) => {
+                    Err(ParseError::new("Unexpected end of file.".to_string()))
                 }
-                nom::Err::Incomplete(_) => Err(ParseError {
-                    message: "Unexpected end of file.".to_string(),
-                }),
             }
         }
         Result::Ok(result) => {
@@ -1909,6 +5174,360 @@ pub fn parse_string<'a>(input: &'a str, file_name: &str) -> Result<NLFile<'a>, P
     }
 }
 
+/// Parses a type on its own, such as `&mut SomeStruct`. The whole input must be the type, apart from blank space.
+pub fn parse_type(input: &str) -> Result<NLType, ParseError> {
+    fn read_whole_type(input: &str) -> ParserResult<NLType> {
+        let (input, nl_type) = read_variable_type(input)?;
+        let (input, _) = blank(input)?;
+
+        if input.is_empty() {
+            Ok((input, nl_type))
+        } else {
+            Err(verbose_error(input, "Unexpected text after the type."))
+        }
+    }
+
+    match read_whole_type(input) {
+        Ok((_, nl_type)) => Ok(nl_type),
+        Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => Err(ParseError::from_nom(input, e)),
+        Err(nom::Err::Incomplete(_)) => Err(ParseError::new("Unexpected end of type.".to_string())),
+    }
+}
+
+/// Reads one of the primitive types from a string, such as `"i32"`. Types that name a struct or trait borrow that
+/// name from the string, so they can't be read this way.
+impl FromStr for NLType<'static> {
+    type Err = ParseError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match parse_type(input)? {
+            NLType::Boolean => Ok(NLType::Boolean),
+            NLType::I8 => Ok(NLType::I8),
+            NLType::I16 => Ok(NLType::I16),
+            NLType::I32 => Ok(NLType::I32),
+            NLType::I64 => Ok(NLType::I64),
+            NLType::U8 => Ok(NLType::U8),
+            NLType::U16 => Ok(NLType::U16),
+            NLType::U32 => Ok(NLType::U32),
+            NLType::U64 => Ok(NLType::U64),
+            NLType::F32 => Ok(NLType::F32),
+            NLType::F64 => Ok(NLType::F64),
+            NLType::BorrowedString => Ok(NLType::BorrowedString),
+            NLType::OwnedString => Ok(NLType::OwnedString),
+            _ => Err(ParseError::new(format!(
+                "`{}` is not a primitive type.",
+                input.trim()
+            ))),
+        }
+    }
+}
+
+/// Parses a single operation on its own, such as `1 + 2` or `foo(bar)`, without needing a function around it. The
+/// whole input must be the operation, apart from blank space.
+pub fn parse_expression(input: &str) -> Result<NLOperation, ParseError> {
+    parse_expression_with_options(input, ParseOptions::default())
+}
+
+/// Parses the operation just like `parse_expression`, but with the given options instead of the defaults.
+pub fn parse_expression_with_options(
+    input: &str,
+    options: ParseOptions,
+) -> Result<NLOperation, ParseError> {
+    fn read_whole_expression(input: &str) -> ParserResult<NLOperation> {
+        let (input, operation) = read_operation(input)?;
+        let (input, _) = blank(input)?;
+
+        if input.is_empty() {
+            Ok((input, operation))
+        } else {
+            Err(verbose_error(
+                input,
+                "Unexpected text after the expression.",
+            ))
+        }
+    }
+
+    let _options = OptionsGuard::new(&options);
+    match read_whole_expression(input) {
+        Ok((_, operation)) => Ok(operation),
+        Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => Err(ParseError::from_nom(input, e)),
+        Err(nom::Err::Incomplete(_)) => {
+            Err(ParseError::new("Unexpected end of expression.".to_string()))
+        }
+    }
+}
+
+/// Parses a single statement from the body of a function, such as `let x = 5;`. The semicolon at the end is optional.
+/// Like `parse_expression`, the whole input must be the statement, apart from blank space.
+pub fn parse_statement(input: &str) -> Result<NLOperation, ParseError> {
+    parse_statement_with_options(input, ParseOptions::default())
+}
+
+/// Parses the statement just like `parse_statement`, but with the given options instead of the defaults.
+pub fn parse_statement_with_options(
+    input: &str,
+    options: ParseOptions,
+) -> Result<NLOperation, ParseError> {
+    fn read_whole_statement(input: &str) -> ParserResult<NLOperation> {
+        let (input, operation) = read_operation(input)?;
+        let (input, _) = opt(preceded(blank, char(';')))(input)?;
+        let (input, _) = blank(input)?;
+
+        if input.is_empty() {
+            Ok((input, operation))
+        } else {
+            Err(verbose_error(input, "Unexpected text after the statement."))
+        }
+    }
+
+    let _options = OptionsGuard::new(&options);
+    match read_whole_statement(input) {
+        Ok((_, operation)) => Ok(operation),
+        Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => Err(ParseError::from_nom(input, e)),
+        Err(nom::Err::Incomplete(_)) => {
+            Err(ParseError::new("Unexpected end of statement.".to_string()))
+        }
+    }
+}
+
+/// Reads the first root deceleration from the input and gives back what's left after it, so that concatenated
+/// snippets can be read one item at a time. The tail starts right after the item, blank space included.
+pub fn parse_one_item(input: &str) -> Result<(RootItem, &str), ParseError> {
+    parse_one_item_with_options(input, ParseOptions::default())
+}
+
+/// Reads the first item just like `parse_one_item`, but with the given options instead of the defaults.
+pub fn parse_one_item_with_options(
+    input: &str,
+    options: ParseOptions,
+) -> Result<(RootItem, &str), ParseError> {
+    let _options = OptionsGuard::new(&options);
+
+    // The blank parser can't fail, it just might not consume anything. Doc comments are left for the item after them.
+    let remaining = match blank_before_docs(input) {
+        Ok((remaining, _)) => remaining,
+        Err(_) => input,
+    };
+
+    if remaining.is_empty() {
+        return Err(ParseError::new("Expected an item.".to_string()));
+    }
+
+    match read_root_deceleration(remaining) {
+        Ok((tail, item)) => Ok((item, tail)),
+        Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => Err(ParseError::from_nom(input, e)),
+        Err(nom::Err::Incomplete(_)) => Err(ParseError::new("Unexpected end of item.".to_string())),
+    }
+}
+
+/// Parses the file just like `parse_string`, and interns the names of its items and struct fields, so that they can be
+/// looked up by `Symbol` instead of by string. The table turns the symbols back into names.
+pub fn parse_interned<'a>(
+    input: &'a str,
+    file_name: &str,
+) -> Result<(InternedFile<'a>, SymbolTable<'a>), ParseError> {
+    let file = parse_string(input, file_name)?;
+    let mut table = SymbolTable::new();
+
+    let structs = index_names(
+        &mut table,
+        file.structs.iter().map(|nl_struct| nl_struct.name),
+    );
+    let traits = index_names(&mut table, file.traits.iter().map(|nl_trait| nl_trait.name));
+    let functions = index_names(
+        &mut table,
+        file.functions.iter().map(|function| function.name),
+    );
+    let enums = index_names(&mut table, file.enums.iter().map(|nl_enum| nl_enum.name));
+
+    let mut fields = HashMap::new();
+    for (struct_index, nl_struct) in file.structs.iter().enumerate() {
+        // Only the struct that a name finds can have its fields found.
+        let struct_symbol = table.intern(nl_struct.name);
+        if structs.get(&struct_symbol) != Some(&struct_index) {
+            continue;
+        }
+
+        let names = nl_struct.variables.iter().map(|variable| variable.name);
+        for (field, index) in index_names(&mut table, names) {
+            fields.insert((struct_symbol, field), index);
+        }
+    }
+
+    let file = InternedFile {
+        file,
+        structs,
+        traits,
+        functions,
+        enums,
+        fields,
+    };
+
+    Ok((file, table))
+}
+
+/// Parses the file just like `parse_string`, but also counts how many times the major parsers were run.
+pub fn parse_string_with_stats<'a>(
+    input: &'a str,
+    file_name: &str,
+) -> Result<(NLFile<'a>, ParseStats), ParseError> {
+    PARSE_STATS.with(|stats| *stats.borrow_mut() = Some(ParseStats::default()));
+    let file = parse_string(input, file_name);
+    let stats = PARSE_STATS.with(|stats| stats.borrow_mut().take().unwrap_or_default());
+
+    file.map(|file| (file, stats))
+}
+
+/// Parses the file just like `parse_string`, but also records the span of every root deceleration.
+pub fn parse_string_spanned<'a>(
+    input: &'a str,
+    file_name: &str,
+) -> Result<SpannedFile<'a>, ParseError> {
+    let mut file = SpannedFile {
+        name: file_name.to_string(),
+        structs: vec![],
+        traits: vec![],
+        functions: vec![],
+        enums: vec![],
+    };
+    let mut implementations = Vec::new();
+
+    let mut remaining = input;
+    loop {
+        // The blank parser can't fail, it just might not consume anything. Doc comments are left for the item after them.
+        if let Ok((after_blank, _)) = blank_before_docs(remaining) {
+            remaining = after_blank;
+        }
+
+        if remaining.is_empty() {
+            break;
+        }
+
+        match read_root_deceleration(remaining) {
+            Ok((after_deceleration, root_def)) => {
+                // Some decelerations read the blank space after them too, which isn't part of their span.
+                let text = &remaining[..remaining.len() - after_deceleration.len()];
+                let start = input.len() - remaining.len();
+                let span = Span {
+                    start,
+                    end: start + text.trim_end().len(),
+                };
+
+                match root_def {
+                    RootItem::Struct(item) => file.structs.push(Spanned { span, item }),
+                    RootItem::Trait(item) => file.traits.push(Spanned { span, item }),
+                    RootItem::Function(item) => file.functions.push(Spanned { span, item }),
+                    RootItem::Enum(item) => file.enums.push(Spanned { span, item }),
+                    RootItem::Implementation(item) => implementations.push(item),
+                }
+
+                remaining = after_deceleration;
+            }
+            Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => {
+                let error = ParseError::from_nom(input, e);
+
+                // Makes our error messages more readable when running tests.
+                #[cfg(test)]
+                println!("{}", error);
+
+                return Err(error);
+            }
+            Err(nom::Err::Incomplete(_)) => {
+                return Err(ParseError::new("Unexpected end of file.".to_string()));
+            }
+        }
+    }
+
+    // Detached implementations become part of their struct, but the struct's span stays where it was written.
+    let mut structs: Vec<&mut NLStruct<'a>> = file
+        .structs
+        .iter_mut()
+        .map(|nl_struct| &mut nl_struct.item)
+        .collect();
+    if let Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) =
+        link_implementations(&mut structs, implementations)
+    {
+        return Err(ParseError::from_nom(input, e));
+    }
+
+    Ok(file)
+}
+
+/// Parses as much of the file as possible, rather than stopping at the first error. When a root deceleration fails to
+/// parse, its error is recorded and we skip ahead to the next `struct`, `trait`, `fn`, or `enum` keyword. A file is
+/// only returned if at least one deceleration was read or there were no errors at all.
+pub fn parse_string_collect<'a>(
+    input: &'a str,
+    file_name: &str,
+) -> (Option<NLFile<'a>>, Vec<ParseError>) {
+    let mut file = new_file(file_name);
+    let mut errors = Vec::new();
+    let mut has_decelerations = false;
+
+    let mut remaining = input;
+    loop {
+        // The blank parser can't fail, it just might not consume anything. Doc comments are left for the item after them.
+        if let Ok((after_blank, _)) = blank_before_docs(remaining) {
+            remaining = after_blank;
+        }
+
+        if remaining.is_empty() {
+            break;
+        }
+
+        match read_root_deceleration(remaining) {
+            Ok((after_deceleration, root_def)) => {
+                add_root_deceleration(&mut file, root_def);
+                has_decelerations = true;
+                remaining = after_deceleration;
+            }
+            Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => {
+                // The error references the original input, so its location is relative to the whole file.
+                let error = ParseError::from_nom(input, e);
+
+                // Makes our error messages more readable when running tests.
+                #[cfg(test)]
+                println!("{}", error);
+
+                errors.push(error);
+                remaining = skip_to_next_root_deceleration(remaining);
+            }
+            Err(nom::Err::Incomplete(_)) => {
+                errors.push(ParseError::new("Unexpected end of file.".to_string()));
+                break;
+            }
+        }
+    }
+
+    if let Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) =
+        link_file_implementations(&mut file)
+    {
+        errors.push(ParseError::from_nom(input, e));
+    }
+
+    if has_decelerations || errors.is_empty() {
+        (Some(file), errors)
+    } else {
+        (None, errors)
+    }
+}
+
+// Paths like `..` have no file name, so we fall back to the whole path. Names that aren't UTF-8 are made lossy.
+fn name_of_file(path: &Path) -> Result<String, ParseError> {
+    let file_name = match path.file_name() {
+        Some(file_name) => file_name.to_string_lossy(),
+        None => path.to_string_lossy(),
+    };
+
+    if file_name.is_empty() {
+        return Err(ParseError::new(
+            "Could not get a name for the file.".to_string(),
+        ));
+    }
+
+    Ok(file_name.into_owned())
+}
+
 pub fn parse_file<T>(
     path: &Path,
     function: &dyn Fn(&NLFile) -> T,
@@ -1918,8 +5537,42 @@ pub fn parse_file<T>(
     let mut contents = String::new();
     input_file.read_to_string(&mut contents)?;
 
-    // This should *always* have a name since we shouldn't have been able to get to this point if it wasn't actually a file.
-    let result = parse_string(&contents, &path.file_name().unwrap().to_str().unwrap());
+    let file_name = name_of_file(path)?;
+    let result = parse_string(&contents, &file_name);
+
+    match result {
+        Ok(result) => Ok(function(&result)),
+        Err(error) => Err(Box::new(error)),
+    }
+}
+
+/// Like `parse_file`, but parses straight out of a memory map of the file instead of copying it onto the heap first.
+/// The parsed file borrows from the map, which is why it's only lent to `function`.
+///
+/// The file must not be modified while it's being parsed. The map reflects changes made by other processes, so
+/// changing the file underneath us could break the UTF-8 guarantee we checked when it was mapped.
+#[cfg(feature = "mmap")]
+pub fn parse_mmap<T>(
+    path: &Path,
+    function: &dyn Fn(&NLFile) -> T,
+) -> Result<T, Box<dyn std::error::Error>> {
+    let input_file = File::open(&path)?;
+
+    // Safety: See the requirements in the doc comment above. The map lives until the end of this function, and
+    // everything borrowing from it is dropped before then. Empty files can't be mapped, so we skip those.
+    let map = if input_file.metadata()?.len() > 0 {
+        Some(unsafe { memmap2::Mmap::map(&input_file)? })
+    } else {
+        None
+    };
+
+    let contents = match &map {
+        Some(map) => std::str::from_utf8(map)?,
+        None => "",
+    };
+
+    let file_name = name_of_file(path)?;
+    let result = parse_string(contents, &file_name);
 
     match result {
         Ok(result) => Ok(function(&result)),
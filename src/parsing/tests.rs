@@ -47,7 +47,7 @@ fn unwrap_constant_boolean<'a>(op: &NLOperation<'a>) -> bool {
 fn unwrap_constant_signed(op: &NLOperation) -> i64 {
     let constant = unwrap_to!(op => NLOperation::Constant);
     match constant {
-        OpConstant::Signed(value, _) => *value,
+        OpConstant::Signed(value, _, _) => *value,
         _ => {
             panic!("Expected integer for constant type, got: {:?}");
         }
@@ -107,6 +107,548 @@ mod root {
         }
     }
 
+    #[test]
+    /// Paths without a file name are named after the whole path, and an empty path gives an error instead of a panic.
+    fn file_without_name() {
+        assert_eq!(name_of_file(Path::new("..")).unwrap(), "..");
+        assert_eq!(
+            name_of_file(Path::new("tests/parsing/..")).unwrap(),
+            "tests/parsing/.."
+        );
+        assert!(name_of_file(Path::new("")).is_err());
+
+        assert_eq!(
+            name_of_file(Path::new("tests/parsing/empty_struct_and_trait.nl")).unwrap(),
+            "empty_struct_and_trait.nl"
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    /// Names that aren't valid UTF-8 are converted instead of causing a panic.
+    fn file_with_invalid_name() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = Path::new(OsStr::from_bytes(b"dir/bad\xFF.nl"));
+        assert_eq!(name_of_file(path).unwrap(), "bad\u{FFFD}.nl");
+    }
+
+    #[test]
+    /// Normal paths still name the file after the last part of the path.
+    fn file_with_name() {
+        let file_name = "tests/parsing/empty_struct_and_trait.nl";
+        let name = parse_file(&Path::new(file_name), &|file: &NLFile| {
+            file.get_name().to_string()
+        })
+        .unwrap();
+
+        assert_eq!(name, "empty_struct_and_trait.nl");
+    }
+
+    #[test]
+    #[cfg(feature = "mmap")]
+    /// Parsing out of a memory map gives the same file as parsing out of memory.
+    fn memory_mapped_file() {
+        let mut code = String::new();
+        for index in 0..2000 {
+            code += &format!(
+                "struct Thing{} {{ a: i32 }}\nfn function{}() {{ let b = 5 }}\n",
+                index, index
+            );
+        }
+
+        let path = std::env::temp_dir().join("nested_language_memory_mapped_file.nl");
+        std::fs::write(&path, &code).unwrap();
+
+        let names = |file: &NLFile| -> Vec<String> {
+            file.iter_items()
+                .map(|item| item.get_name().to_string())
+                .collect()
+        };
+
+        let mapped = parse_mmap(&path, &names).unwrap();
+        let in_memory = names(&parse_string(&code, "virtual_file").unwrap());
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(mapped.len(), 4000, "Wrong number of items.");
+        assert_eq!(mapped, in_memory);
+    }
+
+    #[test]
+    /// Pretty errors point at the exact character that was wrong.
+    fn pretty_error() {
+        let code = "fn test() {\n    let x = 1.2.3\n}";
+        let error = parse_string(code, "virtual_file").err().unwrap();
+
+        let pretty = error.pretty(code);
+        let expected = " --> line 2, column 16\n  |\n2 |     let x = 1.2.3\n  |                ^\n";
+        assert!(pretty.starts_with(expected), "{}", pretty);
+        assert!(pretty.contains("Malformed float"), "{}", pretty);
+    }
+
+    #[test]
+    /// Fingerprints only depend on what the code means, not how it was laid out.
+    fn fingerprint() {
+        fn fingerprint_of(code: &str) -> u64 {
+            let (item, _) = parse_one_item(code).unwrap();
+            ast_fingerprint(&item)
+        }
+
+        let compact =
+            fingerprint_of("struct Point{x:i32,y:f32} impl Self{met len(&self)->f32{self.y*2.5}}");
+        let spread = fingerprint_of(
+            "struct Point {\n    x: i32, // Across.\n    y: f32, /* Down. */\n}\n\nimpl Self {\n    met len(&self) -> f32 {\n        self.y * 2.5\n    }\n}",
+        );
+        assert_eq!(compact, spread);
+
+        // Anything that changes the meaning changes the fingerprint.
+        let renamed =
+            fingerprint_of("struct Point{x:i32,z:f32} impl Self{met len(&self)->f32{self.y*2.5}}");
+        let retyped =
+            fingerprint_of("struct Point{x:i32,y:f64} impl Self{met len(&self)->f32{self.y*2.5}}");
+        let changed =
+            fingerprint_of("struct Point{x:i32,y:f32} impl Self{met len(&self)->f32{self.y*3.5}}");
+        assert_ne!(compact, renamed);
+        assert_ne!(compact, retyped);
+        assert_ne!(compact, changed);
+    }
+
+    #[test]
+    /// Numbers are hashed the same way whatever the platform's size and byte order are.
+    fn fingerprint_hasher_fixed_width() {
+        fn hash(write: impl Fn(&mut FingerprintHasher)) -> u64 {
+            let mut hasher = FingerprintHasher(0xcbf2_9ce4_8422_2325);
+            write(&mut hasher);
+            hasher.finish()
+        }
+
+        let bytes = hash(|hasher| hasher.write(&[1, 0, 0, 0, 0, 0, 0, 0]));
+        assert_eq!(hash(|hasher| hasher.write_u64(1)), bytes);
+        assert_eq!(hash(|hasher| hasher.write_usize(1)), bytes);
+        assert_eq!(hash(|hasher| hasher.write_isize(1)), bytes);
+
+        let bytes = hash(|hasher| hasher.write(&[1, 2, 3, 4]));
+        assert_eq!(hash(|hasher| hasher.write_u32(0x0403_0201)), bytes);
+    }
+
+    #[test]
+    /// Anything left over after the last item is an error, rather than being ignored.
+    fn leftover_input() {
+        let code = "struct Point { x: i32 }\n} 5 +";
+        let error = parse_string(code, "virtual_file").err().unwrap();
+        assert!(
+            error
+                .message
+                .contains("Unexpected text after the last item."),
+            "{}",
+            error
+        );
+        assert_eq!(error.location, Some((2, 1)));
+
+        // Blank space and comments are fine, even a comment without a new line after it.
+        parse_string(
+            "struct Point { x: i32 }\n\n/* Done. */ // The end.",
+            "virtual_file",
+        )
+        .unwrap();
+        parse_string("struct Point { x: i32 }\n//", "virtual_file").unwrap();
+    }
+
+    #[test]
+    /// Running out of input before a block is closed points back at where the block was opened.
+    fn unterminated_blocks() {
+        let code = "fn test() {\n    let x = 5\n";
+        let error = parse_string(code, "virtual_file").err().unwrap();
+        assert!(error.message.contains("Unterminated `{`"), "{}", error);
+        assert_eq!(error.location, Some((1, 11)));
+
+        let code = "fn test() {\n    match x {\n        1 => 2,\n";
+        let error = parse_string(code, "virtual_file").err().unwrap();
+        assert!(error.message.contains("Unterminated `{`"), "{}", error);
+        assert_eq!(error.location, Some((2, 13)));
+
+        let code = "fn test() { foo(1, [2, 3";
+        let error = parse_string(code, "virtual_file").err().unwrap();
+        assert!(error.message.contains("Unterminated `[`"), "{}", error);
+        assert_eq!(error.location, Some((1, 20)));
+    }
+
+    #[test]
+    /// Items can be read one at a time, each time from what was left after the last one.
+    fn one_item_at_a_time() {
+        let code = "struct Point { x: i32 } /// Makes a point.\nfn origin() -> i32 { 0 }  ";
+
+        let (item, tail) = parse_one_item(code).unwrap();
+        let nl_struct = unwrap_to!(item => RootItem::Struct);
+        assert_eq!(nl_struct.get_name(), "Point");
+
+        let (item, tail) = parse_one_item(tail).unwrap();
+        let function = unwrap_to!(item => RootItem::Function);
+        assert_eq!(function.get_name(), "origin");
+        assert_eq!(function.get_doc(), Some("Makes a point."));
+        assert_eq!(tail.trim(), "");
+
+        assert!(parse_one_item(tail).is_err(), "Nothing should be left.");
+        assert!(parse_one_item("fn broken( {").is_err());
+    }
+
+    #[test]
+    fn root_item_kinds() {
+        let mut remaining = "struct Point {} trait Shape {} fn area() {} enum Side { Left } impl Shape for Point {}";
+        let mut kinds = Vec::new();
+
+        while !remaining.trim().is_empty() {
+            let (item, tail) = parse_one_item(remaining).unwrap();
+            let kind = match item {
+                RootItem::Struct(_) => "struct",
+                RootItem::Trait(_) => "trait",
+                RootItem::Function(_) => "function",
+                RootItem::Enum(_) => "enum",
+                RootItem::Implementation(_) => "implementation",
+            };
+            kinds.push((kind, item.name()));
+            remaining = tail;
+        }
+
+        assert_eq!(
+            kinds,
+            vec![
+                ("struct", "Point"),
+                ("trait", "Shape"),
+                ("function", "area"),
+                ("enum", "Side"),
+                ("implementation", "Point"),
+            ]
+        );
+    }
+
+    #[test]
+    /// Deeply nested code is rejected with an error instead of overflowing the stack.
+    fn nesting_limit() {
+        fn nested_function(depth: usize) -> String {
+            format!(
+                "fn test() {{ {}x{} }}",
+                "(".repeat(depth),
+                ")".repeat(depth)
+            )
+        }
+
+        let options = ParseOptions { max_depth: 10 };
+        parse_string_with_options(&nested_function(8), "virtual_file", options.clone()).unwrap();
+
+        let error = parse_string_with_options(&nested_function(10), "virtual_file", options)
+            .err()
+            .unwrap();
+        assert!(error.message.contains("nesting too deep"), "{}", error);
+
+        // Plain parsing has a limit too, and it's deep enough for any reasonable code.
+        parse_string(&nested_function(100), "virtual_file").unwrap();
+        for code in [
+            nested_function(100_000),
+            format!("fn test() {{ {}x }}", "-".repeat(100_000)),
+        ]
+        .iter()
+        {
+            let error = parse_string(code, "virtual_file").err().unwrap();
+            assert!(error.message.contains("nesting too deep"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn nesting_limit_on_other_entry_points() {
+        let code = format!("{}x{}", "(".repeat(10), ")".repeat(10));
+        let options = ParseOptions { max_depth: 10 };
+
+        let error = parse_expression_with_options(&code, options.clone())
+            .err()
+            .unwrap();
+        assert!(error.message.contains("nesting too deep"), "{}", error);
+
+        let error = parse_statement_with_options(&code, options.clone())
+            .err()
+            .unwrap();
+        assert!(error.message.contains("nesting too deep"), "{}", error);
+
+        let item = format!("fn test() {{ {} }}", code);
+        let error = parse_one_item_with_options(&item, options).err().unwrap();
+        assert!(error.message.contains("nesting too deep"), "{}", error);
+
+        // The limit only lasts for the one parse.
+        parse_expression(&code).unwrap();
+    }
+
+    #[test]
+    fn options_restored_after_panic() {
+        let result = std::panic::catch_unwind(|| {
+            let _options = OptionsGuard::new(&ParseOptions { max_depth: 1 });
+            panic!("Something went wrong while parsing.");
+        });
+        assert!(result.is_err());
+
+        parse_expression("((((x))))").unwrap();
+    }
+
+    #[test]
+    /// Tools can read errors as JSON instead of picking apart the message.
+    fn json_error() {
+        let code = "fn test() {\n    let x = 1.2.3\n}";
+        let error = parse_string(code, "virtual_file").err().unwrap();
+
+        let json = error.to_json();
+        assert!(json.starts_with("{\"message\":\""), "{}", json);
+        assert!(json.contains("Malformed float"), "{}", json);
+        assert!(
+            json.ends_with("\"severity\":\"error\",\"offset\":27,\"line\":2,\"column\":16}"),
+            "{}",
+            json
+        );
+
+        // The message spans several lines, which can't be written into a JSON string as is.
+        assert!(!json.contains('\n'), "{}", json);
+
+        let error = ParseError::new(String::from("A \"quoted\" problem."));
+        assert_eq!(
+            error.to_json(),
+            r#"{"message":"A \"quoted\" problem.","severity":"error","offset":null,"line":null,"column":null}"#
+        );
+    }
+
+    #[test]
+    /// Errors in one root deceleration should not stop us from reading the ones after it.
+    fn collect_errors() {
+        let code = "struct First {} struct Broken { a: } struct Second {}";
+        let (file, errors) = parse_string_collect(code, "virtual_file");
+
+        assert_eq!(errors.len(), 1, "Wrong number of errors.");
+        assert!(
+            errors[0].to_string().contains("line 1"),
+            "Error did not have a location."
+        );
+
+        let file = file.expect("Expected a partially parsed file.");
+        assert_eq!(file.structs.len(), 2, "Wrong number of structs.");
+        assert_eq!(file.structs[0].name, "First", "Wrong name for struct.");
+        assert_eq!(file.structs[1].name, "Second", "Wrong name for struct.");
+    }
+
+    #[test]
+    /// Keywords in comments and strings of a broken deceleration are not where we resume.
+    fn collect_errors_skips_comments_and_strings() {
+        let code = "struct Broken { a: /* fn oops() {} */ b: \"fn nope\" } struct Second {}";
+        let (file, errors) = parse_string_collect(code, "virtual_file");
+
+        assert_eq!(errors.len(), 1, "Wrong number of errors.");
+
+        let file = file.expect("Expected a partially parsed file.");
+        assert_eq!(file.functions.len(), 0, "Wrong number of functions.");
+        assert_eq!(file.structs.len(), 1, "Wrong number of structs.");
+        assert_eq!(file.structs[0].name, "Second", "Wrong name for struct.");
+    }
+
+    #[test]
+    /// Items of every kind can be walked through at once.
+    fn iter_items() {
+        let code = "enum Color { Red } fn draw() {} struct Canvas {} trait Shape {} fn clear() {}";
+        let file = parse_string(code, "virtual_file").unwrap();
+
+        let names: Vec<&str> = file.iter_items().map(|item| item.get_name()).collect();
+        assert_eq!(names, vec!["Canvas", "Shape", "draw", "clear", "Color"]);
+
+        let functions = file
+            .iter_items()
+            .filter(|item| matches!(item, NLItemRef::Function(_)))
+            .count();
+        assert_eq!(functions, 2, "Wrong number of functions.");
+    }
+
+    #[test]
+    /// Items can be looked up by name, and missing ones are None.
+    fn find_items() {
+        let code =
+            "struct Canvas {} trait Shape {} fn draw() {} enum Color { Red } fn draw(a: i32) {}";
+        let file = parse_string(code, "virtual_file").unwrap();
+
+        assert_eq!(file.find_struct("Canvas").unwrap().get_name(), "Canvas");
+        assert_eq!(file.find_trait("Shape").unwrap().get_name(), "Shape");
+        assert_eq!(file.find_enum("Color").unwrap().get_name(), "Color");
+
+        // The first function with a name wins.
+        let draw = file.find_function("draw").unwrap();
+        assert_eq!(draw.get_name(), "draw");
+        assert_eq!(draw.arguments.len(), 0, "Found the wrong function.");
+
+        assert!(matches!(file.find_item("Shape"), Some(NLItemRef::Trait(_))));
+        assert!(matches!(
+            file.find_item("draw"),
+            Some(NLItemRef::Function(_))
+        ));
+
+        assert!(file.find_struct("Shape").is_none());
+        assert!(file.find_function("missing").is_none());
+        assert!(file.find_item("missing").is_none());
+    }
+
+    mod unique_names {
+        use super::*;
+
+        fn name_errors(code: &str) -> Vec<String> {
+            let file = parse_string(code, "virtual_file").unwrap();
+            match validate_unique_names(&file) {
+                Ok(()) => Vec::new(),
+                Err(errors) => errors.iter().map(|error| error.to_string()).collect(),
+            }
+        }
+
+        #[test]
+        fn clean_file() {
+            let file = parse_string(
+                "struct Foo { a: i32, b: i32 } enum Bar { A, B } fn foo() {}",
+                "virtual_file",
+            )
+            .unwrap();
+            assert!(validate_unique_names(&file).is_ok());
+        }
+
+        #[test]
+        fn duplicate_types() {
+            let errors = name_errors("struct Foo {} trait Bar {} enum Foo { A } fn Bar() {}");
+            assert_eq!(
+                errors,
+                vec!["The type `Foo` is declared more than once."],
+                "Functions don't share a namespace with types."
+            );
+        }
+
+        #[test]
+        fn duplicate_functions() {
+            let errors = name_errors("fn foo() {} fn foo() {}");
+            assert_eq!(
+                errors,
+                vec!["The function `foo` is declared more than once."]
+            );
+        }
+
+        #[test]
+        fn duplicate_fields_and_variants() {
+            let errors = name_errors("struct Foo { a: i32, a: bool } enum Bar { A, B, A }");
+            assert_eq!(
+                errors,
+                vec![
+                    "The field `a` is declared more than once in struct `Foo`.",
+                    "The variant `A` is declared more than once in enum `Bar`.",
+                ]
+            );
+        }
+    }
+
+    #[test]
+    /// The same name always interns to the same symbol.
+    fn symbol_table() {
+        let code = "struct Thing {} fn thing() {} fn other() {}";
+        let file = parse_string(code, "virtual_file").unwrap();
+
+        let mut table = SymbolTable::from_file(&file);
+        let thing = table
+            .get_symbol("thing")
+            .expect("Function name was not interned.");
+
+        assert_eq!(table.intern("thing"), thing);
+        assert_ne!(table.intern("Thing"), thing);
+        assert_ne!(table.intern("other"), thing);
+        assert_eq!(table.get_name(thing), "thing");
+        assert_eq!(table.get_symbol("missing"), None);
+    }
+
+    #[test]
+    /// Names that show up more than once in a file all share one symbol.
+    fn interned_file() {
+        let code =
+            "struct Point { x: i32, y: i32 } struct Size { x: u32 } fn x() {} enum Axis { X }";
+        let (file, table) = parse_interned(code, "virtual_file").unwrap();
+
+        let point = table.get_symbol("Point").unwrap();
+        let size = table.get_symbol("Size").unwrap();
+        let x = table.get_symbol("x").unwrap();
+        let y = table.get_symbol("y").unwrap();
+        assert_eq!(table.get_name(x), "x");
+
+        assert_eq!(file.find_struct(point).unwrap().get_name(), "Point");
+        assert_eq!(file.find_function(x).unwrap().get_name(), "x");
+        assert!(file.find_struct(x).is_none(), "x is not a struct.");
+        assert_eq!(
+            file.find_enum(table.get_symbol("Axis").unwrap())
+                .unwrap()
+                .get_name(),
+            "Axis"
+        );
+
+        // Both structs have an `x`, but each finds its own.
+        assert_eq!(file.find_field(point, x).unwrap().get_type(), &NLType::I32);
+        assert_eq!(file.find_field(size, x).unwrap().get_type(), &NLType::U32);
+        assert!(file.find_field(size, y).is_none(), "Size has no y.");
+    }
+
+    #[test]
+    fn qualified_names() {
+        let code = "fn foo() {} struct Bar {} trait Baz {} enum Qux { A }";
+        let file = parse_string(code, "my_mod.nl").unwrap();
+
+        assert_eq!(file.source_name(), "my_mod");
+        assert_eq!(file.get_functions()[0].qualified_name(&file), "my_mod::foo");
+        assert_eq!(file.get_structs()[0].qualified_name(&file), "my_mod::Bar");
+        assert_eq!(file.get_traits()[0].qualified_name(&file), "my_mod::Baz");
+        assert_eq!(file.get_enums()[0].qualified_name(&file), "my_mod::Qux");
+        assert_eq!(
+            file.find_item("foo").unwrap().qualified_name(&file),
+            "my_mod::foo"
+        );
+
+        // Without an extension, the whole name is used.
+        let file = parse_string(code, "virtual_file").unwrap();
+        assert_eq!(
+            file.get_functions()[0].qualified_name(&file),
+            "virtual_file::foo"
+        );
+    }
+
+    #[test]
+    /// Spans cover exactly the text of each root deceleration.
+    fn spanned_file() {
+        let code = "struct First {}\n\n  struct Second { a: i32 }  \n";
+        let file = parse_string_spanned(code, "virtual_file").unwrap();
+
+        let structs = file.get_structs();
+        assert_eq!(structs.len(), 2, "Wrong number of structs.");
+        assert_eq!(structs[1].item.name, "Second", "Wrong name for struct.");
+
+        let span = structs[1].span;
+        assert_eq!(&code[span.start..span.end], "struct Second { a: i32 }");
+    }
+
+    #[test]
+    /// Parsing with stats counts how often the major parsers ran, and leaves normal parsing uncounted.
+    fn parse_stats() {
+        let code =
+            "fn first(a: i32) -> i32 { let b = a + 1 b * 2 } fn second(c: i32) -> i32 { first(c) }";
+        let (file, stats) = parse_string_with_stats(code, "virtual_file").unwrap();
+
+        assert_eq!(file.functions.len(), 2, "Wrong number of functions.");
+        assert!(stats.read_operation >= 3, "{:?}", stats);
+        assert!(stats.read_expression >= 2, "{:?}", stats);
+        assert!(stats.read_function_call >= 1, "{:?}", stats);
+        assert!(
+            stats.read_sub_operation >= stats.read_expression,
+            "{:?}",
+            stats
+        );
+
+        // Stats are only collected while asked for.
+        parse_string(code, "virtual_file").unwrap();
+        let (_, second_stats) = parse_string_with_stats(code, "virtual_file").unwrap();
+        assert_eq!(stats, second_stats);
+    }
+
     mod nl_struct {
         use super::*;
 
@@ -224,6 +766,103 @@ mod root {
             .unwrap();
         }
 
+        #[test]
+        /// A struct variable can be given a default value.
+        fn variable_with_default() {
+            let code = "struct Config { retries: i32 = 3, }";
+            let file = parse_string(code, "virtual_file").unwrap();
+
+            assert_eq!(file.structs.len(), 1, "Wrong number of structs.");
+            let my_struct = &file.structs[0];
+            assert_eq!(my_struct.variables.len(), 1, "Wrong number of variables.");
+            let variable = &my_struct.variables[0];
+            assert_eq!(variable.name, "retries", "Variable had wrong name.");
+            assert_eq!(variable.my_type, NLType::I32, "Variable had wrong type.");
+            assert_eq!(
+                variable.get_default(),
+                &Some(NLOperation::Constant(OpConstant::Signed(
+                    3,
+                    NLType::I32,
+                    false
+                ))),
+                "Variable had wrong default."
+            );
+        }
+
+        #[test]
+        /// Strings owned by the struct.
+        fn owned_string_variable() {
+            let code = "struct Person { name: String }";
+            let file = parse_string(code, "virtual_file").unwrap();
+
+            let my_struct = &file.structs[0];
+            assert_eq!(my_struct.variables.len(), 1, "Wrong number of variables.");
+            let variable = &my_struct.variables[0];
+            assert_eq!(variable.name, "name", "Variable had wrong name.");
+            assert_eq!(
+                variable.my_type,
+                NLType::OwnedString,
+                "Variable had wrong type."
+            );
+        }
+
+        #[test]
+        /// A struct variable without a default value.
+        fn variable_without_default() {
+            let code = "struct Config { retries: i32, }";
+            let file = parse_string(code, "virtual_file").unwrap();
+
+            assert_eq!(file.structs.len(), 1, "Wrong number of structs.");
+            let my_struct = &file.structs[0];
+            assert_eq!(my_struct.variables.len(), 1, "Wrong number of variables.");
+            let variable = &my_struct.variables[0];
+            assert_eq!(variable.name, "retries", "Variable had wrong name.");
+            assert_eq!(variable.get_default(), &None, "Variable had a default.");
+        }
+
+        #[test]
+        /// Only one of the two variables has a default value. We don't put the trailing comma after the last one.
+        fn two_variables_one_default() {
+            let code = "struct Config { retries: i32 = 3, timeout: u64 }";
+            let file = parse_string(code, "virtual_file").unwrap();
+
+            assert_eq!(file.structs.len(), 1, "Wrong number of structs.");
+            let my_struct = &file.structs[0];
+            assert_eq!(my_struct.variables.len(), 2, "Wrong number of variables.");
+
+            let variable = &my_struct.variables[0];
+            assert_eq!(variable.name, "retries", "Variable had wrong name.");
+            assert_eq!(
+                variable.get_default(),
+                &Some(NLOperation::Constant(OpConstant::Signed(
+                    3,
+                    NLType::I32,
+                    false
+                ))),
+                "Variable had wrong default."
+            );
+
+            let variable = &my_struct.variables[1];
+            assert_eq!(variable.name, "timeout", "Variable had wrong name.");
+            assert_eq!(variable.my_type, NLType::U64, "Variable had wrong type.");
+            assert_eq!(variable.get_default(), &None, "Variable had a default.");
+        }
+
+        #[test]
+        /// Struct and variable names are not limited to ASCII.
+        fn unicode_struct_name() {
+            let code = "struct Café { 数: i32 }";
+            let file = parse_string(code, "virtual_file").unwrap();
+
+            assert_eq!(file.structs.len(), 1, "Wrong number of structs.");
+            let my_struct = &file.structs[0];
+            assert_eq!(my_struct.name, "Café", "Wrong name for struct.");
+            assert_eq!(my_struct.variables.len(), 1, "Wrong number of variables.");
+            let variable = &my_struct.variables[0];
+            assert_eq!(variable.name, "数", "Variable had wrong name.");
+            assert_eq!(variable.my_type, NLType::I32, "Variable had wrong type.");
+        }
+
         #[test]
         /// Compile a file with an empty struct and an empty trait. This one is special because it has multi line comments in it.
         fn struct_empty_self_implementation() {
@@ -384,6 +1023,78 @@ mod root {
             assert_eq!(arg.nl_type, NLType::I16, "Wrong argument type.");
         }
 
+        #[test]
+        fn default_value() {
+            let code = "(name: str, greeting: str = \"hello\", count: i32 = 1 + 2)";
+            let args = pretty_read(code, &read_argument_deceleration_list);
+
+            assert_eq!(args.len(), 3, "Wrong number of args.");
+            assert!(args[0].get_default().is_none(), "First arg has no default.");
+
+            let greeting = unwrap_to!(args[1].get_default().unwrap() => NLOperation::Constant);
+            assert_eq!(*greeting, OpConstant::String(String::from("hello")));
+
+            let count = unwrap_to!(args[2].get_default().unwrap() => NLOperation::Operator);
+            assert!(matches!(count, OpOperator::ArithmeticAdd(_)));
+        }
+
+        #[test]
+        /// Argument types with parentheses of their own don't end the list early.
+        fn nested_parentheses() {
+            let code = "(pair: (i32, (u8, bool)), other: i16)";
+            let args = pretty_read(code, &read_argument_deceleration_list);
+
+            assert_eq!(args.len(), 2, "Wrong number of args.");
+
+            let arg = &args[0];
+            assert_eq!(arg.name, "pair", "Wrong argument name.");
+            assert_eq!(
+                arg.nl_type,
+                NLType::Tuple(vec![
+                    NLType::I32,
+                    NLType::Tuple(vec![NLType::U8, NLType::Boolean])
+                ]),
+                "Wrong argument type."
+            );
+
+            let arg = &args[1];
+            assert_eq!(arg.name, "other", "Wrong argument name.");
+            assert_eq!(arg.nl_type, NLType::I16, "Wrong argument type.");
+        }
+
+        #[test]
+        /// Function types have parentheses of their own, and an arrow that isn't the end of the list either.
+        fn function_argument() {
+            let code = "(cb: fn(i32) -> bool)";
+            let args = pretty_read(code, &read_argument_deceleration_list);
+
+            assert_eq!(args.len(), 1, "Wrong number of args.");
+            assert_eq!(args[0].name, "cb", "Wrong argument name.");
+            assert_eq!(
+                args[0].nl_type,
+                NLType::Function(vec![NLType::I32], Box::new(NLType::Boolean)),
+                "Wrong argument type."
+            );
+
+            let code = "(map: fn(fn(u8), (i32, bool)), count: u64)";
+            let args = pretty_read(code, &read_argument_deceleration_list);
+
+            assert_eq!(args.len(), 2, "Wrong number of args.");
+            assert_eq!(
+                args[0].nl_type,
+                NLType::Function(
+                    vec![
+                        NLType::Function(vec![NLType::U8], Box::new(NLType::None)),
+                        NLType::Tuple(vec![NLType::I32, NLType::Boolean])
+                    ],
+                    Box::new(NLType::None)
+                ),
+                "Wrong argument type."
+            );
+            assert_eq!(args[1].name, "count", "Wrong argument name.");
+            assert_eq!(args[1].nl_type, NLType::U64, "Wrong argument type.");
+        }
+
         #[test]
         /// Testing the argument declaration reader.
         fn three_args() {
@@ -405,6 +1116,23 @@ mod root {
             assert_eq!(arg.nl_type, NLType::I8, "Wrong argument type.");
         }
 
+        #[test]
+        /// Strings are borrowed with str and owned with String.
+        fn string_args() {
+            let code = "(borrowed: str, owned: String)";
+            let args = pretty_read(code, &read_argument_deceleration_list);
+
+            assert_eq!(args.len(), 2, "Wrong number of args.");
+
+            let arg = &args[0];
+            assert_eq!(arg.name, "borrowed", "Wrong argument name.");
+            assert_eq!(arg.nl_type, NLType::BorrowedString, "Wrong argument type.");
+
+            let arg = &args[1];
+            assert_eq!(arg.name, "owned", "Wrong argument name.");
+            assert_eq!(arg.nl_type, NLType::OwnedString, "Wrong argument type.");
+        }
+
         #[test]
         /// Testing the argument declaration reader.
         fn self_reference_arg() {
@@ -666,41 +1394,457 @@ mod root {
         }
     }
 
-    mod nl_methods {
+    mod equivalence {
         use super::*;
 
-        fn pretty_read_method(input: &str) -> (&str, NLFunction) {
-            let result = read_method(input);
-            match result {
-                Ok(tuple) => {
-                    let (s, method) = tuple;
-                    match method {
-                        NLImplementor::Method(method) => (s, method),
-                        _ => {
-                            panic!("Did not get a method.");
-                        }
-                    }
-                }
-                Err(e) => {
-                    match e {
-                        nom::Err::Error(e) | nom::Err::Failure(e) => {
-                            let message = convert_error(input, e);
+        fn compare(a: &str, b: &str) -> Result<(), String> {
+            let a = parse_string(a, "a").unwrap();
+            let b = parse_string(b, "b").unwrap();
+            files_equivalent(&a, &b)
+        }
 
-                            // Makes our error messages more readable when running tests.
-                            #[cfg(test)]
-                            println!("{}", message);
-                            panic!(message);
-                        }
-                        nom::Err::Incomplete(_) => {
-                            panic!("Unexpected end of file.");
-                        }
-                    }
-                }
-            }
+        #[test]
+        fn identical_files() {
+            let code = "struct Foo { x: i32 } impl Self { met get(&self) -> i32 { self.x } } fn test() { let y = 5 }";
+            assert_eq!(compare(code, code), Ok(()));
         }
 
         #[test]
-        /// Construct a blank and unimplemented method.
+        fn reordered_files() {
+            let a = "struct Foo {} trait Bar {} fn test() {} enum Choice { A, B }";
+            let b = "enum Choice { A, B } fn test() {} /// Docs aren't compared.\n trait Bar {} struct Foo {}";
+            assert_eq!(compare(a, b), Ok(()));
+        }
+
+        #[test]
+        fn files_differ() {
+            let a = "struct Foo {} fn test() {}";
+            let b = "fn test() {}";
+            assert_eq!(compare(a, b), Err("struct Foo missing in b".to_string()));
+            assert_eq!(compare(b, a), Err("struct Foo missing in a".to_string()));
+
+            let a = "fn test() { let x = 5 }";
+            let b = "fn test() { let x = 6 }";
+            assert_eq!(
+                compare(a, b),
+                Err("function test differs in its body".to_string())
+            );
+
+            let a = "struct Foo { x: i32 }";
+            let b = "struct Foo { x: i64 }";
+            assert_eq!(
+                compare(a, b),
+                Err("struct Foo differs in its variables".to_string())
+            );
+        }
+
+        #[test]
+        fn access_rules_differ() {
+            let a = "external fn test() {}";
+            let b = "fn test() {}";
+            assert_eq!(
+                compare(a, b),
+                Err("function test differs in its access rule".to_string())
+            );
+
+            let a = "external enum Choice { A, B }";
+            let b = "enum Choice { A, B }";
+            assert_eq!(
+                compare(a, b),
+                Err("enum Choice differs in its access rule".to_string())
+            );
+
+            let a = "struct Foo {} impl Self { external met open(&self) {} }";
+            let b = "struct Foo {} impl Self { met open(&self) {} }";
+            assert_eq!(
+                compare(a, b),
+                Err("struct Foo differs in its implementations".to_string())
+            );
+
+            let a = "trait Shape { external get size(&self) -> i32; }";
+            let b = "trait Shape { get size(&self) -> i32; }";
+            assert_eq!(
+                compare(a, b),
+                Err("trait Shape differs in its methods".to_string())
+            );
+
+            // Members that take their item's rule are the same as members that spell it out.
+            let a = "external struct Foo {} impl Self { met open(&self) {} }";
+            let b = "external struct Foo {} impl Self { external met open(&self) {} }";
+            assert_eq!(compare(a, b), Ok(()));
+        }
+
+        #[test]
+        fn attributes_differ() {
+            let a = "#[derive(Debug)] struct Foo { x: i32 }";
+            let b = "struct Foo { x: i32 }";
+            assert_eq!(
+                compare(a, b),
+                Err("struct Foo differs in its attributes".to_string())
+            );
+
+            let a = "struct Foo { #[skip] x: i32 }";
+            let b = "struct Foo { x: i32 }";
+            assert_eq!(
+                compare(a, b),
+                Err("struct Foo differs in its variables".to_string())
+            );
+
+            let a = "#[test] fn test() {}";
+            let b = "#[inline] fn test() {}";
+            assert_eq!(
+                compare(a, b),
+                Err("function test differs in its attributes".to_string())
+            );
+
+            let a = "struct Foo {} impl Self { #[inline] met open(&self) {} }";
+            let b = "struct Foo {} impl Self { met open(&self) {} }";
+            assert_eq!(
+                compare(a, b),
+                Err("struct Foo differs in its implementations".to_string())
+            );
+
+            let a = "#[repr(u8)] enum Choice { A, B }";
+            let b = "enum Choice { A, B }";
+            assert_eq!(
+                compare(a, b),
+                Err("enum Choice differs in its attributes".to_string())
+            );
+
+            let a = "enum Choice { #[default] A, B }";
+            let b = "enum Choice { A, B }";
+            assert_eq!(
+                compare(a, b),
+                Err("enum Choice differs in its variants".to_string())
+            );
+        }
+    }
+
+    mod detached_implementations {
+        use super::*;
+
+        #[test]
+        fn detached_impl() {
+            let code = "struct Circle { radius: i32 }
+                        fn helper() {}
+                        impl Circle { met get(&self) -> i32 { self.radius } }
+                        impl Drawable for Circle { met draw(&self) {} }";
+            let file = parse_string(code, "virtual_file").unwrap();
+
+            let implementations = &file.structs[0].implementations;
+            assert_eq!(implementations.len(), 2);
+            assert_eq!(implementations[0].get_name(), "Circle");
+            assert_eq!(implementations[0].get_trait_name(), None);
+            assert_eq!(implementations[1].get_trait_name(), Some("Drawable"));
+
+            let file = parse_string_spanned(code, "virtual_file").unwrap();
+            assert_eq!(file.get_structs()[0].item.implementations.len(), 2);
+        }
+
+        #[test]
+        fn impl_after_other_struct() {
+            let code = "struct Circle {} impl Drawable for Square { met draw(&self) {} }
+                        struct Square {} impl Self {}";
+            let file = parse_string(code, "virtual_file").unwrap();
+
+            assert_eq!(file.structs[0].implementations.len(), 0);
+            let implementations = &file.structs[1].implementations;
+            assert_eq!(implementations.len(), 2);
+            assert_eq!(implementations[0].get_trait_name(), None);
+            assert_eq!(implementations[1].get_trait_name(), Some("Drawable"));
+
+            let code = "struct Circle {} impl Drawable for Square {}";
+            let error = parse_string(code, "virtual_file").err().unwrap();
+            assert!(
+                error
+                    .message
+                    .contains("Implementation for Square, which is not a struct in this file."),
+                "{}",
+                error
+            );
+        }
+
+        #[test]
+        fn impl_for_missing_struct() {
+            let code = "fn helper() {} impl Square { met side(&self) {} }";
+            let error = match parse_string(code, "virtual_file") {
+                Err(error) => error,
+                Ok(_) => panic!("The implementation should have been rejected."),
+            };
+            assert!(
+                error
+                    .message
+                    .contains("Implementation for Square, which is not a struct in this file."),
+                "{}",
+                error
+            );
+            assert_eq!(error.offset, Some(code.find("Square").unwrap()));
+
+            let (_, errors) = parse_string_collect(code, "virtual_file");
+            assert_eq!(errors.len(), 1);
+        }
+    }
+
+    mod access_rules {
+        use super::*;
+
+        fn access_rules(implementors: &[NLImplementor]) -> Vec<NLAccessRule> {
+            implementors
+                .iter()
+                .map(|implementor| implementor.get_access())
+                .collect()
+        }
+
+        #[test]
+        fn items_default_to_internal() {
+            let code = "struct Thing {} external fn test() {} internal trait Shape {} external enum Choice { A }";
+            let file = parse_string(code, "virtual_file").unwrap();
+
+            assert_eq!(file.structs[0].get_access(), NLAccessRule::Internal);
+            assert_eq!(file.functions[0].get_access(), NLAccessRule::External);
+            assert_eq!(file.traits[0].get_access(), NLAccessRule::Internal);
+            assert_eq!(file.enums[0].get_access(), NLAccessRule::External);
+        }
+
+        #[test]
+        fn external_method_in_internal_struct() {
+            let code = "struct Thing {} impl Self {
+                            external met open(&self) {}
+                            met hidden(&self) {}
+                            external get size(&self) -> i32 {}
+                            set size(value: i32) {}
+                        }";
+            let file = parse_string(code, "virtual_file").unwrap();
+
+            let implementors = file.structs[0].implementations[0].get_implementors();
+            assert_eq!(
+                access_rules(implementors),
+                vec![
+                    NLAccessRule::External,
+                    NLAccessRule::Internal,
+                    NLAccessRule::External,
+                    NLAccessRule::Internal
+                ]
+            );
+        }
+
+        #[test]
+        fn members_default_to_their_item() {
+            let code = "external enum Choice { A, internal B }
+                        external trait Shape { met area(&self) -> i32; }
+                        external struct Thing {}
+                        fn helper() {}
+                        impl Thing { met open(&self) {} internal met close(&self) {} }";
+            let file = parse_string(code, "virtual_file").unwrap();
+
+            let variants = file.enums[0].get_variants();
+            assert_eq!(variants[0].get_access(), NLAccessRule::External);
+            assert_eq!(variants[1].get_access(), NLAccessRule::Internal);
+
+            assert_eq!(
+                access_rules(file.traits[0].get_implementors()),
+                vec![NLAccessRule::External]
+            );
+
+            // Implementations written apart from their struct get its rule once they're moved into it.
+            let implementors = file.structs[0].implementations[0].get_implementors();
+            assert_eq!(
+                access_rules(implementors),
+                vec![NLAccessRule::External, NLAccessRule::Internal]
+            );
+        }
+    }
+
+    mod doc_comments {
+        use super::*;
+
+        #[test]
+        fn block_doc_comment() {
+            let code = "/** Adds things. */ fn add() {}";
+            let file = parse_string(code, "virtual_file").unwrap();
+            assert_eq!(file.functions[0].get_doc(), Some("Adds things."));
+        }
+
+        #[test]
+        fn plain_block_comment_is_ignored() {
+            let code = "/* Not documentation. */ fn add() {} /**/ struct Thing {}";
+            let file = parse_string(code, "virtual_file").unwrap();
+            assert_eq!(file.functions[0].get_doc(), None);
+            assert_eq!(file.structs[0].get_doc(), None);
+        }
+
+        #[test]
+        fn doc_lines_are_joined() {
+            let code = "/// First line.\n// Not documentation.\n/// Second line.\n/** Third line. */\nstruct Thing {}";
+            let file = parse_string(code, "virtual_file").unwrap();
+            assert_eq!(
+                file.structs[0].get_doc(),
+                Some("First line.\nSecond line.\nThird line.")
+            );
+        }
+
+        #[test]
+        fn every_item_can_be_documented() {
+            let code = "/** A trait. */ trait Shape { /// A method.\n met area(&self) -> i32; }
+                        /** A struct. */ struct Square {} impl Self { /** A method. */ met side(&self) {} }
+                        /** An enum. */ enum Choice { A, B }
+                        /// A function.\n fn test() {}";
+            let file = parse_string(code, "virtual_file").unwrap();
+
+            assert_eq!(file.traits[0].get_doc(), Some("A trait."));
+            assert_eq!(file.structs[0].get_doc(), Some("A struct."));
+            assert_eq!(file.enums[0].get_doc(), Some("An enum."));
+            assert_eq!(file.functions[0].get_doc(), Some("A function."));
+
+            let method = file.traits[0].iter_methods().next().unwrap();
+            assert_eq!(method.get_doc(), Some("A method."));
+            let method = file.structs[0].implementations[0]
+                .iter_methods()
+                .next()
+                .unwrap();
+            assert_eq!(method.get_doc(), Some("A method."));
+        }
+    }
+
+    mod attributes {
+        use super::*;
+
+        #[test]
+        fn struct_attribute() {
+            let code = "#[derive(Debug)] struct Foo {}";
+            let file = parse_string(code, "virtual_file").unwrap();
+
+            let attributes = file.structs[0].get_attributes();
+            assert_eq!(attributes.len(), 1);
+            assert_eq!(attributes[0].get_name(), "derive");
+            assert_eq!(attributes[0].get_args(), &vec!["Debug"]);
+        }
+
+        #[test]
+        fn field_attribute() {
+            let code = "struct Foo { #[skip] a: i32, b: i32 }";
+            let file = parse_string(code, "virtual_file").unwrap();
+
+            let variables = file.structs[0].get_variables();
+            assert_eq!(variables[0].get_attributes()[0].get_name(), "skip");
+            assert!(variables[0].get_attributes()[0].get_args().is_empty());
+            assert!(variables[1].get_attributes().is_empty());
+        }
+
+        #[test]
+        fn attributes_stack() {
+            let code =
+                "/// Documented.\n#[test]\n#[derive( Debug , Hash )]\n/// More docs.\nfn check() {}
+                        enum Choice { #[default] A, B }";
+            let file = parse_string(code, "virtual_file").unwrap();
+
+            let function = &file.functions[0];
+            assert_eq!(function.get_doc(), Some("Documented.\nMore docs."));
+            let attributes = function.get_attributes();
+            assert_eq!(attributes.len(), 2);
+            assert_eq!(attributes[0].get_name(), "test");
+            assert_eq!(attributes[1].get_args(), &vec!["Debug", "Hash"]);
+
+            let variants = file.enums[0].get_variants();
+            assert_eq!(variants[0].get_attributes()[0].get_name(), "default");
+            assert!(variants[1].get_attributes().is_empty());
+        }
+
+        #[test]
+        fn malformed_attribute() {
+            for (code, expected) in [
+                ("#[derive(Debug struct Foo {}", "Expected a `,` or `)`"),
+                (
+                    "#[test fn check() {}",
+                    "Expected a `]` to close the attribute.",
+                ),
+                ("#[] fn check() {}", "Expected the name of an attribute."),
+                ("#[derive(Debug", "Unterminated `(`"),
+            ]
+            .iter()
+            {
+                let error = parse_string(code, "virtual_file").err().unwrap();
+                assert!(error.to_string().contains(expected), "{}", error);
+            }
+        }
+    }
+
+    mod type_params {
+        use super::*;
+
+        #[test]
+        fn single_bound() {
+            let code = "fn foo<T: A>(x: T) {}";
+            let file = parse_string(code, "virtual_file").unwrap();
+            assert_eq!(file.functions[0].get_type_params(), &vec![("T", vec!["A"])]);
+        }
+
+        #[test]
+        fn multiple_bounds() {
+            let code = "fn foo< T : A + B >(x: T) {}";
+            let file = parse_string(code, "virtual_file").unwrap();
+            assert_eq!(
+                file.functions[0].get_type_params(),
+                &vec![("T", vec!["A", "B"])]
+            );
+        }
+
+        #[test]
+        fn multiple_params() {
+            let code = "struct Thing {} impl Self { met foo<T: Drawable, U, V: A + B,>(&self, x: T, y: U) {} }";
+            let file = parse_string(code, "virtual_file").unwrap();
+            let method = file.structs[0].implementations[0]
+                .iter_methods()
+                .next()
+                .unwrap();
+
+            assert_eq!(
+                method.get_type_params(),
+                &vec![
+                    ("T", vec!["Drawable"]),
+                    ("U", vec![]),
+                    ("V", vec!["A", "B"])
+                ]
+            );
+            assert_eq!(method.arguments.len(), 3);
+        }
+    }
+
+    mod nl_methods {
+        use super::*;
+
+        fn pretty_read_method(input: &str) -> (&str, NLFunction) {
+            let result = read_method(input);
+            match result {
+                Ok(tuple) => {
+                    let (s, method) = tuple;
+                    match method {
+                        NLImplementor::Method(method) => (s, method),
+                        _ => {
+                            panic!("Did not get a method.");
+                        }
+                    }
+                }
+                Err(e) => {
+                    match e {
+                        nom::Err::Error(e) | nom::Err::Failure(e) => {
+                            let message = convert_error(input, e);
+
+                            // Makes our error messages more readable when running tests.
+                            #[cfg(test)]
+                            println!("{}", message);
+                            panic!(message);
+                        }
+                        nom::Err::Incomplete(_) => {
+                            panic!("Unexpected end of file.");
+                        }
+                    }
+                }
+            }
+        }
+
+        #[test]
+        /// Construct a blank and unimplemented method.
         fn method_no_arg_no_return_no_impl() {
             let code = "met my_method();";
 
@@ -766,6 +1910,93 @@ mod root {
                 "Method should have been implemented."
             );
         }
+
+        #[test]
+        /// Implementations name the trait they implement with `for`.
+        fn implementation_names() {
+            let code =
+                "struct Circle {} impl Self {} impl Drawable for Circle { met draw(&self) {} }";
+            let file = parse_string(code, "virtual_file").unwrap();
+
+            let implementations = &file.structs[0].implementations;
+            assert_eq!(implementations.len(), 2, "Wrong number of implementations.");
+
+            assert_eq!(implementations[0].get_name(), "Self");
+            assert_eq!(implementations[0].get_trait_name(), None);
+
+            assert_eq!(implementations[1].get_name(), "Circle");
+            assert_eq!(implementations[1].get_trait_name(), Some("Drawable"));
+            assert_eq!(implementations[1].iter_methods().count(), 1);
+        }
+
+        #[test]
+        /// Split the methods of an implementation by whether or not they take self.
+        fn instance_methods_and_associated_functions() {
+            let code = "struct A {} impl Self { met get(&self) -> i32 {} met new() -> A {} }";
+            let file = parse_string(code, "virtual_file").unwrap();
+
+            let implementation = &file.structs[0].implementations[0];
+
+            let instance_methods = implementation.instance_methods();
+            assert_eq!(
+                instance_methods.len(),
+                1,
+                "Wrong number of instance methods."
+            );
+            assert_eq!(instance_methods[0].name, "get", "Wrong instance method.");
+            assert_eq!(
+                instance_methods[0].get_receiver(),
+                Some(&NLType::SelfReference),
+                "Wrong receiver."
+            );
+
+            let associated_functions = implementation.associated_functions();
+            assert_eq!(
+                associated_functions.len(),
+                1,
+                "Wrong number of associated functions."
+            );
+            assert_eq!(
+                associated_functions[0].name, "new",
+                "Wrong associated function."
+            );
+            assert_eq!(
+                associated_functions[0].get_receiver(),
+                None,
+                "Wrong receiver."
+            );
+        }
+
+        #[test]
+        /// Implementors can be walked by kind, without matching on each one.
+        fn iterate_implementors_by_kind() {
+            let code = "struct A {} impl Self {
+                met first() {}
+                get value(&self) -> i32 {}
+                met second(&self) {}
+                set value(value: i32) {}
+                get other:default;
+            }
+            trait B { met required(&self); get size(&self) -> i32; }";
+            let file = parse_string(code, "virtual_file").unwrap();
+
+            let implementation = &file.structs[0].implementations[0];
+            assert_eq!(implementation.iter_methods().count(), 2);
+            assert_eq!(implementation.iter_getters().count(), 2);
+            assert_eq!(implementation.iter_setters().count(), 1);
+
+            let names: Vec<&str> = implementation
+                .get_implementors()
+                .iter()
+                .map(|implementor| implementor.get_name())
+                .collect();
+            assert_eq!(names, vec!["first", "value", "second", "value", "other"]);
+
+            let nl_trait = &file.traits[0];
+            assert_eq!(nl_trait.iter_methods().count(), 1);
+            assert_eq!(nl_trait.iter_getters().count(), 1);
+            assert_eq!(nl_trait.iter_setters().count(), 0);
+        }
     }
 
     mod nl_getters {
@@ -871,6 +2102,19 @@ mod root {
                 "Getter did not have correct return type."
             );
         }
+
+        #[test]
+        /// Getter names are borrowed from the source rather than copied out of it.
+        fn getter_name_borrows_source() {
+            let code = "get my_getter(&self) -> i32;";
+            let (_, getter) = pretty_read_getter(code);
+
+            let source = code.as_bytes().as_ptr_range();
+            assert!(
+                source.contains(&getter.get_name().as_ptr()),
+                "Getter name was not borrowed from the source."
+            );
+        }
     }
 
     mod nl_setters {
@@ -1096,7 +2340,7 @@ mod root {
             assert_eq!(arguments.len(), 1);
 
             let argument = &arguments[0];
-            assert_eq!(argument.get_name(), "a");
+            assert_eq!(argument.get_name(), Some("a"));
             assert_eq!(*unwrap_to!(argument.get_type() => NLType::OwnedStruct), "A");
         }
 
@@ -1121,11 +2365,11 @@ mod root {
             assert_eq!(arguments.len(), 2);
 
             let argument = &arguments[0];
-            assert_eq!(argument.get_name(), "a");
+            assert_eq!(argument.get_name(), Some("a"));
             assert_eq!(*unwrap_to!(argument.get_type() => NLType::OwnedStruct), "A");
 
             let argument = &arguments[1];
-            assert_eq!(argument.get_name(), "b");
+            assert_eq!(argument.get_name(), Some("b"));
             assert_eq!(*unwrap_to!(argument.get_type() => NLType::OwnedStruct), "B");
         }
 
@@ -1150,11 +2394,11 @@ mod root {
             assert_eq!(arguments.len(), 2);
 
             let argument = &arguments[0];
-            assert_eq!(argument.get_name(), "a");
+            assert_eq!(argument.get_name(), Some("a"));
             assert_eq!(*unwrap_to!(argument.get_type() => NLType::OwnedStruct), "A");
 
             let argument = &arguments[1];
-            assert_eq!(argument.get_name(), "b");
+            assert_eq!(argument.get_name(), Some("b"));
             assert_eq!(*unwrap_to!(argument.get_type() => NLType::OwnedStruct), "B");
 
             let variant = &variants[1];
@@ -1164,37 +2408,233 @@ mod root {
             assert_eq!(arguments.len(), 2);
 
             let argument = &arguments[0];
-            assert_eq!(argument.get_name(), "c");
+            assert_eq!(argument.get_name(), Some("c"));
             assert_eq!(*unwrap_to!(argument.get_type() => NLType::OwnedStruct), "C");
 
             let argument = &arguments[1];
-            assert_eq!(argument.get_name(), "d");
+            assert_eq!(argument.get_name(), Some("d"));
             assert_eq!(*unwrap_to!(argument.get_type() => NLType::OwnedStruct), "D");
         }
-    }
-}
 
-mod executable_blocks {
-    use super::*;
+        #[test]
+        fn variant_kinds() {
+            let code =
+                "enum Shape { Empty, Line(i32, length: f32), Rect { width: i32, height: i32 } }";
+            let file = parse_string(code, "virtual_file").unwrap();
+            let variants = file.get_enums()[0].get_variants();
+            assert_eq!(variants.len(), 3);
+
+            let unit = &variants[0];
+            assert_eq!(unit.get_kind(), VariantKind::Unit);
+            assert_eq!(unit.get_arguments().len(), 0);
+
+            // Tuple fields don't need names, but can have them.
+            let tuple = &variants[1];
+            assert_eq!(tuple.get_kind(), VariantKind::Tuple);
+            let fields: Vec<(Option<&str>, &NLType)> = tuple
+                .get_arguments()
+                .iter()
+                .map(|field| (field.get_name(), field.get_type()))
+                .collect();
+            assert_eq!(
+                fields,
+                vec![(None, &NLType::I32), (Some("length"), &NLType::F32)]
+            );
 
-    mod constants {
-        use super::*;
+            let nl_struct = &variants[2];
+            assert_eq!(nl_struct.get_kind(), VariantKind::Struct);
+            let fields: Vec<(Option<&str>, &NLType)> = nl_struct
+                .get_arguments()
+                .iter()
+                .map(|field| (field.get_name(), field.get_type()))
+                .collect();
+            assert_eq!(
+                fields,
+                vec![
+                    (Some("width"), &NLType::I32),
+                    (Some("height"), &NLType::I32)
+                ]
+            );
+
+            // Struct fields always need names.
+            assert!(parse_string("enum Shape { Rect { i32 } }", "virtual_file").is_err());
+        }
 
         #[test]
-        fn decimal_number() {
-            let code = "5";
-            let constant = pretty_read(code, &read_constant);
-            let constant = unwrap_constant(constant);
+        fn find_variant() {
+            let code = "enum Shape { Empty, Line(i32, f32), Rect { width: i32, height: i32 } }";
+            let file = parse_string(code, "virtual_file").unwrap();
+            let shape = &file.get_enums()[0];
 
-            match constant {
-                OpConstant::Signed(constant, cast) => {
-                    assert_eq!(constant, 5, "Constant had wrong value.");
-                    assert_eq!(cast, NLType::I32, "Wrong type cast recommendation.");
+            let (index, empty) = shape.find_variant("Empty").unwrap();
+            assert_eq!(index, 0);
+            assert_eq!(empty.arity(), 0);
+
+            let (index, line) = shape.find_variant("Line").unwrap();
+            assert_eq!(index, 1);
+            assert_eq!(line.get_name(), "Line");
+            assert_eq!(line.arity(), 2);
+
+            let (index, rect) = shape.find_variant("Rect").unwrap();
+            assert_eq!(index, 2);
+            assert_eq!(rect.arity(), 2);
+
+            assert!(shape.find_variant("Circle").is_none());
+
+            // The position doesn't care about explicit discriminants.
+            let code = "enum Color { Red = 1, Green = 2, Blue = 4 }";
+            let file = parse_string(code, "virtual_file").unwrap();
+            let (index, blue) = file.get_enums()[0].find_variant("Blue").unwrap();
+            assert_eq!(index, 2);
+            assert_eq!(blue.get_discriminant(), Some(4));
+        }
+
+        #[test]
+        fn explicit_discriminants() {
+            let code = "enum Color { Red = 1, Green = 2, Blue = 4 }";
+            let file = parse_string(code, "virtual_file").unwrap();
+            let enums = file.get_enums();
+
+            assert_eq!(enums.len(), 1);
+
+            let nl_enum = &enums[0];
+            assert_eq!(nl_enum.get_name(), "Color");
+
+            let variants = nl_enum.get_variants();
+            assert_eq!(variants.len(), 3);
+
+            let variant = &variants[0];
+            assert_eq!(variant.name, "Red");
+            assert_eq!(variant.get_discriminant(), Some(1));
+
+            let variant = &variants[1];
+            assert_eq!(variant.name, "Green");
+            assert_eq!(variant.get_discriminant(), Some(2));
+
+            let variant = &variants[2];
+            assert_eq!(variant.name, "Blue");
+            assert_eq!(variant.get_discriminant(), Some(4));
+        }
+
+        #[test]
+        fn discriminant_expression() {
+            let code = "enum Flags { A = 1 << 2, B = 0x10 | 1, C = (250u8 + 5) }";
+            let file = parse_string(code, "virtual_file").unwrap();
+            let enums = file.get_enums();
+
+            let variants = enums[0].get_variants();
+            assert_eq!(variants.len(), 3);
+
+            let variant = &variants[0];
+            assert_eq!(variant.name, "A");
+            assert!(
+                variant.get_discriminant_expression().is_some(),
+                "Expected an expression for the discriminant."
+            );
+            assert_eq!(variant.get_discriminant(), Some(4));
+
+            let variant = &variants[1];
+            assert_eq!(variant.name, "B");
+            assert_eq!(variant.get_discriminant(), Some(0x11));
+
+            let variant = &variants[2];
+            assert_eq!(variant.name, "C");
+            assert_eq!(variant.get_discriminant(), Some(255));
+        }
+
+        #[test]
+        fn discriminant_not_constant() {
+            for code in &[
+                "enum Flags { A = a + 1 }",
+                "enum Flags { A = 18446744073709551615u64 }",
+                // Discriminants follow the same overflow rules as the rest of constant folding.
+                "enum Flags { A = 250u8 + 10u8 }",
+            ] {
+                let message = match parse_string(code, "virtual_file") {
+                    Err(error) => error.message,
+                    Ok(_) => panic!("Discriminant should have been rejected: {}", code),
+                };
+                assert!(message.contains("must be integer constants"), "{}", message);
+            }
+        }
+
+        #[test]
+        fn discriminant_with_data_rejected() {
+            let code = "enum MyVariant { One = 1, Two(a: A) }";
+            let message = match parse_string(code, "virtual_file") {
+                Err(error) => error.message,
+                Ok(_) => panic!("Mixed discriminants and data were accepted."),
+            };
+            assert!(
+                message.contains("cannot have variants that carry data"),
+                "{}",
+                message
+            );
+
+            // The error should point at the variant carrying data.
+            let caret = format!("{}\n{}^", code, " ".repeat(code.find("Two").unwrap()));
+            assert!(message.contains(&caret), "{}", message);
+        }
+    }
+}
+
+mod executable_blocks {
+    use super::*;
+
+    mod constants {
+        use super::*;
+
+        #[test]
+        fn decimal_number() {
+            let code = "5";
+            let constant = pretty_read(code, &read_constant);
+            let constant = unwrap_constant(constant);
+
+            match constant {
+                OpConstant::Signed(constant, cast, _) => {
+                    assert_eq!(constant, 5, "Constant had wrong value.");
+                    assert_eq!(cast, NLType::I32, "Wrong type cast recommendation.");
                 }
                 _ => panic!("Expected Signed for constant type."),
             }
         }
 
+        #[test]
+        fn decimal_with_underscores() {
+            let (input, constant) = read_constant("1_000").unwrap();
+            assert_eq!(input, "");
+            assert_eq!(
+                unwrap_constant(constant),
+                OpConstant::Signed(1000, NLType::I32, false)
+            );
+        }
+
+        #[test]
+        fn decimal_misplaced_underscores() {
+            assert!(read_constant("_5").is_err());
+            assert!(read_constant("5_").is_err());
+        }
+
+        #[test]
+        fn decimal_misplaced_sign() {
+            assert!(read_constant("--5").is_err());
+
+            // The second sign isn't part of the number.
+            let (input, _) = read_constant("5-").unwrap();
+            assert_eq!(input, "-");
+        }
+
+        #[test]
+        fn subtraction_without_spaces() {
+            let code = "1-2";
+            let operation = pretty_read(code, &read_operation);
+            let operator = unwrap_to!(operation => NLOperation::Operator);
+            let (a, b) = unwrap_to!(operator => OpOperator::ArithmeticSub);
+
+            assert_eq!(unwrap_constant_signed(a), 1, "Wrong value for constant.");
+            assert_eq!(unwrap_constant_signed(b), 2, "Wrong value for constant.");
+        }
+
         #[test]
         fn hexadecimal_number() {
             let code = "0xA5";
@@ -1202,7 +2642,7 @@ mod executable_blocks {
             let constant = unwrap_constant(constant);
 
             match constant {
-                OpConstant::Signed(constant, cast) => {
+                OpConstant::Signed(constant, cast, _) => {
                     assert_eq!(constant, 0xA5, "Constant had wrong value.");
                     assert_eq!(cast, NLType::I32, "Wrong type cast recommendation.");
                 }
@@ -1217,7 +2657,7 @@ mod executable_blocks {
             let constant = unwrap_constant(constant);
 
             match constant {
-                OpConstant::Signed(constant, cast) => {
+                OpConstant::Signed(constant, cast, _) => {
                     assert_eq!(constant, 0o32, "Constant had wrong value.");
                     assert_eq!(cast, NLType::I32, "Wrong type cast recommendation.");
                 }
@@ -1232,7 +2672,7 @@ mod executable_blocks {
             let constant = unwrap_constant(constant);
 
             match constant {
-                OpConstant::Signed(constant, cast) => {
+                OpConstant::Signed(constant, cast, _) => {
                     assert_eq!(constant as i64, -5, "Constant had wrong value.");
                     assert_eq!(cast, NLType::I32, "Wrong type cast recommendation.");
                 }
@@ -1247,7 +2687,7 @@ mod executable_blocks {
             let constant = unwrap_constant(constant);
 
             match constant {
-                OpConstant::Signed(constant, cast) => {
+                OpConstant::Signed(constant, cast, _) => {
                     assert_eq!(constant, 5, "Constant had wrong value.");
                     assert_eq!(cast, NLType::I64, "Wrong type cast recommendation.");
                 }
@@ -1262,7 +2702,7 @@ mod executable_blocks {
             let constant = unwrap_constant(constant);
 
             match constant {
-                OpConstant::Signed(constant, cast) => {
+                OpConstant::Signed(constant, cast, _) => {
                     assert_eq!(constant as i64, -5, "Constant had wrong value.");
                     assert_eq!(cast, NLType::I64, "Wrong type cast recommendation.");
                 }
@@ -1270,6 +2710,150 @@ mod executable_blocks {
             }
         }
 
+        #[test]
+        fn typed_radix_numbers() {
+            for (code, expected) in [
+                ("-0x10i64", OpConstant::Signed(-16, NLType::I64, true)),
+                ("0b1010u8", OpConstant::Unsigned(10, NLType::U8, true)),
+                ("0o17i16", OpConstant::Signed(15, NLType::I16, true)),
+                ("-0b11", OpConstant::Signed(-3, NLType::I32, false)),
+                ("-0o7", OpConstant::Signed(-7, NLType::I32, false)),
+                ("-0x80i8", OpConstant::Signed(-128, NLType::I8, true)),
+            ]
+            .iter()
+            {
+                let constant = pretty_read(code, &read_constant);
+                assert_eq!(&unwrap_constant(constant), expected, "{}", code);
+            }
+
+            let message = constant_error_message("-0x5u8");
+            assert!(
+                message.contains("Integer literal -0x5 is negative, so it cannot be unsigned."),
+                "{}",
+                message
+            );
+
+            let message = constant_error_message("-5u8");
+            assert!(
+                message.contains("Integer literal -5 is negative, so it cannot be unsigned."),
+                "{}",
+                message
+            );
+        }
+
+        fn constant_error_message(code: &str) -> String {
+            match read_constant(code) {
+                Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => describe_error(code, e),
+                Err(nom::Err::Incomplete(_)) => panic!("Unexpected end of file."),
+                Ok(_) => panic!("Expected an error for {}", code),
+            }
+        }
+
+        #[test]
+        fn typed_number_too_large() {
+            let message = constant_error_message("300u8");
+            assert!(
+                message.contains("literal 300 does not fit in u8"),
+                "{}",
+                message
+            );
+
+            let message = constant_error_message("0x1FFu8");
+            assert!(
+                message.contains("literal 0x1FF does not fit in u8"),
+                "{}",
+                message
+            );
+        }
+
+        #[test]
+        fn typed_number_boundary() {
+            let message = constant_error_message("256u8");
+            assert!(message.contains("does not fit in u8"), "{}", message);
+        }
+
+        #[test]
+        fn messages_are_not_templates() {
+            // The literal goes where the message says it does, and nowhere else.
+            let message = constant_error_message("300u8");
+            assert!(
+                message.starts_with("Integer literal 300 does not fit in u8."),
+                "{}",
+                message
+            );
+
+            // Braces in a message are just text.
+            let code = "name";
+            let error = match verbose_failure(code, "Expected `{}` after the name.") {
+                nom::Err::Failure(error) => error,
+                _ => unreachable!(),
+            };
+            let message = describe_error(code, error);
+            assert!(
+                message.contains("Expected `{}` after the name."),
+                "{}",
+                message
+            );
+        }
+
+        #[test]
+        fn default_type() {
+            let (_, constant) = read_numerical_constant_with_default("5", NLType::U64).unwrap();
+            assert_eq!(constant, OpConstant::Unsigned(5, NLType::U64, false));
+
+            // The suffix wins over the default.
+            let (_, constant) = read_numerical_constant_with_default("5i8", NLType::U64).unwrap();
+            assert_eq!(constant, OpConstant::Signed(5, NLType::I8, true));
+
+            let (_, constant) = read_numerical_constant_with_default("-5", NLType::I64).unwrap();
+            assert_eq!(constant, OpConstant::Signed(-5, NLType::I64, false));
+
+            // Without a default, we get an i32.
+            let (_, constant) = read_numerical_constant("-5").unwrap();
+            assert_eq!(constant, OpConstant::Signed(-5, NLType::I32, false));
+        }
+
+        #[test]
+        fn default_type_negative_unsigned() {
+            let code = "-5";
+            match read_numerical_constant_with_default(code, NLType::U64) {
+                Err(nom::Err::Failure(error)) => {
+                    let message = describe_error(code, error);
+                    assert!(
+                        message.contains("Integer literal -5 is negative"),
+                        "{}",
+                        message
+                    );
+                }
+                _ => panic!("A negative number should not be unsigned."),
+            }
+        }
+
+        #[test]
+        fn typed_number_max() {
+            let code = "255u8";
+            let constant = pretty_read(code, &read_constant);
+            let constant = unwrap_constant(constant);
+
+            assert_eq!(
+                constant,
+                OpConstant::Unsigned(255, NLType::U8, true),
+                "Constant had wrong value."
+            );
+        }
+
+        #[test]
+        fn typed_number_signed_too_small() {
+            let message = constant_error_message("-129i8");
+            assert!(message.contains("does not fit in i8"), "{}", message);
+        }
+
+        #[test]
+        fn typed_number_overflows_u64() {
+            let message = constant_error_message("18446744073709551616u64");
+            assert!(message.contains("does not fit in u64"), "{}", message);
+        }
+
         #[test]
         fn float() {
             let code = "5.5";
@@ -1368,6 +2952,159 @@ mod executable_blocks {
             }
         }
 
+        #[test]
+        fn float_leading_dot() {
+            let code = ".5";
+            let constant = pretty_read(code, &read_constant);
+            let constant = unwrap_constant(constant);
+
+            match constant {
+                OpConstant::Float32(constant) => {
+                    assert_eq!(constant, 0.5, "Constant had wrong value.");
+                }
+                _ => panic!("Expected float32 for constant type."),
+            }
+        }
+
+        #[test]
+        fn float_trailing_dot() {
+            let code = "5.";
+            let constant = pretty_read(code, &read_constant);
+            let constant = unwrap_constant(constant);
+
+            match constant {
+                OpConstant::Float32(constant) => {
+                    assert_eq!(constant, 5.0, "Constant had wrong value.");
+                }
+                _ => panic!("Expected float32 for constant type."),
+            }
+        }
+
+        #[test]
+        fn float_exponent_no_fraction() {
+            let code = "1e10";
+            let constant = pretty_read(code, &read_constant);
+            let constant = unwrap_constant(constant);
+
+            match constant {
+                OpConstant::Float32(constant) => {
+                    assert_eq!(constant, 1e10, "Constant had wrong value.");
+                }
+                _ => panic!("Expected float32 for constant type."),
+            }
+        }
+
+        #[test]
+        fn float_negative_exponent() {
+            let code = "1.5e-3";
+            let constant = pretty_read(code, &read_constant);
+            let constant = unwrap_constant(constant);
+
+            match constant {
+                OpConstant::Float32(constant) => {
+                    assert_eq!(constant, 1.5e-3, "Constant had wrong value.");
+                }
+                _ => panic!("Expected float32 for constant type."),
+            }
+        }
+
+        #[test]
+        fn float_with_type() {
+            let code = "5.5f64";
+            let (input, constant) = read_constant(code).unwrap();
+            let constant = unwrap_constant(constant);
+
+            assert_eq!(input, "", "Type suffix was not consumed.");
+            match constant {
+                OpConstant::Float64(constant) => {
+                    assert_eq!(constant, 5.5, "Constant had wrong value.");
+                }
+                _ => panic!("Expected float64 for constant type."),
+            }
+        }
+
+        #[test]
+        fn float_with_f32_type() {
+            let code = "5.5f32";
+            let constant = pretty_read(code, &read_constant);
+            let constant = unwrap_constant(constant);
+
+            match constant {
+                OpConstant::Float32(constant) => {
+                    assert_eq!(constant, 5.5, "Constant had wrong value.");
+                }
+                _ => panic!("Expected float32 for constant type."),
+            }
+        }
+
+        #[test]
+        fn float_suffixes() {
+            // The suffix must not be read as part of the number, and the number must not eat into the suffix.
+            let (input, constant) = read_constant("5f32").unwrap();
+            assert_eq!(input, "", "Type suffix was not consumed.");
+            assert_eq!(unwrap_constant(constant), OpConstant::Float32(5.0));
+
+            let (input, constant) = read_constant("5.0f32").unwrap();
+            assert_eq!(input, "", "Type suffix was not consumed.");
+            assert_eq!(unwrap_constant(constant), OpConstant::Float32(5.0));
+
+            let (input, constant) = read_constant("5e3f64").unwrap();
+            assert_eq!(input, "", "Type suffix was not consumed.");
+            assert_eq!(unwrap_constant(constant), OpConstant::Float64(5e3));
+
+            let (input, constant) = read_constant("5.0").unwrap();
+            assert_eq!(input, "");
+            assert_eq!(unwrap_constant(constant), OpConstant::Float32(5.0));
+
+            let (input, constant) = read_constant("5").unwrap();
+            assert_eq!(input, "");
+            assert_eq!(
+                unwrap_constant(constant),
+                OpConstant::Signed(5, NLType::I32, false)
+            );
+        }
+
+        #[test]
+        fn float_suffix_on_hexadecimal() {
+            // The `f` is a hex digit, so this is just a big hexadecimal number.
+            let (input, constant) = read_constant("0x5f32").unwrap();
+            assert_eq!(input, "");
+            assert_eq!(
+                unwrap_constant(constant),
+                OpConstant::Signed(0x5f32, NLType::I32, false)
+            );
+
+            assert!(read_constant("0b101f32").is_err());
+        }
+
+        #[test]
+        fn float_with_underscores() {
+            let code = "1_000.5";
+            let constant = pretty_read(code, &read_constant);
+            let constant = unwrap_constant(constant);
+
+            match constant {
+                OpConstant::Float32(constant) => {
+                    assert_eq!(constant, 1000.5, "Constant had wrong value.");
+                }
+                _ => panic!("Expected float32 for constant type."),
+            }
+        }
+
+        #[test]
+        fn float_malformed() {
+            let code = "fn main() { a = 1.2.3 }";
+            let message = match parse_string(code, "virtual_file") {
+                Err(error) => error.message,
+                Ok(_) => panic!("Malformed float should not have parsed."),
+            };
+            assert!(
+                message.contains("can only have one decimal point"),
+                "{}",
+                message
+            );
+        }
+
         #[test]
         fn boolean_true() {
             let code = "true";
@@ -1425,39 +3162,129 @@ mod executable_blocks {
                 _ => panic!("Expected string for constant type."),
             }
         }
-    }
-
-    mod variables {
-        use super::*;
 
         #[test]
-        fn variable_access() {
-            let code = "bah";
-            let operation = pretty_read(code, &read_operation);
+        fn string_with_unicode_escape() {
+            let code = "\"\\u{41}\\u{1F600}\"";
+            let constant = pretty_read(code, &read_constant);
+            let constant = unwrap_constant(constant);
 
-            match operation {
-                NLOperation::VariableAccess(access) => {
-                    assert_eq!(access.name, "bah", "Variable had wrong name.");
-                }
-                _ => panic!("Expected variable access operation, got {:?}", operation),
-            }
+            assert_eq!(constant, OpConstant::String(String::from("A\u{1F600}")));
         }
-    }
-
-    mod tuples {
-        use super::*;
 
         #[test]
-        fn tuple_empty() {
-            let code = "()";
-            let (_, tuple) = read_tuple(code).unwrap();
-
-            match tuple {
-                NLOperation::Tuple(tuple) => {
-                    assert_eq!(tuple.len(), 0, "Wrong number of items in tuple.");
-                }
-                _ => panic!("Expected none."),
-            }
+        fn string_with_invalid_unicode_escape() {
+            let message = constant_error_message("\"\\u{110000}\"");
+            assert!(
+                message.contains("Unicode escape \\u{110000} is not a valid character"),
+                "{}",
+                message
+            );
+        }
+
+        #[test]
+        fn string_with_malformed_unicode_escape() {
+            let message = constant_error_message("\"\\u{zz}\"");
+            assert!(message.contains("Malformed unicode escape"), "{}", message);
+        }
+
+        #[test]
+        fn multi_line_string() {
+            // Strings can run over lines, and a backslash at the end of a line skips the indentation of the next.
+            let code = "\"first\nsecond \\\n    third\"";
+            let constant = pretty_read(code, &read_constant);
+            let constant = unwrap_constant(constant);
+
+            assert_eq!(
+                constant,
+                OpConstant::String(String::from("first\nsecond third"))
+            );
+        }
+
+        #[test]
+        fn raw_string() {
+            let code = r#"r"C:\path\to\file""#;
+            let constant = pretty_read(code, &read_constant);
+            let constant = unwrap_constant(constant);
+
+            assert_eq!(constant, OpConstant::RawString(r"C:\path\to\file"));
+        }
+
+        #[test]
+        fn raw_string_with_quotes() {
+            let code = r###"r#"He said "hello"."#"###;
+            let constant = pretty_read(code, &read_constant);
+            let constant = unwrap_constant(constant);
+
+            assert_eq!(constant, OpConstant::RawString(r#"He said "hello"."#));
+
+            let code = r###"r#"a"b"#"###;
+            let constant = pretty_read(code, &read_constant);
+            let constant = unwrap_constant(constant);
+
+            assert_eq!(constant, OpConstant::RawString("a\"b"));
+        }
+
+        #[test]
+        fn raw_string_never_closed() {
+            let message = constant_error_message(r###"r#"a"b"###);
+            assert!(
+                message.contains("Raw string is never closed."),
+                "{}",
+                message
+            );
+        }
+    }
+
+    mod variables {
+        use super::*;
+
+        #[test]
+        fn variable_access() {
+            let code = "bah";
+            let operation = pretty_read(code, &read_operation);
+
+            match operation {
+                NLOperation::VariableAccess(access) => {
+                    assert_eq!(access.name, "bah", "Variable had wrong name.");
+                }
+                _ => panic!("Expected variable access operation, got {:?}", operation),
+            }
+        }
+
+        #[test]
+        fn unicode_variable_access() {
+            let code = "café";
+            let operation = pretty_read(code, &read_operation);
+
+            match operation {
+                NLOperation::VariableAccess(access) => {
+                    assert_eq!(access.name, "café", "Variable had wrong name.");
+                }
+                _ => panic!("Expected variable access operation, got {:?}", operation),
+            }
+        }
+    }
+
+    mod tuples {
+        use super::*;
+
+        #[test]
+        fn tuple_empty() {
+            let code = "()";
+            let (_, tuple) = read_tuple(code).unwrap();
+
+            assert_eq!(tuple, NLOperation::Unit);
+        }
+
+        #[test]
+        fn unit_result() {
+            assert_eq!(parse_expression("( )").unwrap(), NLOperation::Unit);
+
+            let code = "fn f() { () }";
+            let file = parse_string(code, "virtual_file").unwrap();
+            let block = file.get_functions()[0].get_block().as_ref().unwrap();
+            assert_eq!(block.get_result(), Some(&NLOperation::Unit));
         }
 
         #[test]
@@ -1470,7 +3297,7 @@ mod executable_blocks {
                     assert_eq!(tuple.len(), 1, "Wrong number of items in tuple.");
                     assert_eq!(
                         tuple[0],
-                        NLOperation::Constant(OpConstant::Signed(1, NLType::I32)),
+                        NLOperation::Constant(OpConstant::Signed(1, NLType::I32, false)),
                         "Wrong value used for first value."
                     );
                 }
@@ -1488,12 +3315,12 @@ mod executable_blocks {
                     assert_eq!(tuple.len(), 2, "Wrong number of items in tuple.");
                     assert_eq!(
                         tuple[0],
-                        NLOperation::Constant(OpConstant::Signed(1, NLType::I32)),
+                        NLOperation::Constant(OpConstant::Signed(1, NLType::I32, false)),
                         "Wrong value used for first value."
                     );
                     assert_eq!(
                         tuple[1],
-                        NLOperation::Constant(OpConstant::Signed(2, NLType::I32)),
+                        NLOperation::Constant(OpConstant::Signed(2, NLType::I32, false)),
                         "Wrong value used for second value."
                     );
                 }
@@ -1511,17 +3338,17 @@ mod executable_blocks {
                     assert_eq!(tuple.len(), 3, "Wrong number of items in tuple.");
                     assert_eq!(
                         tuple[0],
-                        NLOperation::Constant(OpConstant::Signed(1, NLType::I32)),
+                        NLOperation::Constant(OpConstant::Signed(1, NLType::I32, false)),
                         "Wrong value used for first value."
                     );
                     assert_eq!(
                         tuple[1],
-                        NLOperation::Constant(OpConstant::Signed(2, NLType::I32)),
+                        NLOperation::Constant(OpConstant::Signed(2, NLType::I32, false)),
                         "Wrong value used for second value."
                     );
                     assert_eq!(
                         tuple[2],
-                        NLOperation::Constant(OpConstant::Signed(3, NLType::I32)),
+                        NLOperation::Constant(OpConstant::Signed(3, NLType::I32, false)),
                         "Wrong value used for third value."
                     );
                 }
@@ -1554,7 +3381,11 @@ mod executable_blocks {
 
                     assert_eq!(
                         assign.assignment,
-                        Box::new(NLOperation::Constant(OpConstant::Signed(5, NLType::I32))),
+                        Box::new(NLOperation::Constant(OpConstant::Signed(
+                            5,
+                            NLType::I32,
+                            false
+                        ))),
                         "Wrong assignment."
                     );
 
@@ -1587,7 +3418,11 @@ mod executable_blocks {
 
                     assert_eq!(
                         assign.assignment,
-                        Box::new(NLOperation::Constant(OpConstant::Signed(5, NLType::I32))),
+                        Box::new(NLOperation::Constant(OpConstant::Signed(
+                            5,
+                            NLType::I32,
+                            false
+                        ))),
                         "Wrong assignment."
                     );
 
@@ -1623,7 +3458,11 @@ mod executable_blocks {
 
                     assert_eq!(
                         assign.assignment,
-                        Box::new(NLOperation::Constant(OpConstant::Signed(5, NLType::I32))),
+                        Box::new(NLOperation::Constant(OpConstant::Signed(
+                            5,
+                            NLType::I32,
+                            false
+                        ))),
                         "Wrong assignment."
                     );
 
@@ -1656,7 +3495,11 @@ mod executable_blocks {
 
                     assert_eq!(
                         assign.assignment,
-                        Box::new(NLOperation::Constant(OpConstant::Signed(5, NLType::I32))),
+                        Box::new(NLOperation::Constant(OpConstant::Signed(
+                            5,
+                            NLType::I32,
+                            false
+                        ))),
                         "Wrong assignment."
                     );
 
@@ -1693,8 +3536,8 @@ mod executable_blocks {
                     assert_eq!(
                         assign.assignment,
                         Box::new(NLOperation::Tuple(vec![
-                            NLOperation::Constant(OpConstant::Signed(4, NLType::I32)),
-                            NLOperation::Constant(OpConstant::Signed(5, NLType::I32))
+                            NLOperation::Constant(OpConstant::Signed(4, NLType::I32, false)),
+                            NLOperation::Constant(OpConstant::Signed(5, NLType::I32, false))
                         ])),
                         "Wrong assignment."
                     );
@@ -1731,8 +3574,8 @@ mod executable_blocks {
                     assert_eq!(
                         assign.assignment,
                         Box::new(NLOperation::Tuple(vec![
-                            NLOperation::Constant(OpConstant::Signed(4, NLType::I32)),
-                            NLOperation::Constant(OpConstant::Signed(5, NLType::I32))
+                            NLOperation::Constant(OpConstant::Signed(4, NLType::I32, false)),
+                            NLOperation::Constant(OpConstant::Signed(5, NLType::I32, false))
                         ])),
                         "Wrong assignment."
                     );
@@ -1774,7 +3617,11 @@ mod executable_blocks {
 
                     assert_eq!(
                         assign.assignment,
-                        Box::new(NLOperation::Constant(OpConstant::Signed(5, NLType::I32))),
+                        Box::new(NLOperation::Constant(OpConstant::Signed(
+                            5,
+                            NLType::I32,
+                            false
+                        ))),
                         "Wrong assignment."
                     );
 
@@ -1807,7 +3654,11 @@ mod executable_blocks {
 
                     assert_eq!(
                         assign.assignment,
-                        Box::new(NLOperation::Constant(OpConstant::Signed(5, NLType::I32))),
+                        Box::new(NLOperation::Constant(OpConstant::Signed(
+                            5,
+                            NLType::I32,
+                            false
+                        ))),
                         "Wrong assignment."
                     );
 
@@ -1821,6 +3672,114 @@ mod executable_blocks {
                 _ => panic!("Expected assignment operation."),
             };
         }
+        #[test]
+        fn destructure_struct() {
+            let code = "let Point { x, y } = p;";
+            let (_, operation) = read_assignment(code).unwrap();
+            let assign = unwrap_to!(operation => NLOperation::Assign);
+
+            assert_eq!(assign.is_new, true, "Assignment should have been new.");
+
+            let pattern = assign.struct_pattern.as_ref().unwrap();
+            assert_eq!(pattern.nl_struct, "Point", "Wrong struct name.");
+            assert_eq!(pattern.has_rest, false, "Pattern should not have a rest.");
+
+            assert_eq!(
+                assign.to_assign.len(),
+                2,
+                "Wrong number of values being assigned."
+            );
+            assert_eq!(
+                assign.to_assign[0].name, "x",
+                "Wrong name given to variable."
+            );
+            assert_eq!(
+                assign.to_assign[1].name, "y",
+                "Wrong name given to variable."
+            );
+
+            assert_eq!(
+                unwrap_to!(*assign.assignment => NLOperation::VariableAccess).get_name(),
+                "p",
+                "Wrong assignment."
+            );
+        }
+
+        #[test]
+        fn destructure_struct_with_rest() {
+            let code = "let Point { x, .. } = p;";
+            let (_, operation) = read_assignment(code).unwrap();
+            let assign = unwrap_to!(operation => NLOperation::Assign);
+
+            let pattern = assign.struct_pattern.as_ref().unwrap();
+            assert_eq!(pattern.nl_struct, "Point", "Wrong struct name.");
+            assert_eq!(pattern.has_rest, true, "Pattern should have a rest.");
+
+            assert_eq!(
+                assign.to_assign.len(),
+                1,
+                "Wrong number of values being assigned."
+            );
+            assert_eq!(
+                assign.to_assign[0].name, "x",
+                "Wrong name given to variable."
+            );
+        }
+
+        #[test]
+        fn add_assign() {
+            let code = "x += 1";
+            let (_, operation) = read_assignment(code).unwrap();
+            let assign = unwrap_to!(operation => NLOperation::Assign);
+
+            assert_eq!(assign.is_new, false, "Assignment should not have been new.");
+            assert_eq!(
+                assign.to_assign[0].name, "x",
+                "Wrong name given to variable."
+            );
+
+            let operator = unwrap_to!(*assign.assignment => NLOperation::Operator);
+            let (a, b) = unwrap_to!(operator => OpOperator::ArithmeticAdd);
+            assert_eq!(unwrap_to!(**a => NLOperation::VariableAccess).name, "x");
+            assert_eq!(unwrap_constant_signed(b), 1, "Wrong value for constant.");
+        }
+
+        #[test]
+        fn compound_assign_operators() {
+            let code = "x <<= 2";
+            let (_, operation) = read_assignment(code).unwrap();
+            let assign = unwrap_to!(operation => NLOperation::Assign);
+            let operator = unwrap_to!(*assign.assignment => NLOperation::Operator);
+            unwrap_to!(operator => OpOperator::BitLeftShift);
+
+            let code = "x ^= y";
+            let (_, operation) = read_assignment(code).unwrap();
+            let assign = unwrap_to!(operation => NLOperation::Assign);
+            let operator = unwrap_to!(*assign.assignment => NLOperation::Operator);
+            unwrap_to!(operator => OpOperator::BitXor);
+
+            // Comparisons are not assignments.
+            let code = "x <= y";
+            let operation = pretty_read(code, &read_operation);
+            let operator = unwrap_to!(operation => NLOperation::Operator);
+            unwrap_to!(operator => OpOperator::CompareLessEqual);
+        }
+
+        #[test]
+        fn compound_assign_new_variable() {
+            let code = "let x += 1";
+            match read_assignment(code) {
+                Err(nom::Err::Failure(error)) => {
+                    let message = describe_error(code, error);
+                    assert!(
+                        message.contains("Compound assignments can't declare a new variable."),
+                        "{}",
+                        message
+                    );
+                }
+                _ => panic!("Compound assignments should not declare variables."),
+            }
+        }
     }
 
     mod operators {
@@ -1945,7 +3904,41 @@ mod executable_blocks {
                 );
             }
 
-            // TODO add tests for proper error messages in =< and => conditions.
+            fn assert_mistake(code: &str, expected: &str) {
+                match parse_expression(code) {
+                    Err(error) => assert!(error.message.contains(expected), "{}", error),
+                    Ok(operation) => panic!("Expected {} to be rejected: {:?}", code, operation),
+                }
+            }
+
+            #[test]
+            fn backwards_less_equal() {
+                assert_mistake("2 =< 3", "Did you mean `<=`?");
+                assert_mistake("if a =< b { 1 }", "Did you mean `<=`?");
+            }
+
+            #[test]
+            fn backwards_greater_equal() {
+                assert_mistake("2 => 3", "Did you mean `>=`?");
+                assert_mistake("if a => b { 1 }", "Did you mean `>=`?");
+
+                // The arrow after a match guard is fine, but not one inside of the guard's own brackets.
+                parse_expression("match x { 5 if a => 1 }").unwrap();
+                assert_mistake("match x { 5 if (a => b) => 1 }", "Did you mean `>=`?");
+            }
+
+            #[test]
+            fn spaced_arrows() {
+                assert_mistake("match x { 5 = > 1 }", "Match branches use `=>`");
+
+                let code = "fn test() - > i32 { 5 }";
+                let error = parse_string_spanned(code, "virtual_file").err().unwrap();
+                assert!(
+                    error.message.contains("Return types are given with `->`"),
+                    "{}",
+                    error
+                );
+            }
         }
 
         mod logical {
@@ -2178,8 +4171,93 @@ mod executable_blocks {
             }
         }
 
+        mod adjacent {
+            use super::*;
+
+            #[test]
+            fn mul_negate() {
+                let code = "a * -b";
+                let operation = pretty_read(code, &read_operation);
+                let operator = unwrap_to!(operation => NLOperation::Operator);
+                let (a, b) = unwrap_to!(operator => OpOperator::ArithmeticMul);
+
+                assert_eq!(unwrap_to!(**a => NLOperation::VariableAccess).name, "a");
+
+                let operator = unwrap_to!(**b => NLOperation::Operator);
+                let b = unwrap_to!(operator => OpOperator::ArithmeticNegate);
+                assert_eq!(unwrap_to!(**b => NLOperation::VariableAccess).name, "b");
+            }
+
+            #[test]
+            fn mul_negate_no_spaces() {
+                let code = "a*-b";
+                let operation = pretty_read(code, &read_operation);
+                let operator = unwrap_to!(operation => NLOperation::Operator);
+                let (a, b) = unwrap_to!(operator => OpOperator::ArithmeticMul);
+
+                assert_eq!(unwrap_to!(**a => NLOperation::VariableAccess).name, "a");
+
+                let operator = unwrap_to!(**b => NLOperation::Operator);
+                let b = unwrap_to!(operator => OpOperator::ArithmeticNegate);
+                assert_eq!(unwrap_to!(**b => NLOperation::VariableAccess).name, "b");
+            }
+
+            #[test]
+            fn sub_negative() {
+                let code = "1 - -2";
+                let operation = pretty_read(code, &read_operation);
+                let operator = unwrap_to!(operation => NLOperation::Operator);
+                let (a, b) = unwrap_to!(operator => OpOperator::ArithmeticSub);
+
+                assert_eq!(unwrap_constant_signed(a), 1, "Wrong value for constant.");
+                assert_eq!(unwrap_constant_signed(b), -2, "Wrong value for constant.");
+            }
+
+            #[test]
+            fn negate_add() {
+                let code = "-a + b";
+                let operation = pretty_read(code, &read_operation);
+                let operator = unwrap_to!(operation => NLOperation::Operator);
+                let (a, b) = unwrap_to!(operator => OpOperator::ArithmeticAdd);
+
+                let operator = unwrap_to!(**a => NLOperation::Operator);
+                let a = unwrap_to!(operator => OpOperator::ArithmeticNegate);
+                assert_eq!(unwrap_to!(**a => NLOperation::VariableAccess).name, "a");
+
+                assert_eq!(unwrap_to!(**b => NLOperation::VariableAccess).name, "b");
+            }
+
+            #[test]
+            fn and_not() {
+                let code = "x && !y";
+                let operation = pretty_read(code, &read_operation);
+                let operator = unwrap_to!(operation => NLOperation::Operator);
+                let (a, b) = unwrap_to!(operator => OpOperator::LogicalAnd);
+
+                assert_eq!(unwrap_to!(**a => NLOperation::VariableAccess).name, "x");
+
+                let operator = unwrap_to!(**b => NLOperation::Operator);
+                let b = unwrap_to!(operator => OpOperator::LogicalNegate);
+                assert_eq!(unwrap_to!(**b => NLOperation::VariableAccess).name, "y");
+            }
+        }
+
         mod precedence {
             use super::*;
+
+            #[test]
+            fn long_chain_is_linear() {
+                // Every operand should only be read once, no matter how long the chain is.
+                for length in [10, 100, 1000].iter() {
+                    let chain = vec!["1"; *length].join(" + ");
+                    let code = format!("fn test() -> i32 {{ {} }}", chain);
+                    let (_, stats) = parse_string_with_stats(&code, "virtual_file").unwrap();
+
+                    // The block makes one more attempt at reading an operation when it reaches its `}`.
+                    assert_eq!(stats.read_expression, 2, "{:?}", stats);
+                    assert_eq!(stats.read_sub_operation, *length + 1, "{:?}", stats);
+                }
+            }
             #[test]
             fn mul_div_mod() {
                 let code = "{ 1 % 2 / 3 * 4 }";
@@ -2331,7 +4409,6 @@ mod executable_blocks {
 
             let condition = unwrap_constant_boolean(&statement.condition);
             let true_block = &statement.true_block;
-            let false_block = &statement.false_block;
 
             assert_eq!(condition, true, "Wrong condition value read.");
             assert_eq!(
@@ -2344,11 +4421,7 @@ mod executable_blocks {
                 false,
                 "Expected a false boolean in the true block."
             );
-            assert_eq!(
-                false_block.operations.len(),
-                0,
-                "Wrong number of operations in false block."
-            );
+            assert_eq!(statement.false_block, None, "Expected no false block.");
         }
 
         #[test]
@@ -2359,7 +4432,7 @@ mod executable_blocks {
 
             let condition = unwrap_constant_boolean(&statement.condition);
             let true_block = &statement.true_block;
-            let false_block = &statement.false_block;
+            let false_block = statement.false_block.as_ref().unwrap();
 
             assert_eq!(condition, true, "Wrong condition value read.");
             assert_eq!(
@@ -2399,6 +4472,77 @@ mod executable_blocks {
             assert_eq!(op_a, true, "Expected true for op_a");
             assert_eq!(op_b, false, "Expected true for ob_b");
         }
+
+        #[test]
+        fn accessors() {
+            let code = "if a { 1 } else { 2 3 }";
+            let operation = pretty_read(code, &read_operation);
+            let statement = unwrap_to!(operation => NLOperation::If);
+
+            let condition = unwrap_to!(statement.get_condition() => NLOperation::VariableAccess);
+            assert_eq!(condition.get_name(), "a");
+            assert_eq!(statement.get_true_block().get_operations().len(), 1);
+            assert_eq!(
+                statement.get_false_block().unwrap().get_operations().len(),
+                2
+            );
+            assert!(statement.has_else_block(), "Expected an else block.");
+
+            let code = "if a { 1 }";
+            let operation = pretty_read(code, &read_operation);
+            let statement = unwrap_to!(operation => NLOperation::If);
+            assert!(!statement.has_else_block(), "Expected no else block.");
+
+            // An empty else block still counts.
+            let code = "if a { 1 } else {}";
+            let operation = pretty_read(code, &read_operation);
+            let statement = unwrap_to!(operation => NLOperation::If);
+            assert!(statement.has_else_block(), "Expected an else block.");
+        }
+
+        #[test]
+        fn debug_shows_structure() {
+            let code = "if a { 1 }";
+            let operation = pretty_read(code, &read_operation);
+            let statement = unwrap_to!(operation => NLOperation::If);
+
+            let debug = format!("{:?}", statement);
+            assert!(debug.starts_with("IfStatement { condition: "), "{}", debug);
+            assert!(debug.contains("false_block: None, .. }"), "{}", debug);
+
+            let code = "while a { 1 }";
+            let operation = pretty_read(code, &read_operation);
+            let debug = format!("{:?}", unwrap_to!(operation => NLOperation::WhileLoop));
+            assert!(debug.starts_with("WhileLoop { condition: "), "{}", debug);
+        }
+
+        #[test]
+        fn if_let() {
+            let code = "if let Enum::A(x) = y { x } else { 0 }";
+            let operation = pretty_read(code, &read_operation);
+            let statement = unwrap_to!(operation => NLOperation::IfLet);
+
+            assert_eq!(statement.pattern.nl_enum, "Enum");
+            assert_eq!(statement.pattern.variant, "A");
+            assert_eq!(statement.get_variables(), &vec!["x"]);
+            assert_eq!(
+                unwrap_to!(statement.get_value() => NLOperation::VariableAccess).get_name(),
+                "y"
+            );
+            assert_eq!(statement.get_true_block().get_operations().len(), 1);
+            assert_eq!(statement.get_false_block().get_operations().len(), 1);
+
+            // Variants without data don't need the brackets.
+            let code = "if let Enum::B = y {}";
+            let operation = pretty_read(code, &read_operation);
+            let statement = unwrap_to!(operation => NLOperation::IfLet);
+            assert!(statement.get_variables().is_empty());
+
+            // A variable that happens to start with `let` is still a normal condition.
+            let code = "if letters {}";
+            let operation = pretty_read(code, &read_operation);
+            assert!(matches!(operation, NLOperation::If(_)), "{:?}", operation);
+        }
     }
 
     mod loops {
@@ -2446,6 +4590,22 @@ mod executable_blocks {
             );
         }
 
+        #[test]
+        fn while_let() {
+            let code = "while let Option::Some(item, index) = next() { item }";
+            let operation = pretty_read(code, &read_operation);
+            let while_let = unwrap_to!(operation => NLOperation::WhileLet);
+
+            assert_eq!(while_let.pattern.nl_enum, "Option");
+            assert_eq!(while_let.pattern.variant, "Some");
+            assert_eq!(while_let.get_variables(), &vec!["item", "index"]);
+            assert_eq!(
+                unwrap_to!(while_let.get_value() => NLOperation::FunctionCall).get_path(),
+                "next"
+            );
+            assert_eq!(while_let.get_block().get_operations().len(), 1);
+        }
+
         #[test]
         fn while_loop_with_and() {
             let code = "while true && false { false }";
@@ -2506,13 +4666,39 @@ mod executable_blocks {
             );
         }
 
+        #[test]
+        fn while_loop_accessors() {
+            let code = "while running { a b }";
+            let operation = pretty_read(code, &read_operation);
+            let while_loop = unwrap_to!(operation => NLOperation::WhileLoop);
+
+            let condition = unwrap_to!(while_loop.get_condition() => NLOperation::VariableAccess);
+            assert_eq!(condition.get_name(), "running");
+            assert_eq!(while_loop.get_block().get_operations().len(), 2);
+        }
+
+        #[test]
+        fn for_loop_accessors() {
+            let code = "for item in items { item }";
+            let operation = pretty_read(code, &read_operation);
+            let for_loop = unwrap_to!(operation => NLOperation::ForLoop);
+
+            assert_eq!(for_loop.get_variable().get_name(), "item");
+            let iterator = unwrap_to!(for_loop.get_iterator() => NLOperation::VariableAccess);
+            assert_eq!(iterator.get_name(), "items");
+            assert_eq!(for_loop.get_block().get_operations().len(), 1);
+        }
+
         #[test]
         fn break_keyword() {
             let code = "break";
             let operation = pretty_read(code, &read_operation);
 
             match operation {
-                NLOperation::Break => {
+                NLOperation::Break {
+                    label: None,
+                    value: None,
+                } => {
                     // We pass. That's it.
                 }
                 _ => panic!("Expected break operation, got {:?}", operation),
@@ -2520,37 +4706,185 @@ mod executable_blocks {
         }
     }
 
-    mod match_statements {
+    mod control_flow {
         use super::*;
 
         #[test]
-        fn basic_match() {
-            let code = "match variable {}";
+        fn break_with_value() {
+            let code = "loop { break 5 }";
             let operation = pretty_read(code, &read_operation);
-            let nl_match = unwrap_to!(operation => NLOperation::Match);
+            let block = unwrap_to!(operation => NLOperation::Loop);
 
-            assert_eq!(
-                unwrap_to!(*nl_match.input => NLOperation::VariableAccess).get_name(),
-                "variable"
-            );
-            assert_eq!(nl_match.branches.len(), 0);
+            let value = match &block.operations[0] {
+                NLOperation::Break { label: None, value } => value,
+                operation => panic!("Expected break operation, got {:?}", operation),
+            };
+            let value = value.as_ref().expect("Expected the break to have a value.");
+            assert_eq!(unwrap_constant_signed(value), 5);
         }
 
         #[test]
-        fn one_branch() {
-            let code = "match variable { Enum::One => 0, }";
+        fn labeled_break() {
+            let code = "'outer: loop { loop { break 'outer 5 } }";
             let operation = pretty_read(code, &read_operation);
-            let nl_match = unwrap_to!(operation => NLOperation::Match);
 
-            assert_eq!(
-                unwrap_to!(*nl_match.input => NLOperation::VariableAccess).get_name(),
-                "variable"
-            );
+            let (label, operation) = match operation {
+                NLOperation::Labeled { label, operation } => (label, operation),
+                _ => panic!("Expected labeled operation, got {:?}", operation),
+            };
+            assert_eq!(label, "outer");
+
+            let outer = unwrap_to!(*operation => NLOperation::Loop);
+            let inner = unwrap_to!(outer.operations[0] => NLOperation::Loop);
+            match &inner.operations[0] {
+                NLOperation::Break {
+                    label: Some(label),
+                    value: Some(value),
+                } => {
+                    assert_eq!(*label, "outer");
+                    assert_eq!(unwrap_constant_signed(value), 5);
+                }
+                operation => panic!("Expected labeled break, got {:?}", operation),
+            }
 
-            let branches = &nl_match.branches;
-            assert_eq!(branches.len(), 1);
+            // Labels can go on any kind of loop, but only on loops.
+            pretty_read("'rows: while running { break 'rows }", &read_operation);
+            pretty_read("'items: for item in items {}", &read_operation);
+            assert!(parse_expression("'block: { 5 }").is_err());
+        }
 
-            let (branch, operation) = &branches[0];
+        #[test]
+        fn block_result() {
+            let code = "{ foo(); 5 }";
+            let block = pretty_read(code, &read_code_block_raw);
+            assert_eq!(block.operations.len(), 2, "Wrong number of operations.");
+
+            let result = block
+                .get_result()
+                .expect("Expected the block to have a value.");
+            assert_eq!(unwrap_constant_signed(result), 5);
+        }
+
+        #[test]
+        fn block_without_result() {
+            let code = "{ foo(); 5; }";
+            let block = pretty_read(code, &read_code_block_raw);
+            assert_eq!(block.operations.len(), 2, "Wrong number of operations.");
+            assert!(
+                block.get_result().is_none(),
+                "Block should not have a value."
+            );
+
+            let code = "{}";
+            let block = pretty_read(code, &read_code_block_raw);
+            assert!(
+                block.get_result().is_none(),
+                "Empty block should not have a value."
+            );
+        }
+
+        #[test]
+        fn return_keyword() {
+            let code = "return 5";
+            let operation = pretty_read(code, &read_operation);
+            let value = unwrap_to!(operation => NLOperation::Return);
+
+            match value {
+                Some(value) => assert_eq!(unwrap_constant_signed(value), 5),
+                None => panic!("Expected a value to be returned."),
+            }
+        }
+
+        #[test]
+        fn return_prefixed_names() {
+            let code = "return_value = 5";
+            let operation = pretty_read(code, &read_operation);
+            let assignment = unwrap_to!(operation => NLOperation::Assign);
+            assert_eq!(assignment.to_assign[0].name, "return_value");
+
+            let code = "returned(x)";
+            let operation = pretty_read(code, &read_operation);
+            let function = unwrap_to!(operation => NLOperation::FunctionCall);
+            assert_eq!(function.path, "returned");
+        }
+
+        #[test]
+        fn ends_in_return() {
+            let code = "{ a = 5 return a }";
+            let block = pretty_read(code, &read_code_block_raw);
+
+            assert!(block_always_diverges(&block), "Block should diverge.");
+        }
+
+        #[test]
+        fn ends_in_expression() {
+            let code = "{ a = 5 a }";
+            let block = pretty_read(code, &read_code_block_raw);
+
+            assert!(!block_always_diverges(&block), "Block should not diverge.");
+        }
+
+        #[test]
+        fn infinite_loop() {
+            let code = "{ loop { if true { loop { break } } } }";
+            let block = pretty_read(code, &read_code_block_raw);
+
+            assert!(block_always_diverges(&block), "Block should diverge.");
+        }
+
+        #[test]
+        fn loop_with_break() {
+            let code = "{ loop { if true { break } } }";
+            let block = pretty_read(code, &read_code_block_raw);
+
+            assert!(!block_always_diverges(&block), "Block should not diverge.");
+        }
+
+        #[test]
+        fn loop_with_labeled_break() {
+            // Breaking out of the outer loop from an inner one still exits it.
+            let code = "{ 'outer: loop { while running { break 'outer } } }";
+            let block = pretty_read(code, &read_code_block_raw);
+            assert!(!block_always_diverges(&block), "Block should not diverge.");
+
+            // But breaking out of the inner loop by its label doesn't.
+            let code = "{ loop { 'inner: loop { loop { break 'inner } } } }";
+            let block = pretty_read(code, &read_code_block_raw);
+            assert!(block_always_diverges(&block), "Block should diverge.");
+        }
+    }
+
+    mod match_statements {
+        use super::*;
+
+        #[test]
+        fn basic_match() {
+            let code = "match variable {}";
+            let operation = pretty_read(code, &read_operation);
+            let nl_match = unwrap_to!(operation => NLOperation::Match);
+
+            assert_eq!(
+                unwrap_to!(*nl_match.input => NLOperation::VariableAccess).get_name(),
+                "variable"
+            );
+            assert_eq!(nl_match.branches.len(), 0);
+        }
+
+        #[test]
+        fn one_branch() {
+            let code = "match variable { Enum::One => 0, }";
+            let operation = pretty_read(code, &read_operation);
+            let nl_match = unwrap_to!(operation => NLOperation::Match);
+
+            assert_eq!(
+                unwrap_to!(*nl_match.input => NLOperation::VariableAccess).get_name(),
+                "variable"
+            );
+
+            let branches = &nl_match.branches;
+            assert_eq!(branches.len(), 1);
+
+            let (branch, _, operation) = &branches[0];
             let branch = unwrap_to!(branch => MatchBranch::Enum);
             assert_eq!(branch.nl_enum, "Enum");
             assert_eq!(branch.variant, "One");
@@ -2574,7 +4908,7 @@ mod executable_blocks {
             let branches = &nl_match.branches;
             assert_eq!(branches.len(), 1);
 
-            let (branch, operation) = &branches[0];
+            let (branch, _, operation) = &branches[0];
             let branch = unwrap_to!(branch => MatchBranch::Enum);
             assert_eq!(branch.nl_enum, "Enum");
             assert_eq!(branch.variant, "One");
@@ -2598,7 +4932,7 @@ mod executable_blocks {
             let branches = &nl_match.branches;
             assert_eq!(branches.len(), 1);
 
-            let (branch, operation) = &branches[0];
+            let (branch, _, operation) = &branches[0];
             let branch = unwrap_to!(branch => MatchBranch::Enum);
             assert_eq!(branch.nl_enum, "Enum");
             assert_eq!(branch.variant, "One");
@@ -2624,7 +4958,7 @@ mod executable_blocks {
             let branches = &nl_match.branches;
             assert_eq!(branches.len(), 1);
 
-            let (branch, operation) = &branches[0];
+            let (branch, _, operation) = &branches[0];
             let branch = unwrap_to!(branch => MatchBranch::Enum);
             assert_eq!(branch.nl_enum, "Enum");
             assert_eq!(branch.variant, "One");
@@ -2651,7 +4985,7 @@ mod executable_blocks {
             let branches = &nl_match.branches;
             assert_eq!(branches.len(), 2);
 
-            let (branch, operation) = &branches[0];
+            let (branch, _, operation) = &branches[0];
             let branch = unwrap_to!(branch => MatchBranch::Enum);
             assert_eq!(branch.nl_enum, "Enum");
             assert_eq!(branch.variant, "One");
@@ -2660,7 +4994,7 @@ mod executable_blocks {
 
             assert_eq!(branch.variables.len(), 0);
 
-            let (branch, operation) = &branches[1];
+            let (branch, _, operation) = &branches[1];
             let branch = unwrap_to!(branch => MatchBranch::Enum);
             assert_eq!(branch.nl_enum, "Enum");
             assert_eq!(branch.variant, "Two");
@@ -2684,7 +5018,7 @@ mod executable_blocks {
             let branches = &nl_match.branches;
             assert_eq!(branches.len(), 2);
 
-            let (branch, operation) = &branches[0];
+            let (branch, _, operation) = &branches[0];
             let branch = unwrap_to!(branch => MatchBranch::Enum);
             assert_eq!(branch.nl_enum, "Enum");
             assert_eq!(branch.variant, "One");
@@ -2693,7 +5027,7 @@ mod executable_blocks {
 
             assert_eq!(branch.variables.len(), 0);
 
-            let (branch, operation) = &branches[1];
+            let (branch, _, operation) = &branches[1];
             let branch = unwrap_to!(branch => MatchBranch::Enum);
             assert_eq!(branch.nl_enum, "Enum");
             assert_eq!(branch.variant, "Two");
@@ -2717,10 +5051,11 @@ mod executable_blocks {
             let branches = &nl_match.branches;
             assert_eq!(branches.len(), 1);
 
-            let (branch, operation) = &branches[0];
-            let branch = unwrap_to!(branch => MatchBranch::Constant);
+            let (branch, _, operation) = &branches[0];
+            let (binding, branch) = unwrap_to!(branch => MatchBranch::Constant);
+            assert_eq!(*binding, None);
             match branch {
-                OpConstant::Signed(value, _) => {
+                OpConstant::Signed(value, _, _) => {
                     assert_eq!(*value, 42);
                 }
                 _ => {
@@ -2746,14 +5081,354 @@ mod executable_blocks {
             let branches = &nl_match.branches;
             assert_eq!(branches.len(), 1);
 
-            let (branch, operation) = &branches[0];
-            let (low, high) = unwrap_to!(branch => MatchBranch::Range);
+            let (branch, _, operation) = &branches[0];
+            let (binding, low, high) = unwrap_to!(branch => MatchBranch::Range);
+            assert_eq!(*binding, None);
 
             assert_eq!(*low, 25);
             assert_eq!(*high, 42);
 
             assert_eq!(unwrap_constant_signed(operation), 0);
         }
+
+        #[test]
+        fn negative_and_hexadecimal_ranges() {
+            let code = "match variable { -5..5 => 0, 0x00..0xFF => 1, -0b10..0o7 => 2 }";
+            let operation = pretty_read(code, &read_operation);
+            let nl_match = unwrap_to!(operation => NLOperation::Match);
+
+            let bounds: Vec<(i128, i128)> = nl_match
+                .branches
+                .iter()
+                .map(|(branch, _, _)| {
+                    let (_, low, high) = unwrap_to!(branch => MatchBranch::Range);
+                    (*low, *high)
+                })
+                .collect();
+            assert_eq!(bounds, vec![(-5, 5), (0, 255), (-2, 7)]);
+        }
+
+        #[test]
+        fn range_bounds_must_be_literals() {
+            let codes = ["match variable { a..b => 0 }", "match variable { 0..b => 0 }"];
+            for code in codes.iter() {
+                match read_operation(code) {
+                    Err(nom::Err::Failure(error)) => {
+                        let message = describe_error(code, error);
+                        assert!(message.contains("must be integer literals"), "{}", message);
+                    }
+                    _ => panic!("Expected {} to be rejected.", code),
+                }
+            }
+        }
+
+        #[test]
+        fn negative_and_typed_constant_branches() {
+            // Without spaces, the `-` and the suffix shouldn't get mixed up with the `=>`.
+            for code in [
+                "match x { -1 => a, 2i64 => b }",
+                "match x { -1=>a, 2i64=>b }",
+            ]
+            .iter()
+            {
+                let operation = pretty_read(code, &read_operation);
+                let nl_match = unwrap_to!(operation => NLOperation::Match);
+
+                let constants: Vec<&OpConstant> = nl_match
+                    .branches
+                    .iter()
+                    .map(|(branch, _, _)| &unwrap_to!(branch => MatchBranch::Constant).1)
+                    .collect();
+                assert_eq!(
+                    constants,
+                    vec![
+                        &OpConstant::Signed(-1, NLType::I32, false),
+                        &OpConstant::Signed(2, NLType::I64, true)
+                    ]
+                );
+            }
+        }
+
+        #[test]
+        fn string_constant_branches() {
+            let code = r#"match x { "foo" => a, r"bar"=>b }"#;
+            let operation = pretty_read(code, &read_operation);
+            let nl_match = unwrap_to!(operation => NLOperation::Match);
+
+            let constants: Vec<&OpConstant> = nl_match
+                .branches
+                .iter()
+                .map(|(branch, _, _)| &unwrap_to!(branch => MatchBranch::Constant).1)
+                .collect();
+            assert_eq!(
+                constants,
+                vec![
+                    &OpConstant::String(String::from("foo")),
+                    &OpConstant::RawString("bar")
+                ]
+            );
+        }
+
+        #[test]
+        fn constant_branch_binding() {
+            let code = "match variable { n @ 5 => n }";
+            let operation = pretty_read(code, &read_operation);
+            let nl_match = unwrap_to!(operation => NLOperation::Match);
+
+            let (branch, _, operation) = &nl_match.branches[0];
+            let (binding, constant) = unwrap_to!(branch => MatchBranch::Constant);
+            assert_eq!(*binding, Some("n"));
+            assert_eq!(*constant, OpConstant::Signed(5, NLType::I32, false));
+            assert_eq!(
+                unwrap_to!(operation => NLOperation::VariableAccess).get_name(),
+                "n"
+            );
+        }
+
+        #[test]
+        fn range_branch_binding() {
+            let code = "match variable { n @ 0..10 => n }";
+            let operation = pretty_read(code, &read_operation);
+            let nl_match = unwrap_to!(operation => NLOperation::Match);
+
+            let (branch, _, operation) = &nl_match.branches[0];
+            let (binding, low, high) = unwrap_to!(branch => MatchBranch::Range);
+            assert_eq!(*binding, Some("n"));
+            assert_eq!(*low, 0);
+            assert_eq!(*high, 10);
+            assert_eq!(
+                unwrap_to!(operation => NLOperation::VariableAccess).get_name(),
+                "n"
+            );
+        }
+
+        #[test]
+        fn tuple_branch() {
+            let code = "match pair { (0, 1) => a }";
+            let operation = pretty_read(code, &read_operation);
+            let nl_match = unwrap_to!(operation => NLOperation::Match);
+
+            let (branch, _, operation) = &nl_match.branches[0];
+            let elements = unwrap_to!(branch => MatchBranch::Tuple);
+            assert_eq!(
+                *elements,
+                vec![
+                    MatchBranch::Constant((None, OpConstant::Signed(0, NLType::I32, false))),
+                    MatchBranch::Constant((None, OpConstant::Signed(1, NLType::I32, false))),
+                ]
+            );
+            assert_eq!(
+                unwrap_to!(operation => NLOperation::VariableAccess).get_name(),
+                "a"
+            );
+        }
+
+        #[test]
+        fn tuple_branch_wildcard_and_binding() {
+            let code = "match pair { (_, y) => y, (0..10, (_, 2)) => 3 }";
+            let operation = pretty_read(code, &read_operation);
+            let nl_match = unwrap_to!(operation => NLOperation::Match);
+            assert_eq!(nl_match.branches.len(), 2);
+
+            let (branch, _, operation) = &nl_match.branches[0];
+            let elements = unwrap_to!(branch => MatchBranch::Tuple);
+            assert_eq!(
+                *elements,
+                vec![MatchBranch::AllOther, MatchBranch::Binding("y")]
+            );
+            assert_eq!(
+                unwrap_to!(operation => NLOperation::VariableAccess).get_name(),
+                "y"
+            );
+
+            // Tuple patterns can be nested.
+            let (branch, _, _) = &nl_match.branches[1];
+            let elements = unwrap_to!(branch => MatchBranch::Tuple);
+            assert_eq!(
+                *elements,
+                vec![
+                    MatchBranch::Range((None, 0, 10)),
+                    MatchBranch::Tuple(vec![
+                        MatchBranch::AllOther,
+                        MatchBranch::Constant((None, OpConstant::Signed(2, NLType::I32, false))),
+                    ]),
+                ]
+            );
+        }
+
+        #[test]
+        fn tuple_scrutinee() {
+            let code = "match (a, b) { (0, y) => y, (x, 0) => x, _ => 0 }";
+            let operation = pretty_read(code, &read_operation);
+            let nl_match = unwrap_to!(operation => NLOperation::Match);
+
+            let scrutinee = unwrap_to!(*nl_match.input => NLOperation::Tuple);
+            assert_eq!(scrutinee.len(), 2);
+            assert_eq!(
+                unwrap_to!(scrutinee[0] => NLOperation::VariableAccess).get_name(),
+                "a"
+            );
+            assert_eq!(
+                unwrap_to!(scrutinee[1] => NLOperation::VariableAccess).get_name(),
+                "b"
+            );
+
+            let branches: Vec<&MatchBranch> = nl_match
+                .branches
+                .iter()
+                .map(|(branch, _, _)| branch)
+                .collect();
+            assert_eq!(
+                branches,
+                vec![
+                    &MatchBranch::Tuple(vec![
+                        MatchBranch::Constant((None, OpConstant::Signed(0, NLType::I32, false))),
+                        MatchBranch::Binding("y"),
+                    ]),
+                    &MatchBranch::Tuple(vec![
+                        MatchBranch::Binding("x"),
+                        MatchBranch::Constant((None, OpConstant::Signed(0, NLType::I32, false))),
+                    ]),
+                    &MatchBranch::AllOther,
+                ]
+            );
+        }
+
+        #[test]
+        fn constant_branch_guard() {
+            let code = "match variable { 5 if other > 0 => 1, 6 => 2 }";
+            let operation = pretty_read(code, &read_operation);
+            let nl_match = unwrap_to!(operation => NLOperation::Match);
+
+            let branches = &nl_match.branches;
+            assert_eq!(branches.len(), 2);
+
+            let (_, guard, operation) = &branches[0];
+            let guard = guard.as_ref().expect("Expected a guard.");
+            let operator = unwrap_to!(guard => NLOperation::Operator);
+            let (left, right) = unwrap_to!(operator => OpOperator::CompareGreater);
+            assert_eq!(
+                unwrap_to!(**left => NLOperation::VariableAccess).get_name(),
+                "other"
+            );
+            assert_eq!(unwrap_constant_signed(right), 0);
+            assert_eq!(unwrap_constant_signed(operation), 1);
+
+            let (_, guard, _) = &branches[1];
+            assert!(guard.is_none(), "Second branch should not have a guard.");
+        }
+
+        #[test]
+        fn block_branch_without_comma() {
+            let code = "match variable { Enum::One => { foo(); bar() } Enum::Two => 1 }";
+            let operation = pretty_read(code, &read_operation);
+            let nl_match = unwrap_to!(operation => NLOperation::Match);
+
+            let branches = &nl_match.branches;
+            assert_eq!(branches.len(), 2);
+
+            let (_, _, operation) = &branches[0];
+            let block = unwrap_to!(operation => NLOperation::Block);
+            assert_eq!(block.get_operations().len(), 2);
+            assert!(block.get_result().is_some());
+
+            let (branch, _, operation) = &branches[1];
+            assert_eq!(unwrap_to!(branch => MatchBranch::Enum).variant, "Two");
+            assert_eq!(unwrap_constant_signed(operation), 1);
+
+            // The comma is still allowed after a block.
+            let code = "match variable { 5 => { a }, 6 => b }";
+            let operation = pretty_read(code, &read_operation);
+            let nl_match = unwrap_to!(operation => NLOperation::Match);
+            assert_eq!(nl_match.branches.len(), 2);
+        }
+
+        #[test]
+        fn expression_branch_needs_comma() {
+            let code = "match variable { Enum::One => a Enum::Two => b }";
+            match read_operation(code) {
+                Err(nom::Err::Failure(error)) => {
+                    let message = describe_error(code, error);
+                    assert!(message.contains("Expected a `,`"), "{}", message);
+                }
+                _ => panic!("Expected the missing comma to be rejected."),
+            }
+        }
+
+        #[test]
+        fn enum_branch_guard() {
+            let code = "match variable { Enum::One(a) if a => a }";
+            let operation = pretty_read(code, &read_operation);
+            let nl_match = unwrap_to!(operation => NLOperation::Match);
+
+            let branches = &nl_match.branches;
+            assert_eq!(branches.len(), 1);
+
+            let (branch, guard, operation) = &branches[0];
+            let branch = unwrap_to!(branch => MatchBranch::Enum);
+            assert_eq!(branch.variables, vec!["a"]);
+
+            let guard = guard.as_ref().expect("Expected a guard.");
+            assert_eq!(
+                unwrap_to!(guard => NLOperation::VariableAccess).get_name(),
+                "a"
+            );
+            assert_eq!(
+                unwrap_to!(operation => NLOperation::VariableAccess).get_name(),
+                "a"
+            );
+        }
+    }
+
+    mod arrays {
+        use super::*;
+
+        #[test]
+        fn array_literal() {
+            let code = "[1, 2, 3]";
+            let operation = pretty_read(code, &read_operation);
+            let elements = unwrap_to!(operation => NLOperation::ArrayLiteral);
+
+            assert_eq!(elements.len(), 3);
+            assert_eq!(unwrap_constant_signed(&elements[0]), 1);
+            assert_eq!(unwrap_constant_signed(&elements[2]), 3);
+        }
+
+        #[test]
+        fn empty_array_literal() {
+            let code = "[ ]";
+            let operation = pretty_read(code, &read_operation);
+            let elements = unwrap_to!(operation => NLOperation::ArrayLiteral);
+
+            assert!(elements.is_empty());
+        }
+
+        #[test]
+        fn nested_array_literal() {
+            let code = "[[a, b], [c + 1], ]";
+            let operation = pretty_read(code, &read_operation);
+            let elements = unwrap_to!(operation => NLOperation::ArrayLiteral);
+
+            assert_eq!(elements.len(), 2);
+            assert_eq!(
+                unwrap_to!(elements[0] => NLOperation::ArrayLiteral).len(),
+                2
+            );
+        }
+
+        #[test]
+        fn array_repeat() {
+            let code = "[0; 8]";
+            let operation = pretty_read(code, &read_operation);
+
+            match operation {
+                NLOperation::ArrayRepeat { value, count } => {
+                    assert_eq!(unwrap_constant_signed(&value), 0);
+                    assert_eq!(unwrap_constant_signed(&count), 8);
+                }
+                _ => panic!("Expected a repeated array."),
+            }
+        }
     }
 
     mod function_calls {
@@ -2789,7 +5464,10 @@ mod executable_blocks {
 
             let arguments = &function.arguments;
             assert_eq!(arguments.len(), 1);
-            assert_eq!(arguments[0], "one");
+            assert_eq!(
+                unwrap_to!(arguments[0] => NLOperation::VariableAccess).name,
+                "one"
+            );
         }
 
         #[test]
@@ -2803,8 +5481,866 @@ mod executable_blocks {
             let arguments = &function.arguments;
 
             assert_eq!(arguments.len(), 2);
-            assert_eq!(arguments[0], "one");
-            assert_eq!(arguments[1], "two");
+            assert_eq!(
+                unwrap_to!(arguments[0] => NLOperation::VariableAccess).name,
+                "one"
+            );
+            assert_eq!(
+                unwrap_to!(arguments[1] => NLOperation::VariableAccess).name,
+                "two"
+            );
+        }
+
+        #[test]
+        fn call_expression_args() {
+            let code = "function(2, a + 1, other(3))";
+            let operation = pretty_read(code, &read_operation);
+            let function = unwrap_to!(operation => NLOperation::FunctionCall);
+
+            let arguments = &function.arguments;
+            assert_eq!(arguments.len(), 3);
+            assert_eq!(unwrap_constant_signed(&arguments[0]), 2);
+
+            let operator = unwrap_to!(arguments[1] => NLOperation::Operator);
+            unwrap_to!(operator => OpOperator::ArithmeticAdd);
+
+            let inner = unwrap_to!(arguments[2] => NLOperation::FunctionCall);
+            assert_eq!(inner.path, "other");
+            assert_eq!(inner.arguments.len(), 1);
+        }
+    }
+
+    mod radix_operands {
+        use super::*;
+
+        fn operands(code: &str) -> (i64, i64) {
+            let operation = pretty_read(code, &read_operation);
+            let operator = unwrap_to!(operation => NLOperation::Operator);
+            let (a, b) = match operator {
+                OpOperator::BitAnd(operands) | OpOperator::ArithmeticAdd(operands) => operands,
+                OpOperator::BitLeftShift(operands) | OpOperator::ArithmeticSub(operands) => {
+                    operands
+                }
+                _ => panic!("Unexpected operator: {:?}", operator),
+            };
+
+            (unwrap_constant_signed(a), unwrap_constant_signed(b))
+        }
+
+        #[test]
+        fn hexadecimal_operands() {
+            assert_eq!(operands("0xFF & 0x0F"), (255, 15));
+            assert_eq!(operands("0x1F+0xa"), (31, 10));
         }
+
+        #[test]
+        fn mixed_radix_operands() {
+            assert_eq!(operands("0o17 + 0b1"), (15, 1));
+            assert_eq!(operands("0b1<<0o3"), (1, 3));
+            assert_eq!(operands("0b101 - 0"), (5, 0));
+            assert_eq!(operands("1-0x1"), (1, 1));
+        }
+
+        #[test]
+        fn digit_outside_of_radix() {
+            for (code, expected) in [
+                ("0b12", "The digit 2 can't be used in a binary number."),
+                ("0o18 + 1", "The digit 8 can't be used in an octal number."),
+            ]
+            .iter()
+            {
+                match read_operation(code) {
+                    Err(nom::Err::Failure(error)) => {
+                        let message = describe_error(code, error);
+                        assert!(message.contains(expected), "{}", message);
+                    }
+                    other => panic!("Expected {} to be rejected: {:?}", code, other),
+                }
+            }
+        }
+    }
+
+    mod struct_literals {
+        use super::*;
+
+        #[test]
+        fn without_base() {
+            let code = "Point { x: 1, y: a + 2, }";
+            let operation = pretty_read(code, &read_operation);
+            let literal = unwrap_to!(operation => NLOperation::StructLiteral);
+
+            assert_eq!(literal.get_struct(), "Point");
+            assert!(literal.get_base().is_none());
+
+            let fields = literal.get_fields();
+            assert_eq!(fields.len(), 2);
+            assert_eq!(fields[0].0, "x");
+            assert_eq!(unwrap_constant_signed(&fields[0].1), 1);
+            assert_eq!(fields[1].0, "y");
+
+            let code = "Empty {}";
+            let operation = pretty_read(code, &read_operation);
+            let literal = unwrap_to!(operation => NLOperation::StructLiteral);
+            assert_eq!(literal.get_fields().len(), 0);
+        }
+
+        #[test]
+        fn with_base() {
+            let code = "Point { x: 1, ..origin }";
+            let operation = pretty_read(code, &read_operation);
+            let literal = unwrap_to!(operation => NLOperation::StructLiteral);
+
+            assert_eq!(literal.get_fields().len(), 1);
+            let base = literal.get_base().as_ref().expect("Expected a base.");
+            assert_eq!(
+                unwrap_to!(**base => NLOperation::VariableAccess).get_name(),
+                "origin"
+            );
+
+            let code = "Point { ..origin }";
+            let operation = pretty_read(code, &read_operation);
+            let literal = unwrap_to!(operation => NLOperation::StructLiteral);
+            assert_eq!(literal.get_fields().len(), 0);
+            assert!(literal.get_base().is_some());
+        }
+
+        #[test]
+        fn not_in_conditions() {
+            // The braces after a condition are its block, whatever the case of the name in front of them.
+            for code in [
+                "if running {}",
+                "if Ready {}",
+                "while Ready {}",
+                "for x in Items {}",
+            ] {
+                let operation = pretty_read(code, &read_operation);
+                let condition = match &operation {
+                    NLOperation::If(if_statement) => if_statement.get_condition(),
+                    NLOperation::WhileLoop(while_loop) => while_loop.get_condition(),
+                    NLOperation::ForLoop(for_loop) => for_loop.get_iterator(),
+                    _ => panic!("Expected {} to be a block, got {:?}", code, operation),
+                };
+                assert!(
+                    matches!(condition, NLOperation::VariableAccess(_)),
+                    "{:?}",
+                    condition
+                );
+            }
+
+            let code = "match State {}";
+            let operation = pretty_read(code, &read_operation);
+            let nl_match = unwrap_to!(operation => NLOperation::Match);
+            assert_eq!(
+                unwrap_to!(*nl_match.input => NLOperation::VariableAccess).get_name(),
+                "State"
+            );
+
+            // Brackets allow them again.
+            let code = "if check(Point { x: 1 }) { Point { x: 2 } }";
+            let operation = pretty_read(code, &read_operation);
+            let if_statement = unwrap_to!(operation => NLOperation::If);
+            let call = unwrap_to!(if_statement.get_condition() => NLOperation::FunctionCall);
+            assert!(matches!(
+                call.get_arguments()[0],
+                NLOperation::StructLiteral(_)
+            ));
+            assert!(matches!(
+                if_statement.get_true_block().get_operations()[0],
+                NLOperation::StructLiteral(_)
+            ));
+
+            let code = "if p == (Point { x: 1 }) {}";
+            let operation = pretty_read(code, &read_operation);
+            let if_statement = unwrap_to!(operation => NLOperation::If);
+            assert!(matches!(
+                if_statement.get_condition(),
+                NLOperation::Operator(_)
+            ));
+        }
+
+        #[test]
+        fn field_after_base() {
+            let code = "Point { ..origin, y: 2 }";
+            match read_operation(code) {
+                Err(nom::Err::Failure(error)) => {
+                    let message = describe_error(code, error);
+                    assert!(
+                        message.contains("must come after all of its fields"),
+                        "{}",
+                        message
+                    );
+                }
+                _ => panic!("Expected the field after the base to be rejected."),
+            }
+        }
+    }
+
+    mod casts {
+        use super::*;
+
+        fn unwrap_cast<'a, 'b>(
+            operation: &'b NLOperation<'a>,
+        ) -> (&'b NLOperation<'a>, &'b NLType<'a>) {
+            match operation {
+                NLOperation::Cast { value, target } => (value, target),
+                _ => panic!("Expected a cast, got {:?}", operation),
+            }
+        }
+
+        #[test]
+        fn cast_variable() {
+            let code = "x as u64";
+            let operation = pretty_read(code, &read_operation);
+            let (value, target) = unwrap_cast(&operation);
+
+            assert_eq!(*target, NLType::U64);
+            assert_eq!(
+                unwrap_to!(*value => NLOperation::VariableAccess).get_name(),
+                "x"
+            );
+        }
+
+        #[test]
+        fn cast_function_call() {
+            let code = "foo() as i32";
+            let operation = pretty_read(code, &read_operation);
+            let (value, target) = unwrap_cast(&operation);
+
+            assert_eq!(*target, NLType::I32);
+            assert_eq!(unwrap_to!(*value => NLOperation::FunctionCall).path, "foo");
+        }
+
+        #[test]
+        fn nested_cast() {
+            let code = "a as i32 as i64";
+            let operation = pretty_read(code, &read_operation);
+            let (value, target) = unwrap_cast(&operation);
+            assert_eq!(*target, NLType::I64);
+
+            let (value, target) = unwrap_cast(value);
+            assert_eq!(*target, NLType::I32);
+            assert_eq!(
+                unwrap_to!(*value => NLOperation::VariableAccess).get_name(),
+                "a"
+            );
+        }
+
+        #[test]
+        fn cast_binds_tighter_than_operators() {
+            let code = "a.b + c as i64";
+            let operation = pretty_read(code, &read_operation);
+            let operator = unwrap_to!(operation => NLOperation::Operator);
+            let (a, b) = unwrap_to!(operator => OpOperator::ArithmeticAdd);
+
+            assert_eq!(
+                unwrap_to!(**a => NLOperation::VariableAccess).get_name(),
+                "a.b"
+            );
+            let (_, target) = unwrap_cast(b);
+            assert_eq!(*target, NLType::I64);
+        }
+    }
+
+    mod comma_lists {
+        use super::*;
+
+        #[test]
+        fn trailing_commas_accepted() {
+            let tuple = parse_expression("(1, 2,)").unwrap();
+            assert_eq!(unwrap_to!(tuple => NLOperation::Tuple).len(), 2);
+
+            let call = parse_expression("foo(a, b,)").unwrap();
+            assert_eq!(
+                unwrap_to!(call => NLOperation::FunctionCall)
+                    .arguments
+                    .len(),
+                2
+            );
+
+            let array = parse_expression("[1, 2,]").unwrap();
+            assert_eq!(unwrap_to!(array => NLOperation::ArrayLiteral).len(), 2);
+
+            assert_eq!(
+                parse_type("(i32, bool,)").unwrap(),
+                NLType::Tuple(vec![NLType::I32, NLType::Boolean])
+            );
+
+            let code = "struct Thing { a: i32, b: bool, }
+                        enum Choice { A, B, }
+                        fn test<T, U,>(a: i32, b: bool,) { let (x, y,) = (a, b) }";
+            let file = parse_string(code, "virtual_file").unwrap();
+            assert_eq!(file.structs[0].variables.len(), 2);
+            assert_eq!(file.enums[0].variants.len(), 2);
+            assert_eq!(file.functions[0].type_params.len(), 2);
+            assert_eq!(file.functions[0].arguments.len(), 2);
+
+            let with_comma = parse_expression("match x { 1 => a, _ => b, }").unwrap();
+            let without_comma = parse_expression("match x { 1 => a, _ => b }").unwrap();
+            assert_eq!(
+                unwrap_to!(with_comma => NLOperation::Match).branches.len(),
+                2
+            );
+            assert_eq!(with_comma, without_comma);
+        }
+
+        #[test]
+        fn keywords_are_not_calls() {
+            let code = "if (ready) {}";
+            let operation = parse_expression(code).unwrap();
+            let if_statement = unwrap_to!(operation => NLOperation::If);
+            assert!(matches!(
+                if_statement.get_condition(),
+                NLOperation::Tuple(_)
+            ));
+
+            let operation = parse_expression("while (running) {}").unwrap();
+            assert!(
+                matches!(operation, NLOperation::WhileLoop(_)),
+                "{:?}",
+                operation
+            );
+
+            let operation = parse_expression("match (a, b) {}").unwrap();
+            assert!(
+                matches!(operation, NLOperation::Match(_)),
+                "{:?}",
+                operation
+            );
+        }
+
+        #[test]
+        fn doubled_commas_rejected() {
+            assert!(parse_expression("(1,, 2)").is_err());
+            assert!(parse_expression("(,)").is_err());
+            assert!(parse_expression("foo(a,,)").is_err());
+            assert!(parse_expression("foo(,)").is_err());
+            assert!(parse_expression("[1,,]").is_err());
+            assert!(parse_type("(i32,,)").is_err());
+
+            assert!(parse_string("struct Thing { a: i32,, }", "virtual_file").is_err());
+            assert!(parse_string("enum Choice { A,, }", "virtual_file").is_err());
+            assert!(parse_string("fn test(a: i32,,) {}", "virtual_file").is_err());
+            assert!(parse_string("fn test<T,,>() {}", "virtual_file").is_err());
+        }
+
+        #[test]
+        fn nested_tuples() {
+            let tuple = parse_expression("((1, 2), foo(3, 4), 5)").unwrap();
+            let elements = unwrap_to!(tuple => NLOperation::Tuple);
+
+            assert_eq!(elements.len(), 3);
+            assert_eq!(unwrap_to!(elements[0] => NLOperation::Tuple).len(), 2);
+        }
+
+        #[test]
+        fn comments_between_elements() {
+            let call = parse_expression("foo(a /* comment */, b)").unwrap();
+            let call = unwrap_to!(call => NLOperation::FunctionCall);
+            assert_eq!(call.arguments.len(), 2);
+
+            let tuple = parse_expression("(1, // trailing\n 2)").unwrap();
+            let elements = unwrap_to!(tuple => NLOperation::Tuple);
+            assert_eq!(unwrap_constant_signed(&elements[1]), 2);
+
+            // Closing delimiters inside of a comment don't end the list.
+            let call = parse_expression("foo(/* ) */ a, b // )\n)").unwrap();
+            assert_eq!(
+                unwrap_to!(call => NLOperation::FunctionCall)
+                    .arguments
+                    .len(),
+                2
+            );
+            let array = parse_expression("[1 /* ] */, 2]").unwrap();
+            assert_eq!(unwrap_to!(array => NLOperation::ArrayLiteral).len(), 2);
+
+            assert_eq!(
+                parse_type("(i32 /* ) */, // last\n bool)").unwrap(),
+                NLType::Tuple(vec![NLType::I32, NLType::Boolean])
+            );
+
+            let code = "fn test(a: i32 /* ) */, // b is next\n b: bool /* , */) {}";
+            let file = parse_string(code, "virtual_file").unwrap();
+            assert_eq!(file.functions[0].arguments.len(), 2);
+        }
+    }
+
+    mod expressions {
+        use super::*;
+
+        #[test]
+        fn parse_expression_on_its_own() {
+            let operation = parse_expression("1 + 2").unwrap();
+            let operator = unwrap_to!(operation => NLOperation::Operator);
+            let (a, b) = unwrap_to!(operator => OpOperator::ArithmeticAdd);
+            assert_eq!(unwrap_constant_signed(a), 1, "Wrong value for constant.");
+            assert_eq!(unwrap_constant_signed(b), 2, "Wrong value for constant.");
+
+            let operation = parse_expression(" foo(bar) ").unwrap();
+            let function = unwrap_to!(operation => NLOperation::FunctionCall);
+            assert_eq!(function.path, "foo");
+            assert_eq!(function.arguments.len(), 1);
+        }
+
+        #[test]
+        fn parse_expression_trailing_text() {
+            let error = parse_expression("1 + 2 leftover").unwrap_err();
+            assert!(
+                error
+                    .to_string()
+                    .contains("Unexpected text after the expression."),
+                "{}",
+                error
+            );
+        }
+
+        #[test]
+        fn parse_statement_on_its_own() {
+            let operation = parse_statement("let x = 5;").unwrap();
+            let assignment = unwrap_to!(operation => NLOperation::Assign);
+            assert!(assignment.is_new, "Assignment should have been new.");
+            assert_eq!(assignment.to_assign.len(), 1);
+
+            // The semicolon is optional.
+            let operation = parse_statement(" foo(bar) ").unwrap();
+            unwrap_to!(operation => NLOperation::FunctionCall);
+
+            let error = parse_statement("a; b").unwrap_err();
+            assert!(
+                error
+                    .to_string()
+                    .contains("Unexpected text after the statement."),
+                "{}",
+                error
+            );
+        }
+
+        #[test]
+        fn lone_operator() {
+            for (code, operator) in [("+", "+"), (" * 2", "*"), ("== 3", "=="), ("-", "-")].iter() {
+                let error = parse_expression(code).unwrap_err();
+                let expected = format!("The operator {} is missing an operand.", operator);
+                assert!(error.to_string().contains(&expected), "{}", error);
+            }
+
+            let error = parse_statement("+;").unwrap_err();
+            assert!(
+                error.to_string().contains("missing an operand"),
+                "{}",
+                error
+            );
+        }
+    }
+
+    mod constant_folding {
+        use super::*;
+
+        fn fold(code: &str) -> NLOperation {
+            fold_constants(parse_expression(code).unwrap())
+        }
+
+        #[test]
+        fn arithmetic() {
+            assert_eq!(
+                fold("2 + 3 * 4"),
+                NLOperation::Constant(OpConstant::Signed(14, NLType::I32, false))
+            );
+            assert_eq!(
+                fold("(1u8 << 3) | 1u8"),
+                NLOperation::Constant(OpConstant::Unsigned(9, NLType::U8, true))
+            );
+            assert_eq!(
+                fold("1.5f64 * 2.0f64"),
+                NLOperation::Constant(OpConstant::Float64(3.0))
+            );
+        }
+
+        #[test]
+        fn booleans() {
+            assert_eq!(
+                fold("true && false"),
+                NLOperation::Constant(OpConstant::Boolean(false))
+            );
+            assert_eq!(
+                fold("!(2 > 3)"),
+                NLOperation::Constant(OpConstant::Boolean(true))
+            );
+        }
+
+        #[test]
+        fn partially_constant() {
+            // The constant half is folded, but the variable keeps the rest from being folded.
+            let operation = fold("x + 2 * 3");
+            let operator = unwrap_to!(operation => NLOperation::Operator);
+            let (a, b) = unwrap_to!(operator => OpOperator::ArithmeticAdd);
+            assert_eq!(
+                unwrap_to!(**a => NLOperation::VariableAccess).get_name(),
+                "x"
+            );
+            assert_eq!(unwrap_constant_signed(b), 6);
+        }
+
+        #[test]
+        fn literal_takes_other_type() {
+            // Literals without a suffix take the type of the other side, the same as the checker allows.
+            assert_eq!(
+                fold("250u8 + 5"),
+                NLOperation::Constant(OpConstant::Unsigned(255, NLType::U8, true))
+            );
+        }
+
+        #[test]
+        fn left_unfolded() {
+            // Dividing by zero, overflowing, and mixing types are all left for someone else to report.
+            for code in [
+                "1 / 0",
+                "250u8 + 10u8",
+                "250u8 + 10",
+                "1i32 + 1i64",
+                "1.0f32 / 0.0f32",
+            ]
+            .iter()
+            {
+                let operation = fold(code);
+                assert!(
+                    matches!(operation, NLOperation::Operator(_)),
+                    "{} folded into {:?}",
+                    code,
+                    operation
+                );
+            }
+        }
+    }
+}
+
+mod types {
+    use super::*;
+
+    #[test]
+    fn parse_type_on_its_own() {
+        assert_eq!(
+            parse_type("&mut SomeStruct").unwrap(),
+            NLType::MutableReferencedStruct("SomeStruct")
+        );
+        assert_eq!(
+            parse_type(" (i32, bool) ").unwrap(),
+            NLType::Tuple(vec![NLType::I32, NLType::Boolean])
+        );
+    }
+
+    #[test]
+    fn parse_type_trailing_text() {
+        let error = parse_type("i32 garbage").unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("Unexpected text after the type."),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn reference_helpers() {
+        let cases = [
+            (NLType::I32, false, false, NLType::I32, None),
+            (
+                NLType::BorrowedString,
+                true,
+                false,
+                NLType::BorrowedString,
+                None,
+            ),
+            (
+                NLType::OwnedStruct("Foo"),
+                false,
+                false,
+                NLType::OwnedStruct("Foo"),
+                Some("Foo"),
+            ),
+            (
+                NLType::ReferencedStruct("Foo"),
+                true,
+                false,
+                NLType::OwnedStruct("Foo"),
+                Some("Foo"),
+            ),
+            (
+                NLType::MutableReferencedStruct("Foo"),
+                true,
+                true,
+                NLType::OwnedStruct("Foo"),
+                Some("Foo"),
+            ),
+            (
+                NLType::OwnedTrait("Bar"),
+                false,
+                false,
+                NLType::OwnedTrait("Bar"),
+                Some("Bar"),
+            ),
+            (
+                NLType::ReferencedTrait("Bar"),
+                true,
+                false,
+                NLType::OwnedTrait("Bar"),
+                Some("Bar"),
+            ),
+            (
+                NLType::MutableReferencedTrait("Bar"),
+                true,
+                true,
+                NLType::OwnedTrait("Bar"),
+                Some("Bar"),
+            ),
+            (
+                NLType::SelfReference,
+                true,
+                false,
+                NLType::SelfReference,
+                None,
+            ),
+            (
+                NLType::MutableSelfReference,
+                true,
+                true,
+                NLType::SelfReference,
+                None,
+            ),
+        ];
+
+        for (nl_type, is_reference, is_mutable, stripped, base_name) in cases.iter() {
+            assert_eq!(nl_type.is_reference(), *is_reference, "{:?}", nl_type);
+            assert_eq!(nl_type.is_mutable_reference(), *is_mutable, "{:?}", nl_type);
+            assert_eq!(&nl_type.strip_reference(), stripped, "{:?}", nl_type);
+            assert_eq!(nl_type.base_name(), *base_name, "{:?}", nl_type);
+        }
+    }
+
+    #[test]
+    fn primitive_and_numeric() {
+        // The type, whether it's primitive, and whether it's numeric.
+        let cases = [
+            (NLType::I8, true, true),
+            (NLType::U64, true, true),
+            (NLType::F32, true, true),
+            (NLType::Boolean, true, false),
+            (NLType::OwnedString, true, false),
+            (NLType::BorrowedString, true, false),
+            (NLType::None, false, false),
+            (NLType::Tuple(vec![NLType::I32]), false, false),
+            (
+                NLType::Array(Box::new(NLType::I32), ArrayLen::Literal(2)),
+                false,
+                false,
+            ),
+            (NLType::OwnedStruct("Foo"), false, false),
+            (NLType::ReferencedTrait("Foo"), false, false),
+            (NLType::Enum("Foo"), false, false),
+            (NLType::SelfReference, false, false),
+        ];
+
+        for (nl_type, is_primitive, is_numeric) in cases.iter() {
+            assert_eq!(nl_type.is_primitive(), *is_primitive, "{:?}", nl_type);
+            assert_eq!(nl_type.is_numeric(), *is_numeric, "{:?}", nl_type);
+        }
+    }
+
+    #[test]
+    fn eq_ignoring_mutability() {
+        assert!(NLType::MutableReferencedStruct("Foo")
+            .eq_ignoring_mutability(&NLType::ReferencedStruct("Foo")));
+        assert!(NLType::MutableSelfReference.eq_ignoring_mutability(&NLType::SelfReference));
+        assert!(
+            !NLType::ReferencedStruct("Foo").eq_ignoring_mutability(&NLType::OwnedStruct("Foo"))
+        );
+        assert!(!NLType::ReferencedStruct("Foo")
+            .eq_ignoring_mutability(&NLType::ReferencedStruct("Bar")));
+    }
+
+    #[test]
+    fn type_from_str() {
+        assert_eq!("i32".parse::<NLType>().unwrap(), NLType::I32);
+        assert_eq!(NLType::from_str(" bool").unwrap(), NLType::Boolean);
+        assert_eq!(NLType::from_str("str").unwrap(), NLType::BorrowedString);
+
+        // The struct's name would have to be borrowed from the string.
+        let error = NLType::from_str("&Foo").unwrap_err();
+        assert!(error.to_string().contains("not a primitive"), "{}", error);
+    }
+
+    #[test]
+    fn tuple_type() {
+        let code = "(i32, bool)";
+        let nl_type = pretty_read(code, &read_variable_type);
+
+        assert_eq!(
+            nl_type,
+            NLType::Tuple(vec![NLType::I32, NLType::Boolean]),
+            "Wrong tuple type."
+        );
+    }
+
+    #[test]
+    fn nested_tuple_type() {
+        let code = "(i32, (bool, u8))";
+        let nl_type = pretty_read(code, &read_variable_type);
+
+        assert_eq!(
+            nl_type,
+            NLType::Tuple(vec![
+                NLType::I32,
+                NLType::Tuple(vec![NLType::Boolean, NLType::U8])
+            ]),
+            "Wrong tuple type."
+        );
+    }
+
+    #[test]
+    fn empty_tuple_type() {
+        let code = "()";
+        let nl_type = pretty_read(code, &read_variable_type);
+
+        assert_eq!(nl_type, NLType::Tuple(vec![]), "Wrong tuple type.");
+    }
+
+    #[test]
+    fn array_literal_length() {
+        let code = "[i32; 4]";
+        let nl_type = pretty_read(code, &read_variable_type);
+
+        assert_eq!(
+            nl_type,
+            NLType::Array(Box::new(NLType::I32), ArrayLen::Literal(4)),
+            "Wrong array type."
+        );
+    }
+
+    #[test]
+    fn array_named_length() {
+        let code = "[i32; N]";
+        let nl_type = pretty_read(code, &read_variable_type);
+
+        assert_eq!(
+            nl_type,
+            NLType::Array(Box::new(NLType::I32), ArrayLen::Named("N")),
+            "Wrong array type."
+        );
+    }
+
+    #[test]
+    fn owned_string() {
+        let nl_type = pretty_read("String", &read_variable_type);
+        assert_eq!(nl_type, NLType::OwnedString, "Wrong type.");
+
+        // A struct that just starts with the word String.
+        let nl_type = pretty_read("Strings", &read_variable_type);
+        assert_eq!(nl_type, NLType::OwnedStruct("Strings"), "Wrong type.");
+    }
+
+    #[test]
+    fn referenced_owned_string() {
+        for code in &["&String", "&mut String"] {
+            match read_variable_type(code) {
+                Err(nom::Err::Failure(error)) => {
+                    let message = convert_error(*code, error);
+                    assert!(message.contains("Use str to borrow"), "{}", message);
+                }
+                _ => panic!("Referenced String should have been rejected: {}", code),
+            }
+        }
+    }
+
+    #[test]
+    fn array_hexadecimal_length() {
+        let code = "[u8; 0x10]";
+        let nl_type = pretty_read(code, &read_variable_type);
+
+        assert_eq!(
+            nl_type,
+            NLType::Array(Box::new(NLType::U8), ArrayLen::Literal(16)),
+            "Wrong array type."
+        );
+    }
+
+    #[test]
+    fn array_separated_length() {
+        let code = "[u8; 1_000]";
+        let nl_type = pretty_read(code, &read_variable_type);
+
+        assert_eq!(
+            nl_type,
+            NLType::Array(Box::new(NLType::U8), ArrayLen::Literal(1000)),
+            "Wrong array type."
+        );
+    }
+}
+
+mod tokens {
+    use super::*;
+
+    fn token_at(tokens: &[SemanticToken], line: usize, start: usize) -> &SemanticToken {
+        tokens
+            .iter()
+            .find(|token| token.line == line && token.start == start)
+            .expect("No token at that position.")
+    }
+
+    #[test]
+    fn function_deceleration() {
+        let code = "// Adds numbers.\nfn add(a: i32) -> i32 { a + 5 }";
+        let tokens = semantic_tokens(code);
+
+        let comment = token_at(&tokens, 0, 0);
+        assert_eq!(comment.token_type, SemanticTokenType::Comment);
+        assert_eq!(comment.length, 16);
+
+        let keyword = token_at(&tokens, 1, 0);
+        assert_eq!(keyword.token_type, SemanticTokenType::Keyword);
+        assert_eq!(keyword.length, 2);
+
+        let name = token_at(&tokens, 1, 3);
+        assert_eq!(name.token_type, SemanticTokenType::Function);
+        assert_eq!(name.length, 3);
+
+        let argument = token_at(&tokens, 1, 7);
+        assert_eq!(argument.token_type, SemanticTokenType::Variable);
+
+        let argument_type = token_at(&tokens, 1, 10);
+        assert_eq!(argument_type.token_type, SemanticTokenType::Type);
+
+        let number = token_at(&tokens, 1, 28);
+        assert_eq!(number.token_type, SemanticTokenType::Number);
+    }
+
+    #[test]
+    fn multi_line_comment() {
+        let code = "/* one\ntwo */ struct A {}";
+        let tokens = semantic_tokens(code);
+
+        let first = token_at(&tokens, 0, 0);
+        assert_eq!(first.token_type, SemanticTokenType::Comment);
+        assert_eq!(first.length, 6);
+
+        let second = token_at(&tokens, 1, 0);
+        assert_eq!(second.token_type, SemanticTokenType::Comment);
+        assert_eq!(second.length, 6);
+
+        let name = token_at(&tokens, 1, 14);
+        assert_eq!(name.token_type, SemanticTokenType::Type);
+    }
+
+    #[test]
+    fn paths() {
+        let code = "a = Foo::bar + Foo::new()";
+        let tokens = semantic_tokens(code);
+
+        let path_type = token_at(&tokens, 0, 4);
+        assert_eq!(path_type.token_type, SemanticTokenType::Type);
+
+        let value = token_at(&tokens, 0, 9);
+        assert_eq!(value.token_type, SemanticTokenType::Variable);
+
+        let function = token_at(&tokens, 0, 20);
+        assert_eq!(function.token_type, SemanticTokenType::Function);
     }
 }